@@ -0,0 +1,107 @@
+//! The highs and lows forecast for each grid square, kept so `history
+//! --compare` has forecasts made days ahead to hold observations against.
+//!
+//! The response cache cannot serve for this: it holds the latest copy of a
+//! forecast and the last good one, and drops both after a day. Instead, each
+//! time the detailed forecast is fetched for a place, the periods not yet on
+//! record are added to a file for its grid square under the state directory.
+//! A period keeps the first forecast recorded for it, the longest-range one,
+//! and is dropped a week after it ends, past the reach of `history --hours`.
+
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+
+use anyhow::Result;
+use chrono::{DateTime, FixedOffset, TimeDelta, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::dirs;
+use crate::units::{celsius_to_fahrenheit, fahrenheit_to_celsius, Units};
+use crate::weatherdotgov::{Period, PointsProperties};
+
+/// How long a period stays on record after it ends: the longest `history
+/// --hours`, and a day to spare.
+const KEEP: TimeDelta = TimeDelta::days(8);
+
+/// A period's forecast high or low, as first recorded.
+#[derive(Clone, Debug, PartialEq, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Recorded {
+    pub name: String,
+    pub start_time: DateTime<FixedOffset>,
+    pub end_time: DateTime<FixedOffset>,
+    pub is_daytime: bool,
+    /// In °F whatever `--units` the forecast was fetched in, so the record
+    /// does not split by unit system.
+    pub fahrenheit: f64,
+}
+
+impl Recorded {
+    pub fn temperature(&self, units: Units) -> f64 {
+        match units {
+            Units::Us => self.fahrenheit,
+            Units::Si => fahrenheit_to_celsius(self.fahrenheit),
+        }
+    }
+}
+
+/// A grid square's record, keyed by period start time in RFC 3339.
+pub type Archive = BTreeMap<String, Recorded>;
+
+/// `$XDG_STATE_HOME/wxdotgov/forecasts/SEW-124-67.json` for the grid square
+/// `point` is in.
+pub fn file(point: &PointsProperties) -> Option<PathBuf> {
+    let name = format!("{}-{}-{}.json", point.grid_id, point.grid_x, point.grid_y);
+    Some(dirs::state_dir()?.join("forecasts").join(name))
+}
+
+/// Record the periods of a forecast fetched in `units` that are not on record
+/// yet, and drop those that ended more than [`KEEP`] before `now`.
+pub fn add(archive: &mut Archive, periods: &[Period], units: Units, now: DateTime<Utc>) {
+    for period in periods {
+        let Some(temperature) = period.temperature.map(f64::from) else {
+            continue;
+        };
+        let fahrenheit = match units {
+            Units::Us => temperature,
+            Units::Si => celsius_to_fahrenheit(temperature),
+        };
+        archive
+            .entry(period.start_time.to_rfc3339())
+            .or_insert_with(|| Recorded {
+                name: period.name.clone(),
+                start_time: period.start_time,
+                end_time: period.end_time,
+                is_daytime: period.is_daytime,
+                fahrenheit,
+            });
+    }
+    archive.retain(|_, recorded| {
+        recorded
+            .end_time
+            .checked_add_signed(KEEP)
+            .is_some_and(|until| until > now)
+    });
+}
+
+/// [`add`] to the record for `point`'s grid square. Without a state
+/// directory there is nowhere to keep it, and nothing is recorded.
+pub fn record(point: &PointsProperties, periods: &[Period], units: Units) -> Result<()> {
+    let Some(path) = file(point) else {
+        return Ok(());
+    };
+    let mut archive: Archive = dirs::load_state(&path)?;
+    add(&mut archive, periods, units, Utc::now());
+    dirs::save_state(&path, &archive)
+}
+
+/// Everything on record for `point`'s grid square, oldest period first.
+pub fn recorded(point: &PointsProperties) -> Result<Vec<Recorded>> {
+    let Some(path) = file(point) else {
+        return Ok(Vec::new());
+    };
+    let archive: Archive = dirs::load_state(&path)?;
+    let mut periods: Vec<Recorded> = archive.into_values().collect();
+    periods.sort_by_key(|p| p.start_time);
+    Ok(periods)
+}
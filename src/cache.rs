@@ -0,0 +1,364 @@
+//! Response cache, in memory for long-running modes and on disk for the CLI.
+//!
+//! `serve` answers many requests for the same few places, and without a cache
+//! every dashboard refresh would be a Nominatim lookup and two or three NWS
+//! requests. The server runs each request inside [`scope`] with its one shared
+//! [`Cache`]; the fetch functions consult [`lookup`] and [`store`], which do
+//! nothing outside a scope.
+//!
+//! One-shot commands run in a scope too, with a cache that also writes each
+//! entry to a file under [`default_dir`]. A status bar that runs `wxdotgov
+//! current` every minute then geocodes its place once a day and fetches the
+//! forecast once per forecast lifetime, rather than on every tick. `wxdotgov
+//! cache` reports on that directory and clears it.
+//!
+//! The cache is task-local rather than a global so tests running in parallel
+//! against reused mock servers never see another caller's responses.
+//!
+//! Entries are response bodies keyed by URL and query. How long one stays
+//! fresh is the caller's decision, made per lookup: a geocoded place is good
+//! for a day, an alert list for a minute.
+//!
+//! An entry past its freshness is kept until [`RETAIN`] along with the
+//! [`Validators`] it was served with, so the caller can ask the server whether
+//! it has changed ([`lookup_stale`]) instead of downloading it again.
+
+use std::collections::HashMap;
+use std::future::Future;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use anyhow::{bail, Context, Result};
+use serde::{Deserialize, Serialize};
+
+use crate::dirs;
+
+/// Entries older than this are dropped on the next insert, or on disk by the
+/// next sweep. It is the longest freshness any caller asks for, so nothing
+/// still usable is discarded.
+const RETAIN: Duration = Duration::from_secs(24 * 60 * 60);
+
+/// How often an insert sweeps the cache directory for files past [`RETAIN`].
+/// A status bar inserts every few minutes, and listing the directory each
+/// time would cost more than the lookups it saves.
+const SWEEP_EVERY: Duration = Duration::from_secs(60 * 60);
+
+/// Marks the last sweep by its modification time. Not a `.json` file, so
+/// `wxdotgov cache` neither counts nor clears it.
+const SWEPT: &str = "swept";
+
+#[derive(Deserialize, Serialize)]
+struct Entry {
+    /// Stored in each file too, so a hash collision reads as a miss.
+    key: String,
+    body: String,
+    /// Seconds since the Unix epoch. Wall-clock rather than `Instant`, as an
+    /// entry on disk must be dated across runs.
+    stored: u64,
+    /// Defaulted, so entries written before validators were kept still read.
+    #[serde(default)]
+    validators: Validators,
+}
+
+/// The `ETag` and `Last-Modified` a body was served with, sent back as
+/// `If-None-Match` and `If-Modified-Since` to revalidate it.
+#[derive(Clone, Debug, Default, Deserialize, Serialize, PartialEq, Eq)]
+pub struct Validators {
+    pub etag: Option<String>,
+    pub last_modified: Option<String>,
+}
+
+impl Entry {
+    fn age(&self) -> Duration {
+        Duration::from_secs(unix_now().saturating_sub(self.stored))
+    }
+}
+
+fn unix_now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |d| d.as_secs())
+}
+
+#[derive(Default)]
+pub struct Cache {
+    entries: Mutex<HashMap<String, Entry>>,
+    /// Where entries are also kept as files, or `None` for memory only.
+    dir: Option<PathBuf>,
+}
+
+impl Cache {
+    /// A cache that also writes every entry under `dir`, where the next run
+    /// will find it.
+    pub fn on_disk(dir: PathBuf) -> Self {
+        Cache {
+            entries: Mutex::default(),
+            dir: Some(dir),
+        }
+    }
+
+    /// The cache the one-shot commands share between runs: on disk under
+    /// [`default_dir`], or in memory when there is no home to put it in.
+    pub fn persistent() -> Self {
+        default_dir().map_or_else(Cache::default, Cache::on_disk)
+    }
+
+    /// The body stored under `key`, if it is younger than `max_age`.
+    pub fn get(&self, key: &str, max_age: Duration) -> Option<String> {
+        let entries = self.entries.lock().unwrap_or_else(|e| e.into_inner());
+        if let Some(entry) = entries.get(key) {
+            return (entry.age() < max_age).then(|| entry.body.clone());
+        }
+        drop(entries);
+        let dir = self.dir.as_ref()?;
+        read_entry(&dir.join(file_name(key)))
+            .filter(|entry| entry.key == key && entry.age() < max_age)
+            .map(|entry| entry.body)
+    }
+
+    /// The body stored under `key` at any age, with its validators.
+    pub fn get_stale(&self, key: &str) -> Option<(String, Validators)> {
+        let entries = self.entries.lock().unwrap_or_else(|e| e.into_inner());
+        if let Some(entry) = entries.get(key) {
+            return Some((entry.body.clone(), entry.validators.clone()));
+        }
+        drop(entries);
+        let dir = self.dir.as_ref()?;
+        read_entry(&dir.join(file_name(key)))
+            .filter(|entry| entry.key == key)
+            .map(|entry| (entry.body, entry.validators))
+    }
+
+    pub fn insert(&self, key: String, body: String) {
+        self.insert_validated(key, body, Validators::default());
+    }
+
+    /// Store `body` with the validators it was served with.
+    pub fn insert_validated(&self, key: String, body: String, validators: Validators) {
+        let entry = Entry {
+            key: key.clone(),
+            body,
+            stored: unix_now(),
+            validators,
+        };
+        if let Some(dir) = &self.dir {
+            // A cache that cannot be written costs the next run a fetch; it is
+            // no reason to fail this one.
+            let _ = write_entry(dir, &entry);
+        }
+        let mut entries = self.entries.lock().unwrap_or_else(|e| e.into_inner());
+        entries.retain(|_, entry| entry.age() < RETAIN);
+        entries.insert(key, entry);
+    }
+}
+
+/// Where [`Cache::persistent`] keeps its files: `$XDG_CACHE_HOME/wxdotgov`,
+/// or `~/.cache/wxdotgov`.
+pub fn default_dir() -> Option<PathBuf> {
+    dirs::cache_dir()
+}
+
+/// A hash of `text` that is the same in every build, for naming files. FNV-1a
+/// rather than `DefaultHasher`, whose output may change between Rust releases.
+pub fn stable_hash(text: &str) -> u64 {
+    text.bytes().fold(0xcbf2_9ce4_8422_2325, |hash, byte| {
+        (hash ^ u64::from(byte)).wrapping_mul(0x0000_0100_0000_01b3)
+    })
+}
+
+fn file_name(key: &str) -> String {
+    format!("{:016x}.json", stable_hash(key))
+}
+
+fn read_entry(path: &Path) -> Option<Entry> {
+    let text = std::fs::read_to_string(path).ok()?;
+    serde_json::from_str(&text).ok()
+}
+
+/// Write through a temporary file named for this process and write, so a
+/// concurrent run never reads half an entry nor renames another writer's half
+/// into place. Then [`sweep`] if the last one was [`SWEEP_EVERY`] ago.
+fn write_entry(dir: &Path, entry: &Entry) -> std::io::Result<()> {
+    static WRITES: AtomicU64 = AtomicU64::new(0);
+
+    std::fs::create_dir_all(dir)?;
+    let path = dir.join(file_name(&entry.key));
+    let write = WRITES.fetch_add(1, Ordering::Relaxed);
+    let partial = path.with_extension(format!("json.{}.{write}.tmp", std::process::id()));
+    std::fs::write(&partial, serde_json::to_string(entry)?)?;
+    if let Err(e) = std::fs::rename(&partial, &path) {
+        let _ = std::fs::remove_file(&partial);
+        return Err(e);
+    }
+
+    let swept = dir.join(SWEPT);
+    let due = std::fs::metadata(&swept)
+        .and_then(|meta| meta.modified())
+        .map_or(true, |at| {
+            at.elapsed().map_or(true, |age| age >= SWEEP_EVERY)
+        });
+    if due {
+        std::fs::write(&swept, "")?;
+        sweep(dir);
+    }
+    Ok(())
+}
+
+/// Drop the files in `dir` past [`RETAIN`], including temporary files a
+/// crashed run left behind. A file that cannot be removed, perhaps because a
+/// concurrent sweep got there first, is left for the next one.
+fn sweep(dir: &Path) {
+    let Ok(files) = std::fs::read_dir(dir) else {
+        return;
+    };
+    for file in files.flatten() {
+        let old = file
+            .metadata()
+            .and_then(|meta| meta.modified())
+            .is_ok_and(|at| at.elapsed().is_ok_and(|age| age >= RETAIN));
+        if old {
+            let _ = std::fs::remove_file(file.path());
+        }
+    }
+}
+
+/// What `wxdotgov cache` reports.
+#[derive(Debug, PartialEq, Eq)]
+pub struct Usage {
+    pub entries: usize,
+    pub bytes: u64,
+}
+
+/// Count the entries in `dir`. A directory that does not exist yet is empty.
+pub fn usage(dir: &Path) -> Result<Usage> {
+    let mut usage = Usage {
+        entries: 0,
+        bytes: 0,
+    };
+    for path in entry_files(dir)? {
+        usage.entries += 1;
+        usage.bytes += std::fs::metadata(&path)
+            .with_context(|| format!("Error reading {}", path.display()))?
+            .len();
+    }
+    Ok(usage)
+}
+
+/// Remove every entry in `dir`, returning how many there were.
+pub fn clear(dir: &Path) -> Result<usize> {
+    let files = entry_files(dir)?;
+    for path in &files {
+        std::fs::remove_file(path).with_context(|| format!("Error removing {}", path.display()))?;
+    }
+    Ok(files.len())
+}
+
+/// The entry files in `dir`. Only `.json` files are touched, so pointing
+/// `--clear` at the wrong directory cannot empty it.
+fn entry_files(dir: &Path) -> Result<Vec<PathBuf>> {
+    let files = match std::fs::read_dir(dir) {
+        Ok(files) => files,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(e) => return Err(e).with_context(|| format!("Error reading {}", dir.display())),
+    };
+    let mut paths = Vec::new();
+    for file in files {
+        let path = file
+            .with_context(|| format!("Error reading {}", dir.display()))?
+            .path();
+        if path.extension().is_some_and(|ext| ext == "json") {
+            paths.push(path);
+        }
+    }
+    Ok(paths)
+}
+
+#[derive(Debug, clap::Args)]
+pub struct CacheArgs {
+    /// Remove every cached response.
+    #[arg(long)]
+    clear: bool,
+}
+
+/// `wxdotgov cache`: where the cache is, and how much is in it.
+pub fn run(args: CacheArgs) -> Result<()> {
+    let Some(dir) = default_dir() else {
+        bail!("No cache directory: neither XDG_CACHE_HOME nor HOME is set");
+    };
+    if args.clear {
+        let removed = clear(&dir)?;
+        println!("Removed {removed} cached responses from {}", dir.display());
+    } else {
+        let usage = usage(&dir)?;
+        println!("{}", dir.display());
+        println!("{} cached responses, {} bytes", usage.entries, usage.bytes);
+    }
+    Ok(())
+}
+
+/// The cache key for a GET of `url` with `query`.
+pub fn key(url: &str, query: &[(&str, &str)]) -> String {
+    let mut key = url.to_string();
+    for (i, (name, value)) in query.iter().enumerate() {
+        key.push(if i == 0 { '?' } else { '&' });
+        key.push_str(&format!("{name}={value}"));
+    }
+    key
+}
+
+tokio::task_local! {
+    static CURRENT: Arc<Cache>;
+    static RELOADING: ();
+}
+
+/// Run `f` with `cache` as the cache for every fetch it makes.
+pub async fn scope<F: Future>(cache: Arc<Cache>, f: F) -> F::Output {
+    CURRENT.scope(cache, f).await
+}
+
+/// [`scope`], but with no entry fresh: every fetch in `f` goes to the origin
+/// ([`reloading`] says so) and stores what it gets for later lookups. For an
+/// explicit refresh, which should not be answered from the cache.
+pub async fn reload<F: Future>(cache: Arc<Cache>, f: F) -> F::Output {
+    CURRENT.scope(cache, RELOADING.scope((), f)).await
+}
+
+/// Whether this fetch runs inside [`reload`].
+pub fn reloading() -> bool {
+    RELOADING.try_with(|_| ()).is_ok()
+}
+
+/// A fresh cached body for `key`, when running inside [`scope`]. Inside
+/// [`reload`] nothing is fresh.
+pub fn lookup(key: &str, max_age: Duration) -> Option<String> {
+    if reloading() {
+        return None;
+    }
+    CURRENT
+        .try_with(|cache| cache.get(key, max_age))
+        .ok()
+        .flatten()
+}
+
+/// A cached body for `key` at any age, with the validators to ask the server
+/// whether it is still current, when running inside [`scope`].
+pub fn lookup_stale(key: &str) -> Option<(String, Validators)> {
+    CURRENT
+        .try_with(|cache| cache.get_stale(key))
+        .ok()
+        .flatten()
+}
+
+/// Remember `body` under `key`, when running inside [`scope`].
+pub fn store(key: &str, body: &str) {
+    // Outside a scope there is nowhere to store it, which is the point.
+    let _ = CURRENT.try_with(|cache| cache.insert(key.to_string(), body.to_string()));
+}
+
+/// [`store`], keeping the validators `body` was served with.
+pub fn store_validated(key: &str, body: &str, validators: Validators) {
+    let _ = CURRENT
+        .try_with(|cache| cache.insert_validated(key.to_string(), body.to_string(), validators));
+}
@@ -0,0 +1,218 @@
+//! `wxdotgov completions <shell>` and `wxdotgov man`.
+//!
+//! Both are generated from the clap definition of [`crate::Args`] at run
+//! time, so they always describe the binary that prints them:
+//!
+//! ```text
+//! wxdotgov completions bash > ~/.local/share/bash-completion/completions/wxdotgov
+//! wxdotgov completions zsh > ~/.zfunc/_wxdotgov
+//! wxdotgov man > ~/.local/share/man/man1/wxdotgov.1
+//! ```
+//!
+//! Flags, subcommands and `value_enum` choices such as `--units` complete in
+//! every shell. In bash, zsh and fish, `--zip`, `--city`, `--point`,
+//! `--station` and `--use` also complete from the locations and stations
+//! saved with `wxdotgov stations --use`: the script asks the binary for them
+//! with the hidden `wxdotgov completions --saved <kind>`. clap_complete's own
+//! dynamic completion is still unstable, so the script is the static one with
+//! a little glue added per shell. PowerShell and elvish get the static script
+//! alone.
+
+use std::io::Write;
+
+use anyhow::{Context, Result};
+use clap::ValueEnum;
+use clap_complete::Shell;
+
+use crate::stations::{self, Choices};
+
+#[derive(Debug, clap::Args)]
+pub struct CompletionsArgs {
+    /// The shell to generate completions for.
+    #[arg(value_enum, required_unless_present = "saved")]
+    shell: Option<Shell>,
+
+    /// Print the saved values the completion script offers for one option,
+    /// one per line.
+    #[arg(long, value_enum, hide = true, conflicts_with = "shell")]
+    saved: Option<Saved>,
+}
+
+/// The options that complete from saved locations and stations.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, ValueEnum)]
+pub enum Saved {
+    Zip,
+    City,
+    Point,
+    Station,
+}
+
+impl Saved {
+    /// The `value_name` zsh's script shows for the option's value, which
+    /// [`zsh_glue`] finds its completer by.
+    fn value_name(self) -> &'static str {
+        match self {
+            Saved::Zip => "ZIP",
+            Saved::City => "CITY",
+            Saved::Point => "LAT,LON",
+            Saved::Station => "STATION",
+        }
+    }
+}
+
+/// Print the completion script for `args.shell`, or with `--saved`, the
+/// saved values for one option.
+pub fn run(args: &CompletionsArgs, command: &mut clap::Command, out: &mut dyn Write) -> Result<()> {
+    match (args.shell, args.saved) {
+        (_, Some(kind)) => {
+            let Some(path) = stations::default_file() else {
+                return Ok(());
+            };
+            // A broken state file should not spill errors into the prompt.
+            let choices = crate::dirs::load_state::<Choices>(&path).unwrap_or_default();
+            for value in saved(&choices, kind) {
+                writeln!(out, "{value}")?;
+            }
+        }
+        (Some(shell), None) => write_completions(shell, command, out)?,
+        (None, None) => unreachable!("clap requires a shell without --saved"),
+    }
+    Ok(())
+}
+
+/// Write the completion script for `shell` to `out`.
+pub fn write_completions(
+    shell: Shell,
+    command: &mut clap::Command,
+    out: &mut dyn Write,
+) -> Result<()> {
+    let name = command.get_name().to_string();
+    let mut script = Vec::new();
+    clap_complete::generate(shell, command, &name, &mut script);
+    let script = String::from_utf8(script).context("Error generating completions")?;
+    let script = match shell {
+        Shell::Bash => script + &bash_glue(&name),
+        Shell::Zsh => zsh_glue(&script, &name),
+        Shell::Fish => script + &fish_glue(&name),
+        _ => script,
+    };
+    out.write_all(script.as_bytes())
+        .context("Error writing completions")
+}
+
+/// The values saved for `kind`, sorted and without repeats. Locations are
+/// saved by their label, so a ZIP code is a label of digits, a point one
+/// that parses as `--point`, and a city the part of any other before its
+/// state.
+pub fn saved(choices: &Choices, kind: Saved) -> Vec<String> {
+    let mut values: Vec<String> = match kind {
+        Saved::Station => choices.values().cloned().collect(),
+        _ => choices
+            .keys()
+            .filter_map(|label| {
+                let label_kind = if crate::parse_point(label).is_ok() {
+                    Saved::Point
+                } else if label.chars().all(|c| c.is_ascii_digit()) {
+                    Saved::Zip
+                } else {
+                    Saved::City
+                };
+                let value = match label_kind {
+                    Saved::City => label
+                        .rsplit_once(", ")
+                        .map_or(label.as_str(), |(city, _)| city),
+                    _ => label,
+                };
+                (label_kind == kind).then(|| value.to_string())
+            })
+            .collect(),
+    };
+    values.sort();
+    values.dedup();
+    values
+}
+
+/// Wraps bash's completer: after a saved-value option, offer the saved
+/// values, and otherwise defer to the generated `_wxdotgov`.
+fn bash_glue(name: &str) -> String {
+    format!(
+        r#"
+_{name}_saved() {{
+    local kind
+    case "${{COMP_WORDS[COMP_CWORD-1]}}" in
+        -z|--zip) kind=zip ;;
+        -c|--city) kind=city ;;
+        --point) kind=point ;;
+        --station|--use) kind=station ;;
+        *) _{name} "$@"; return ;;
+    esac
+    local IFS=$'\n'
+    COMPREPLY=($(compgen -W "$({name} completions --saved "${{kind}}" 2>/dev/null)" -- "${{COMP_WORDS[COMP_CWORD]}}"))
+    COMPREPLY=("${{COMPREPLY[@]// /\\ }}")
+}}
+
+complete -F _{name}_saved -o bashdefault -o default {name}
+"#
+    )
+}
+
+/// zsh's script names a completer for each option's value in its
+/// `_arguments` specs, `_default` for free text. Point those of the
+/// saved-value options at one that asks for the saved values, defined before
+/// the script's last lines call `_wxdotgov`.
+fn zsh_glue(script: &str, name: &str) -> String {
+    let mut script = script.to_string();
+    for kind in Saved::value_variants() {
+        let kind_name = kind.to_possible_value().expect("no variant is skipped");
+        script = script.replace(
+            &format!(":{}:_default'", kind.value_name()),
+            &format!(
+                ":{}:_{name}_saved {}'",
+                kind.value_name(),
+                kind_name.get_name()
+            ),
+        );
+    }
+    let completer = format!(
+        r#"(( $+functions[_{name}_saved] )) ||
+_{name}_saved() {{
+    local -a saved
+    saved=(${{(f)"$({name} completions --saved $1 2>/dev/null)"}})
+    compadd -a saved
+}}
+
+"#
+    );
+    let tail = format!("if [ \"$funcstack[1]\" = \"_{name}\" ]; then");
+    match script.find(&tail) {
+        Some(at) => script.insert_str(at, &completer),
+        None => script.push_str(&completer),
+    }
+    script
+}
+
+/// fish gathers an option's values from every `complete` line for it, so
+/// the saved values are extra lines alongside the generated ones.
+fn fish_glue(name: &str) -> String {
+    let mut glue = String::from("\n");
+    for (short, long, kind) in [
+        (Some('z'), "zip", "zip"),
+        (Some('c'), "city", "city"),
+        (None, "point", "point"),
+        (None, "station", "station"),
+        (None, "use", "station"),
+    ] {
+        let short = short.map(|c| format!(" -s {c}")).unwrap_or_default();
+        glue += &format!(
+            "complete -c {name}{short} -l {long} -x -a '({name} completions --saved {kind} 2>/dev/null)'\n"
+        );
+    }
+    glue
+}
+
+/// Write the man page, in roff, to `out`.
+pub fn write_man(command: clap::Command, out: &mut dyn Write) -> Result<()> {
+    clap_mangen::Man::new(command)
+        .render(out)
+        .context("Error writing man page")
+}
@@ -0,0 +1,57 @@
+//! Where files outlive a run, following the XDG base directory spec: an unset
+//! or empty variable falls back to its default under `$HOME`. `None` means
+//! neither is set, which callers report in their own terms.
+//!
+//! State files are JSON, read with [`load_state`] and written with
+//! [`save_state`].
+
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context, Result};
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+
+fn xdg(var: &str, default: &str) -> Option<PathBuf> {
+    let base = match std::env::var_os(var) {
+        Some(dir) if !dir.is_empty() => PathBuf::from(dir),
+        _ => PathBuf::from(std::env::var_os("HOME")?).join(default),
+    };
+    Some(base.join("wxdotgov"))
+}
+
+/// `$XDG_CACHE_HOME/wxdotgov`: files that can be deleted at any time.
+pub fn cache_dir() -> Option<PathBuf> {
+    xdg("XDG_CACHE_HOME", ".cache")
+}
+
+/// `$XDG_STATE_HOME/wxdotgov`: files whose loss changes behaviour, such as
+/// which alerts have been delivered.
+pub fn state_dir() -> Option<PathBuf> {
+    xdg("XDG_STATE_HOME", ".local/state")
+}
+
+/// Read a state file, or `T::default()` when there is none yet.
+pub fn load_state<T: DeserializeOwned + Default>(path: &Path) -> Result<T> {
+    match std::fs::read_to_string(path) {
+        Ok(text) => serde_json::from_str(&text)
+            .with_context(|| format!("Error parsing state file {}", path.display())),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(T::default()),
+        Err(e) => Err(e).with_context(|| format!("Error reading state file {}", path.display())),
+    }
+}
+
+/// Write a state file through a temporary file of this process's own, so
+/// neither a crash mid-write nor another run writing at the same time can
+/// leave it truncated.
+pub fn save_state<T: Serialize>(path: &Path, state: &T) -> Result<()> {
+    if let Some(dir) = path.parent() {
+        std::fs::create_dir_all(dir)
+            .with_context(|| format!("Error creating {}", dir.display()))?;
+    }
+    let text = serde_json::to_string_pretty(state).context("Error serializing state")?;
+    let partial = path.with_extension(format!("json.{}.tmp", std::process::id()));
+    std::fs::write(&partial, text)
+        .with_context(|| format!("Error writing state file {}", partial.display()))?;
+    std::fs::rename(&partial, path)
+        .with_context(|| format!("Error writing state file {}", path.display()))
+}
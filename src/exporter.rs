@@ -0,0 +1,310 @@
+//! `wxdotgov exporter`: forecast readings as Prometheus metrics.
+//!
+//! Fetches the hourly forecast and active alerts for each `--location` on a
+//! timer and serves the latest readings at `/metrics`, in place of scraping
+//! the text output:
+//!
+//! ```text
+//! wxdotgov_temperature_celsius{location="98101"} 8
+//! wxdotgov_dewpoint_celsius{location="98101"} 5
+//! wxdotgov_precip_probability{location="98101"} 0.4
+//! wxdotgov_active_alerts{location="98101",severity="moderate"} 1
+//! ```
+//!
+//! Readings are for the hourly period in progress, so they are forecast
+//! values, not observations. Values are SI whatever `--units` would say, as
+//! Prometheus convention asks: Celsius, and probabilities as 0-1 ratios.
+//!
+//! The `location` label is the `--location` argument as given, so it stays
+//! stable however Nominatim happens to name the place. A location that fails
+//! to refresh keeps its previous reading, and counts the failure in
+//! `wxdotgov_refresh_errors_total`; `wxdotgov_last_refresh_timestamp_seconds`
+//! shows how old the reading is. The upstream request counters from
+//! [`crate::http`] are exported alongside.
+//!
+//! Refreshes share one [`crate::cache`], so geocoding and grid point lookups
+//! happen once a day rather than once per refresh.
+
+use std::net::SocketAddr;
+use std::str::FromStr;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use anyhow::{Context, Result};
+use axum::extract::State;
+use axum::http::header;
+use axum::response::IntoResponse;
+use axum::routing::get;
+use axum::Router;
+use chrono::{DateTime, Utc};
+
+use crate::cache::{self, Cache};
+use crate::http;
+use crate::metrics::Exposition;
+use crate::nominatim::get_lat_lon;
+use crate::render::{RenderOptions, Style};
+use crate::status::current_period;
+use crate::times::{parse_refresh_interval, DisplayZone, TimeDisplay, TimeFormat};
+use crate::units::Units;
+use crate::weatherdotgov::{get_active_alerts, get_hourly_forecast, get_weather_point, Severity};
+use crate::{parse_zip, LocationInput};
+
+#[derive(Debug, clap::Args)]
+pub struct ExporterArgs {
+    /// A location to export: a ZIP code, "City" or "City, ST". Repeat for
+    /// more than one.
+    #[arg(long = "location", value_name = "PLACE", required = true)]
+    locations: Vec<Target>,
+
+    /// Address to serve /metrics on.
+    #[arg(long, default_value = "127.0.0.1:9184")]
+    listen: SocketAddr,
+
+    /// How often to refresh, e.g. 90s, 10m, 1h. At least 1m.
+    #[arg(long, default_value = "10m", value_parser = parse_refresh_interval)]
+    interval: Duration,
+}
+
+/// A `--location`: the label it is exported under and the place to look up.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Target {
+    pub label: String,
+    pub input: LocationInput,
+}
+
+impl FromStr for Target {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let label = s.trim().to_string();
+        let input = if label.starts_with(|c: char| c.is_ascii_digit()) {
+            parse_zip(&label)?
+        } else {
+            match label.rsplit_once(',') {
+                Some((city, state)) => {
+                    LocationInput::CityWithState(city.trim().to_string(), state.trim().to_string())
+                }
+                None => LocationInput::City(label.clone()),
+            }
+        };
+        Ok(Target { label, input })
+    }
+}
+
+/// One location's values from the period in progress.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Reading {
+    pub temperature: f64,
+    pub dewpoint: Option<f64>,
+    pub precip_probability: Option<f64>,
+    /// Active alerts per severity, in [`Severity::ALL`] order.
+    pub alerts: [usize; Severity::ALL.len()],
+}
+
+#[derive(Default)]
+struct Status {
+    reading: Option<Reading>,
+    refreshed: Option<DateTime<Utc>>,
+    errors: u64,
+}
+
+/// The locations being exported and their latest readings.
+pub struct Exporter {
+    targets: Vec<Target>,
+    statuses: Mutex<Vec<Status>>,
+    cache: Arc<Cache>,
+    /// Upstream base URLs, `None` for the real services. Tests point these at
+    /// a mock server.
+    pub nominatim_url: Option<String>,
+    pub weather_url: Option<String>,
+}
+
+impl Exporter {
+    pub fn new(targets: Vec<Target>) -> Self {
+        let statuses = targets.iter().map(|_| Status::default()).collect();
+        Exporter {
+            targets,
+            statuses: Mutex::new(statuses),
+            cache: Arc::default(),
+            nominatim_url: None,
+            weather_url: None,
+        }
+    }
+
+    async fn read(&self, target: &Target) -> Result<Reading> {
+        let location = get_lat_lon(target.input.clone(), self.nominatim_url.as_deref()).await?;
+        let point = get_weather_point(&location.lat, &location.lon, self.weather_url.as_deref())
+            .await?
+            .properties;
+        let url = point
+            .forecast_hourly
+            .as_ref()
+            .context("Hourly forecast not available for this location")?;
+        let periods = get_hourly_forecast(url, Units::Si)
+            .await?
+            .properties
+            .periods;
+        let alerts =
+            get_active_alerts(&location.lat, &location.lon, self.weather_url.as_deref()).await?;
+
+        let opts = RenderOptions {
+            style: Style::Plain,
+            limit: None,
+            units: Units::Si,
+            times: TimeDisplay::new(TimeFormat::Iso, DisplayZone::Location),
+        };
+        let period = current_period(&periods, &opts).context("The forecast has no periods")?;
+        let si = |value: &Option<crate::units::QuantitativeValue>| {
+            value
+                .as_ref()
+                .and_then(|v| v.to_units(Units::Si))
+                .map(|m| m.value)
+        };
+
+        let mut counts = [0; Severity::ALL.len()];
+        for alert in &alerts {
+            let i = Severity::ALL
+                .iter()
+                .position(|s| *s == alert.severity)
+                .expect("ALL lists every severity");
+            counts[i] += 1;
+        }
+
+        Ok(Reading {
+            temperature: f64::from(period.temperature),
+            dewpoint: si(&period.dewpoint),
+            precip_probability: si(&period.probability_of_precipitation).map(|p| p / 100.0),
+            alerts: counts,
+        })
+    }
+
+    /// Refresh every location once. Failures are logged and counted rather
+    /// than returned, so one bad location does not stop the others.
+    pub async fn refresh(&self) {
+        for (i, target) in self.targets.iter().enumerate() {
+            let result = cache::scope(self.cache.clone(), self.read(target)).await;
+            let mut statuses = self.statuses.lock().unwrap_or_else(|e| e.into_inner());
+            let status = &mut statuses[i];
+            match result {
+                Ok(reading) => {
+                    status.reading = Some(reading);
+                    status.refreshed = Some(Utc::now());
+                }
+                Err(e) => {
+                    eprintln!("warning: refreshing {}: {e:#}", target.label);
+                    status.errors += 1;
+                }
+            }
+        }
+    }
+
+    /// The `/metrics` page.
+    pub fn metrics(&self) -> String {
+        let statuses = self.statuses.lock().unwrap_or_else(|e| e.into_inner());
+        let rows: Vec<(&str, &Status)> = self
+            .targets
+            .iter()
+            .map(|t| t.label.as_str())
+            .zip(statuses.iter())
+            .collect();
+        let mut out = Exposition::default();
+
+        let gauge =
+            |out: &mut Exposition, name: &str, help: &str, value: fn(&Reading) -> Option<f64>| {
+                out.family(name, "gauge", help);
+                for (label, status) in &rows {
+                    if let Some(v) = status.reading.as_ref().and_then(value) {
+                        out.sample(name, &[("location", label)], v);
+                    }
+                }
+            };
+        gauge(
+            &mut out,
+            "wxdotgov_temperature_celsius",
+            "Forecast temperature for the current hour.",
+            |r| Some(r.temperature),
+        );
+        gauge(
+            &mut out,
+            "wxdotgov_dewpoint_celsius",
+            "Forecast dewpoint for the current hour.",
+            |r| r.dewpoint,
+        );
+        gauge(
+            &mut out,
+            "wxdotgov_precip_probability",
+            "Chance of precipitation for the current hour, 0 to 1.",
+            |r| r.precip_probability,
+        );
+
+        let name = "wxdotgov_active_alerts";
+        out.family(name, "gauge", "Active NWS alerts by severity.");
+        for (label, status) in &rows {
+            if let Some(reading) = &status.reading {
+                for (severity, count) in Severity::ALL.iter().zip(reading.alerts) {
+                    let labels = [("location", *label), ("severity", severity.as_str())];
+                    out.sample(name, &labels, count as f64);
+                }
+            }
+        }
+
+        let name = "wxdotgov_last_refresh_timestamp_seconds";
+        out.family(
+            name,
+            "gauge",
+            "When the location last refreshed successfully.",
+        );
+        for (label, status) in &rows {
+            if let Some(t) = status.refreshed {
+                out.sample(name, &[("location", label)], t.timestamp() as f64);
+            }
+        }
+
+        let name = "wxdotgov_refresh_errors_total";
+        out.family(name, "counter", "Refreshes that failed for the location.");
+        for (label, status) in &rows {
+            out.sample(name, &[("location", label)], status.errors as f64);
+        }
+
+        http::write_metrics(&mut out);
+        out.finish()
+    }
+}
+
+async fn metrics(State(exporter): State<Arc<Exporter>>) -> impl IntoResponse {
+    (
+        [(header::CONTENT_TYPE, "text/plain; version=0.0.4")],
+        exporter.metrics(),
+    )
+}
+
+pub fn router(exporter: Arc<Exporter>) -> Router {
+    Router::new()
+        .route("/metrics", get(metrics))
+        .with_state(exporter)
+}
+
+/// Refresh on a timer and serve `/metrics` until the process is stopped.
+pub async fn run(args: ExporterArgs) -> Result<()> {
+    let exporter = Arc::new(Exporter::new(args.locations));
+    let listener = tokio::net::TcpListener::bind(args.listen)
+        .await
+        .with_context(|| format!("Error listening on {}", args.listen))?;
+    eprintln!("Serving metrics on http://{}/metrics", args.listen);
+
+    let refresher = exporter.clone();
+    tokio::spawn(async move {
+        let mut timer = tokio::time::interval(args.interval);
+        // A refresh that overruns the interval is followed by a full
+        // interval, not a burst of catch-up refreshes.
+        timer.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+        loop {
+            timer.tick().await;
+            refresher.refresh().await;
+        }
+    });
+
+    axum::serve(listener, router(exporter))
+        .await
+        .context("Error serving HTTP")
+}
@@ -0,0 +1,95 @@
+//! Selecting which forecast periods to show by time.
+//!
+//! `--limit` caps how many periods print; the filters here decide which ones
+//! are eligible in the first place. They run before rendering, on both the
+//! detailed and hourly lists, so the renderer only ever sees the periods the
+//! user asked about and reports the rest as filtered rather than limited.
+//!
+//! A period is kept if any part of it falls inside the window, so
+//! `--from now` keeps the period in progress rather than starting at the next
+//! one.
+
+use anyhow::Result;
+use chrono::{DateTime, FixedOffset};
+
+use crate::times::{TimeDisplay, TimeSpec};
+use crate::weatherdotgov::{HourlyPeriod, Period};
+
+/// What the filters need to know about a period.
+pub trait Timed {
+    fn start_time(&self) -> &DateTime<FixedOffset>;
+    fn end_time(&self) -> &DateTime<FixedOffset>;
+    fn is_daytime(&self) -> bool;
+}
+
+impl Timed for Period {
+    fn start_time(&self) -> &DateTime<FixedOffset> {
+        &self.start_time
+    }
+    fn end_time(&self) -> &DateTime<FixedOffset> {
+        &self.end_time
+    }
+    fn is_daytime(&self) -> bool {
+        self.is_daytime
+    }
+}
+
+impl Timed for HourlyPeriod {
+    fn start_time(&self) -> &DateTime<FixedOffset> {
+        &self.start_time
+    }
+    fn end_time(&self) -> &DateTime<FixedOffset> {
+        &self.end_time
+    }
+    fn is_daytime(&self) -> bool {
+        self.is_daytime
+    }
+}
+
+/// Day or night, as the API's `isDaytime` defines them (6 AM to 6 PM).
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum DayPart {
+    Day,
+    Night,
+}
+
+/// The time-based filters from the command line. The default keeps everything.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct PeriodFilter {
+    pub from: Option<TimeSpec>,
+    pub until: Option<TimeSpec>,
+    pub part: Option<DayPart>,
+}
+
+impl PeriodFilter {
+    /// Drop the periods outside the filter, returning how many were dropped.
+    ///
+    /// `times` supplies "now" and the zone relative and wall-clock bounds are
+    /// read in; for the location's zone that is the first period's offset.
+    pub fn apply<T: Timed>(&self, periods: &mut Vec<T>, times: &TimeDisplay) -> Result<usize> {
+        let offset = times.offset(periods.first().map(Timed::start_time));
+        let from = self
+            .from
+            .as_ref()
+            .map(|s| s.resolve(times.now, offset))
+            .transpose()?;
+        let until = self
+            .until
+            .as_ref()
+            .map(|s| s.resolve(times.now, offset))
+            .transpose()?;
+
+        let before = periods.len();
+        periods.retain(|p| {
+            let after_from = from.is_none_or(|from| *p.end_time() > from);
+            let before_until = until.is_none_or(|until| *p.start_time() < until);
+            let in_part = match self.part {
+                None => true,
+                Some(DayPart::Day) => p.is_daytime(),
+                Some(DayPart::Night) => !p.is_daytime(),
+            };
+            after_from && before_until && in_part
+        });
+        Ok(before - periods.len())
+    }
+}
@@ -8,10 +8,29 @@
 //!
 //! `reqwest::Client` owns a connection pool and is designed to be built once and
 //! reused, so it is cached here rather than constructed per request.
+//!
+//! Request pacing lives here too, for the same reason: a host's published
+//! rate limit is a property of the host, not of whichever call site happens to
+//! reach it first. Each paced host has a token bucket, shared by every task
+//! and, through a file, by consecutive runs. [`send`] waits on it, so no call
+//! site can forget to.
+//!
+//! The per-upstream request counters the exporter publishes are kept here as
+//! well, since they are only complete if every request goes through [`send`].
+//! `--record` and `--replay` hook in at [`send`] for the same reason.
 
 use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
 use std::sync::OnceLock;
 use std::time::Duration;
+use tokio::sync::Mutex;
+use tokio::time::Instant;
+
+use crate::dirs;
+use crate::metrics::Exposition;
+use crate::tape::{self, RecordedRequest, Tape};
 
 /// Contact point advertised to upstream APIs. Both services accept a project URL
 /// in place of an email address.
@@ -61,6 +80,350 @@ pub fn client() -> Result<&'static reqwest::Client> {
     Ok(CLIENT.get_or_init(|| client))
 }
 
+/// Environment variable setting a request rate for api.weather.gov, in
+/// requests per second. The NWS publishes no number, so by default it is not
+/// paced; operators sharing an address with other clients can set one.
+const NWS_RATE_ENV: &str = "WXDOTGOV_NWS_RATE";
+
+const NOMINATIM_HOST: &str = "nominatim.openstreetmap.org";
+const NWS_HOST: &str = "api.weather.gov";
+
+/// A token bucket's shape: `rate` requests a second on average, and at most
+/// `burst` back to back after a quiet spell.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Limit {
+    pub rate: f64,
+    pub burst: f64,
+}
+
+impl Limit {
+    /// Nominatim's usage policy: "an absolute maximum of 1 request per
+    /// second", so no burst either.
+    const NOMINATIM: Limit = Limit {
+        rate: 1.0,
+        burst: 1.0,
+    };
+}
+
+/// Parse a [`NWS_RATE_ENV`] value. A burst of one second's worth, and at
+/// least one request, is allowed.
+pub fn parse_rate(text: &str) -> Result<Limit> {
+    let rate: f64 = text
+        .trim()
+        .parse()
+        .ok()
+        .filter(|rate: &f64| rate.is_finite() && *rate > 0.0)
+        .with_context(|| format!("'{text}' is not a positive number of requests per second"))?;
+    Ok(Limit {
+        rate,
+        burst: rate.max(1.0),
+    })
+}
+
+/// The limit requests to `host` are held to, if any.
+fn limit_for(host: &str) -> Option<Limit> {
+    static NWS: OnceLock<Option<Limit>> = OnceLock::new();
+    match host {
+        NOMINATIM_HOST => Some(Limit::NOMINATIM),
+        NWS_HOST => *NWS.get_or_init(|| {
+            let text = std::env::var(NWS_RATE_ENV).ok()?;
+            parse_rate(&text)
+                .inspect_err(|e| eprintln!("warning: ignoring {NWS_RATE_ENV}: {e:#}"))
+                .ok()
+        }),
+        _ => None,
+    }
+}
+
+/// A host's bucket: how many requests may be sent now, as of when.
+///
+/// `tokens` goes negative when requests are queued: each caller reserves a
+/// token and waits until it would have been there, so callers never need to
+/// hold a lock while they wait.
+#[derive(Clone, Copy, Debug, Deserialize, PartialEq, Serialize)]
+pub struct Bucket {
+    pub tokens: f64,
+    /// Seconds since the Unix epoch, so the bucket means the same to the next
+    /// process that reads it.
+    pub updated: f64,
+}
+
+impl Bucket {
+    pub fn full(limit: Limit) -> Self {
+        Bucket {
+            tokens: limit.burst,
+            updated: 0.0,
+        }
+    }
+
+    /// Reserve a request at `now`, returning how long to wait before sending
+    /// it.
+    pub fn take(&mut self, limit: Limit, now: f64) -> Duration {
+        let elapsed = (now - self.updated).max(0.0);
+        self.tokens = (self.tokens + elapsed * limit.rate).min(limit.burst) - 1.0;
+        self.updated = now;
+        if self.tokens >= 0.0 {
+            Duration::ZERO
+        } else {
+            Duration::from_secs_f64(-self.tokens / limit.rate)
+        }
+    }
+}
+
+fn unix_now() -> f64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map_or(0.0, |d| d.as_secs_f64())
+}
+
+/// Each paced host's bucket in this process. One lock for all hosts is fine:
+/// it is only held while a bucket is read and written, never while waiting.
+static BUCKETS: OnceLock<Mutex<HashMap<String, Bucket>>> = OnceLock::new();
+
+/// Wait until a request to `url` is within its host's limit.
+///
+/// Buckets are shared by every task in the process and, through a file per
+/// host under the state directory, by every process: a shell loop running
+/// `wxdotgov` once per ZIP code is paced just as `serve` is.
+async fn throttle(url: &reqwest::Url) {
+    let Some(host) = url.host_str() else {
+        return;
+    };
+    let Some(limit) = limit_for(host) else {
+        return;
+    };
+    let dir = dirs::state_dir().map(|dir| dir.join("ratelimit"));
+    let wait = reserve(host, limit, dir.as_deref()).await;
+    tokio::time::sleep(wait).await;
+}
+
+/// Take a token from `host`'s bucket, returning how long to wait before the
+/// request. With a `dir`, the bucket saved there by any process is the one
+/// drawn from, and the result is saved back for the next.
+pub async fn reserve(host: &str, limit: Limit, dir: Option<&Path>) -> Duration {
+    // The file lock can take a while to get, so it is taken before BUCKETS
+    // rather than under it.
+    let file = match dir {
+        Some(dir) => BucketFile::lock(dir, host).await,
+        None => None,
+    };
+    let mut buckets = BUCKETS.get_or_init(Default::default).lock().await;
+    let bucket = buckets
+        .entry(host.to_string())
+        .or_insert_with(|| Bucket::full(limit));
+
+    let Some(lock) = file else {
+        return bucket.take(limit, unix_now());
+    };
+    if let Some(saved) = lock.read().await {
+        *bucket = saved;
+    }
+    let wait = bucket.take(limit, unix_now());
+    lock.write(bucket).await;
+    wait
+}
+
+/// A host's bucket on disk, locked against other processes while held.
+///
+/// The lock is a second file created exclusively, since `File::lock` is newer
+/// than the MSRV. Losing the race for it, or any I/O error, degrades to this
+/// process's own bucket rather than failing the request: pacing across
+/// processes is a courtesy on top of pacing within one. Its I/O goes through
+/// `tokio::fs`, so a slow disk or a long wait for the lock holds up only the
+/// task that is pacing, not the runtime thread under it.
+struct BucketFile {
+    path: PathBuf,
+    lock: PathBuf,
+}
+
+impl BucketFile {
+    /// How long to wait for another process to release the lock.
+    const PATIENCE: Duration = Duration::from_secs(2);
+    /// A lock older than this was left by a process that died holding it.
+    const STALE: Duration = Duration::from_secs(10);
+
+    async fn lock(dir: &Path, host: &str) -> Option<Self> {
+        tokio::fs::create_dir_all(dir).await.ok()?;
+        let file = BucketFile {
+            path: dir.join(format!("{host}.json")),
+            lock: dir.join(format!("{host}.lock")),
+        };
+        let deadline = Instant::now() + Self::PATIENCE;
+        loop {
+            let created = tokio::fs::OpenOptions::new()
+                .write(true)
+                .create_new(true)
+                .open(&file.lock)
+                .await;
+            match created {
+                Ok(_) => return Some(file),
+                Err(e) if e.kind() == std::io::ErrorKind::AlreadyExists => {
+                    let stale = tokio::fs::metadata(&file.lock)
+                        .await
+                        .and_then(|m| m.modified())
+                        .is_ok_and(|t| t.elapsed().is_ok_and(|age| age > Self::STALE));
+                    if stale {
+                        let _ = tokio::fs::remove_file(&file.lock).await;
+                    } else if Instant::now() >= deadline {
+                        return None;
+                    } else {
+                        tokio::time::sleep(Duration::from_millis(10)).await;
+                    }
+                }
+                Err(_) => return None,
+            }
+        }
+    }
+
+    async fn read(&self) -> Option<Bucket> {
+        let text = tokio::fs::read_to_string(&self.path).await.ok()?;
+        serde_json::from_str(&text).ok()
+    }
+
+    async fn write(&self, bucket: &Bucket) {
+        if let Ok(json) = serde_json::to_string(bucket) {
+            let _ = tokio::fs::write(&self.path, json).await;
+        }
+    }
+}
+
+impl Drop for BucketFile {
+    // Drop cannot await. Unlinking one file does not wait on anyone else,
+    // unlike the polling in `lock`.
+    fn drop(&mut self) {
+        let _ = std::fs::remove_file(&self.lock);
+    }
+}
+
+/// The services this tool sends requests to, as labelled in metrics.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Upstream {
+    Nominatim,
+    Nws,
+}
+
+impl Upstream {
+    const ALL: [Upstream; 2] = [Upstream::Nominatim, Upstream::Nws];
+
+    fn label(self) -> &'static str {
+        match self {
+            Upstream::Nominatim => "nominatim",
+            Upstream::Nws => "nws",
+        }
+    }
+}
+
+/// Running totals for one upstream since the process started.
+#[derive(Clone, Copy)]
+struct Stats {
+    requests: u64,
+    errors: u64,
+    seconds: f64,
+}
+
+const NO_STATS: Stats = Stats {
+    requests: 0,
+    errors: 0,
+    seconds: 0.0,
+};
+
+static STATS: std::sync::Mutex<[Stats; 2]> = std::sync::Mutex::new([NO_STATS; 2]);
+
+/// Where requests go other than the network, for the whole process. Set once,
+/// from `--record` or `--replay`.
+static TAPE: OnceLock<Tape> = OnceLock::new();
+
+/// Record or replay every request from now on; see [`crate::tape`].
+pub fn set_tape(tape: Tape) {
+    // Only `main` calls this, once, before any request is sent.
+    let _ = TAPE.set(tape);
+}
+
+/// Whether requests are being recorded or replayed.
+pub fn taping() -> bool {
+    TAPE.get().is_some()
+}
+
+/// Send `request` to `upstream`, counting it and timing it.
+///
+/// A request to a paced host first waits its turn; see [`throttle`].
+/// A transport failure and an error status both count as errors; which one
+/// it was is still reported to the caller as before. A 304 answering a
+/// conditional request is not an error. Under `--replay` nothing
+/// is sent, counted or paced, and the response comes from the tape.
+pub async fn send(
+    upstream: Upstream,
+    request: reqwest::RequestBuilder,
+) -> Result<reqwest::Response> {
+    let (client, request) = request.build_split();
+    let request = request?;
+    // A replayed request reaches no host, so has no host's limit to respect.
+    if !matches!(TAPE.get(), Some(Tape::Replay(_))) {
+        throttle(request.url()).await;
+    }
+    match TAPE.get() {
+        None => Ok(execute(upstream, &client, request).await?),
+        Some(Tape::Replay(dir)) => tape::replay(dir, &RecordedRequest::of(&request)),
+        Some(Tape::Record(dir)) => {
+            let recorded = RecordedRequest::of(&request);
+            let response = execute(upstream, &client, request).await?;
+            tape::record(dir, recorded, response).await
+        }
+    }
+}
+
+async fn execute(
+    upstream: Upstream,
+    client: &reqwest::Client,
+    request: reqwest::Request,
+) -> reqwest::Result<reqwest::Response> {
+    let start = Instant::now();
+    let result = client.execute(request).await;
+    let failed = result.as_ref().map_or(true, |response| {
+        let status = response.status();
+        !(status.is_success() || status == reqwest::StatusCode::NOT_MODIFIED)
+    });
+
+    let mut stats = STATS.lock().unwrap_or_else(|e| e.into_inner());
+    let entry = &mut stats[upstream as usize];
+    entry.requests += 1;
+    entry.errors += u64::from(failed);
+    entry.seconds += start.elapsed().as_secs_f64();
+    result
+}
+
+/// Write the upstream request counters and latency to `out`.
+pub fn write_metrics(out: &mut Exposition) {
+    let stats = *STATS.lock().unwrap_or_else(|e| e.into_inner());
+    let each = |out: &mut Exposition, name: &str, value: fn(&Stats) -> f64| {
+        for upstream in Upstream::ALL {
+            let labels = [("upstream", upstream.label())];
+            out.sample(name, &labels, value(&stats[upstream as usize]));
+        }
+    };
+
+    let name = "wxdotgov_upstream_requests_total";
+    out.family(name, "counter", "Requests sent to each upstream service.");
+    each(out, name, |s| s.requests as f64);
+
+    let name = "wxdotgov_upstream_errors_total";
+    out.family(
+        name,
+        "counter",
+        "Upstream requests that failed or returned an error status.",
+    );
+    each(out, name, |s| s.errors as f64);
+
+    let name = "wxdotgov_upstream_request_duration_seconds";
+    out.family(
+        name,
+        "summary",
+        "Time spent waiting for upstream responses.",
+    );
+    each(out, &format!("{name}_sum"), |s| s.seconds);
+    each(out, &format!("{name}_count"), |s| s.requests as f64);
+}
+
 /// Read an error response body for use in a message, bounded so a full HTML
 /// page cannot flood the terminal.
 ///
@@ -107,6 +470,140 @@ mod tests {
         );
     }
 
+    #[tokio::test]
+    async fn requests_to_nominatim_are_spaced_through_the_bucket_file() {
+        assert_eq!(limit_for(NOMINATIM_HOST), Some(Limit::NOMINATIM));
+        // A host of its own, so no other test's requests count against it,
+        // and a directory of its own rather than the real state directory.
+        let host = "spaced.test";
+        let dir = std::env::temp_dir().join(format!("wxdotgov-spaced-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+
+        let first = reserve(host, Limit::NOMINATIM, Some(&dir)).await;
+        let second = reserve(host, Limit::NOMINATIM, Some(&dir)).await;
+        assert_eq!(first, Duration::ZERO);
+        assert!(second > Duration::from_millis(900), "{second:?}");
+        assert!(dir.join(format!("{host}.json")).exists());
+        assert!(!dir.join(format!("{host}.lock")).exists());
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[tokio::test]
+    async fn throttle_leaves_unlisted_hosts_alone() {
+        // Also the mock servers every other test talks to.
+        let start = Instant::now();
+        for _ in 0..3 {
+            throttle(&"http://127.0.0.1:1234/points/1,2".parse().unwrap()).await;
+        }
+        assert!(start.elapsed() < Duration::from_millis(500));
+    }
+
+    #[tokio::test]
+    async fn a_contended_bucket_file_holds_up_only_its_own_host() {
+        let dir = std::env::temp_dir().join(format!("wxdotgov-contended-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        // Another process's lock, fresh enough to be waited out.
+        std::fs::File::create(dir.join("held.test.lock")).unwrap();
+        let limit = Limit {
+            rate: 100.0,
+            burst: 100.0,
+        };
+        let held = tokio::spawn({
+            let dir = dir.clone();
+            async move { reserve("held.test", limit, Some(&dir)).await }
+        });
+        tokio::time::sleep(Duration::from_millis(50)).await;
+
+        let start = Instant::now();
+        reserve("free.test", limit, None).await;
+        assert!(start.elapsed() < Duration::from_millis(500));
+        held.await.unwrap();
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn bucket_allows_a_burst_then_paces_at_the_rate() {
+        let limit = Limit {
+            rate: 2.0,
+            burst: 2.0,
+        };
+        let mut bucket = Bucket::full(limit);
+        assert_eq!(bucket.take(limit, 100.0), Duration::ZERO);
+        assert_eq!(bucket.take(limit, 100.0), Duration::ZERO);
+        // Queued callers each wait half a second longer than the last.
+        assert_eq!(bucket.take(limit, 100.0), Duration::from_millis(500));
+        assert_eq!(bucket.take(limit, 100.0), Duration::from_secs(1));
+        // A quiet spell refills the bucket, but only up to the burst.
+        assert_eq!(bucket.take(limit, 200.0), Duration::ZERO);
+        assert_eq!(bucket.take(limit, 200.0), Duration::ZERO);
+        assert!(bucket.take(limit, 200.0) > Duration::ZERO);
+    }
+
+    #[test]
+    fn nws_rates_parse_as_requests_per_second() {
+        assert_eq!(
+            parse_rate("5").unwrap(),
+            Limit {
+                rate: 5.0,
+                burst: 5.0
+            }
+        );
+        assert_eq!(parse_rate("0.5").unwrap().burst, 1.0);
+        for bad in ["0", "-1", "fast", "inf"] {
+            assert!(parse_rate(bad).is_err(), "{bad}");
+        }
+    }
+
+    #[tokio::test]
+    async fn buckets_are_shared_through_the_state_file() {
+        let dir = std::env::temp_dir().join(format!("wxdotgov-ratelimit-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        let host = "persisted.test";
+
+        // What an earlier run left: the one token spent just now.
+        let spent = Bucket {
+            tokens: 0.0,
+            updated: unix_now(),
+        };
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(
+            dir.join(format!("{host}.json")),
+            serde_json::to_string(&spent).unwrap(),
+        )
+        .unwrap();
+
+        let wait = reserve(host, Limit::NOMINATIM, Some(&dir)).await;
+        assert!(wait > Duration::from_millis(900), "{wait:?}");
+        let saved: Bucket = serde_json::from_str(
+            &std::fs::read_to_string(dir.join(format!("{host}.json"))).unwrap(),
+        )
+        .unwrap();
+        assert!(saved.tokens < 0.0, "{saved:?}");
+        // The lock is released for the next run.
+        assert!(!dir.join(format!("{host}.lock")).exists());
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[tokio::test]
+    async fn concurrent_tasks_queue_on_one_bucket() {
+        let limit = Limit {
+            rate: 10.0,
+            burst: 1.0,
+        };
+        let tasks: Vec<_> = (0..3)
+            .map(|_| tokio::spawn(reserve("concurrent.test", limit, None)))
+            .collect();
+        let mut waits = Vec::new();
+        for task in tasks {
+            waits.push(task.await.unwrap());
+        }
+        waits.sort();
+        assert_eq!(waits[0], Duration::ZERO);
+        assert!(waits[1] >= Duration::from_millis(90), "{waits:?}");
+        assert!(waits[2] >= Duration::from_millis(190), "{waits:?}");
+    }
+
     #[test]
     fn client_is_reused_across_calls() {
         let first = client().expect("client should build");
@@ -0,0 +1,181 @@
+//! iCalendar export, for subscribing to a forecast from a calendar app.
+//!
+//! `--output ics` writes one timed event per detailed forecast period and,
+//! unless `--no-alerts` is given, one all-day event per active alert:
+//!
+//! ```text
+//! BEGIN:VEVENT
+//! UID:20240129T020000Z-SEW-115-68@wxdotgov
+//! DTSTART:20240129T020000Z
+//! DTEND:20240129T140000Z
+//! SUMMARY:Tonight
+//! DESCRIPTION:A chance of rain. Mostly cloudy...
+//! END:VEVENT
+//! ```
+//!
+//! A calendar re-importing a file, or polling a subscription, matches events
+//! by `UID`. Period UIDs are therefore built from what identifies a forecast
+//! period across runs -- the grid square and the period's start -- rather
+//! than from anything that changes when the forecast is updated, so tomorrow's
+//! run replaces "Tonight" instead of adding a second copy beside it. An
+//! updated alert comes with a new NWS alert ID, so alerts use the ID of the
+//! original alert the update references, and an update replaces its event.
+//!
+//! RFC 5545: <https://datatracker.ietf.org/doc/html/rfc5545>
+
+use chrono::{DateTime, FixedOffset, NaiveDate, TimeDelta, Utc};
+
+use crate::render::{limited, RenderOptions};
+use crate::weatherdotgov::{Alert, Period, PointsProperties};
+
+/// Content lines longer than this many octets must be folded (RFC 5545 §3.1).
+const MAX_LINE_OCTETS: usize = 75;
+
+/// Append a content line, folded and CRLF-terminated.
+fn push_line(out: &mut String, line: &str) {
+    let mut octets = 0;
+    for c in line.chars() {
+        // A continuation line starts with a space, which counts toward its 75.
+        if octets + c.len_utf8() > MAX_LINE_OCTETS {
+            out.push_str("\r\n ");
+            octets = 1;
+        }
+        out.push(c);
+        octets += c.len_utf8();
+    }
+    out.push_str("\r\n");
+}
+
+/// Escape a TEXT value (RFC 5545 §3.3.11).
+fn escape(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '\\' => out.push_str("\\\\"),
+            ';' => out.push_str("\\;"),
+            ',' => out.push_str("\\,"),
+            '\n' => out.push_str("\\n"),
+            '\r' => {}
+            c => out.push(c),
+        }
+    }
+    out
+}
+
+/// A DATE-TIME in UTC form, which needs no VTIMEZONE alongside it.
+fn utc(t: &DateTime<FixedOffset>) -> String {
+    t.with_timezone(&Utc).format("%Y%m%dT%H%M%SZ").to_string()
+}
+
+fn date(d: NaiveDate) -> String {
+    d.format("%Y%m%d").to_string()
+}
+
+/// The stable UID of a forecast period; see the module docs.
+fn period_uid(period: &Period, point: &PointsProperties) -> String {
+    format!(
+        "{}-{}-{}-{}@wxdotgov",
+        utc(&period.start_time),
+        point.grid_id,
+        point.grid_x,
+        point.grid_y
+    )
+}
+
+/// An alert as an all-day event spanning the days from onset to end, in the
+/// display zone. A missing onset is taken as `today` and a missing end as the
+/// onset day, so the alert still shows up somewhere sensible.
+fn push_alert(
+    out: &mut String,
+    alert: &Alert,
+    point: &PointsProperties,
+    stamp: &str,
+    today: NaiveDate,
+    opts: &RenderOptions,
+) {
+    let first = alert
+        .onset
+        .map(|t| opts.times.in_zone(&t).date_naive())
+        .unwrap_or(today);
+    let last = alert
+        .ends
+        .map(|t| opts.times.in_zone(&t).date_naive())
+        .unwrap_or(first)
+        .max(first);
+    let uid = if alert.id.is_empty() {
+        format!(
+            "{}-{}-{}-{}-{}@wxdotgov",
+            alert.event.replace(' ', "-"),
+            date(first),
+            point.grid_id,
+            point.grid_x,
+            point.grid_y
+        )
+    } else {
+        format!("{}@wxdotgov", alert.original_id())
+    };
+
+    push_line(out, "BEGIN:VEVENT");
+    push_line(out, &format!("UID:{uid}"));
+    push_line(out, &format!("DTSTAMP:{stamp}"));
+    push_line(out, &format!("DTSTART;VALUE=DATE:{}", date(first)));
+    // DTEND is exclusive for all-day events: the day after the last one.
+    push_line(
+        out,
+        &format!("DTEND;VALUE=DATE:{}", date(last + TimeDelta::days(1))),
+    );
+    push_line(out, &format!("SUMMARY:⚠ {}", escape(&alert.event)));
+    if let Some(headline) = &alert.headline {
+        push_line(out, &format!("DESCRIPTION:{}", escape(headline)));
+    }
+    push_line(out, "TRANSP:TRANSPARENT");
+    push_line(out, "END:VEVENT");
+}
+
+/// Render a calendar of `periods` and `alerts`, named after `place`.
+///
+/// Times are written in UTC; calendar apps show them in the viewer's zone.
+/// `--limit` applies as it does to the other outputs, but there is nowhere in
+/// a calendar to say periods were left out, so nothing does.
+pub fn render_ics(
+    periods: &[Period],
+    alerts: &[Alert],
+    point: &PointsProperties,
+    place: &str,
+    opts: &RenderOptions,
+) -> String {
+    let stamp = utc(&opts.times.now.fixed_offset());
+    let offset = opts.times.offset(periods.first().map(|p| &p.start_time));
+    let today = opts.times.now.with_timezone(&offset).date_naive();
+    let mut out = String::new();
+    push_line(&mut out, "BEGIN:VCALENDAR");
+    push_line(&mut out, "VERSION:2.0");
+    push_line(&mut out, "PRODID:-//wxdotgov//NWS forecast//EN");
+    push_line(&mut out, "CALSCALE:GREGORIAN");
+    push_line(
+        &mut out,
+        &format!("X-WR-CALNAME:Forecast: {}", escape(place)),
+    );
+
+    for period in limited(periods, opts.limit) {
+        push_line(&mut out, "BEGIN:VEVENT");
+        push_line(&mut out, &format!("UID:{}", period_uid(period, point)));
+        push_line(&mut out, &format!("DTSTAMP:{stamp}"));
+        push_line(&mut out, &format!("DTSTART:{}", utc(&period.start_time)));
+        push_line(&mut out, &format!("DTEND:{}", utc(&period.end_time)));
+        push_line(&mut out, &format!("SUMMARY:{}", escape(&period.name)));
+        push_line(
+            &mut out,
+            &format!("DESCRIPTION:{}", escape(&period.detailed_forecast)),
+        );
+        // A forecast is not an appointment: don't show the time as busy.
+        push_line(&mut out, "TRANSP:TRANSPARENT");
+        push_line(&mut out, "END:VEVENT");
+    }
+    for alert in alerts {
+        push_alert(&mut out, alert, point, &stamp, today, opts);
+    }
+
+    push_line(&mut out, "END:VCALENDAR");
+    out
+}
@@ -3,6 +3,11 @@
 //! Takes a US postal code, or a city name with an optional state code, and
 //! prints that location's weather forecast.
 //!
+//! Each kind of report is a subcommand -- `forecast`, `hourly`, `alerts`,
+//! `current`, `discussion`, `info` -- taking the location, `--pretty` and `--units`
+//! after its name. Without a subcommand the options describe a forecast, as
+//! `forecast` does, which is how the tool was invoked before it had any.
+//!
 //! The lookup runs in three steps:
 //!
 //! 1. Geocode the location to a latitude/longitude via nominatim.openstreetmap.org
@@ -17,23 +22,105 @@
 //! $ wxdotgov --city Seattle --state WA
 //! $ wxdotgov --city Seattle --state WA --forecast-type hourly --pretty
 //! $ wxdotgov --zip 12345 --forecast-type hourly --limit 6
+//! $ wxdotgov --zip 12345 --units si
+//! $ wxdotgov --zip 12345 --forecast-type hourly --time-format 24h --time-zone local
+//! $ wxdotgov --zip 12345 --forecast-type hourly --from tonight --until tomorrow
+//! $ wxdotgov --zip 12345 --daytime-only
+//! $ wxdotgov --zip 12345 --chart --limit 72 --pretty
+//! $ wxdotgov --zip 12345 --format '{temp}°{unit} {short} {wind}'
+//! $ wxdotgov --zip 12345 --waybar
+//! $ wxdotgov --zip 12345 --template slack
+//! $ wxdotgov --zip 12345 --template ./email.j2
+//! $ wxdotgov --zip 12345 --forecast-type hourly --output markdown > forecast.md
+//! $ wxdotgov --zip 12345 --output ics > forecast.ics
+//! $ wxdotgov --zip 12345 --forecast-type hourly --watch 5m
+//! $ wxdotgov hourly --zip 12345 --limit 6
+//! $ wxdotgov alerts --city Seattle --state WA
+//! $ wxdotgov current --zip 12345 --units si
+//! $ wxdotgov discussion --zip 12345
+//! $ wxdotgov info --zip 12345
+//! $ wxdotgov locations --city Springfield --limit 10
+//! $ wxdotgov forecast --zone WAZ558
+//! $ wxdotgov zones search --area WA --name seattle
+//! $ wxdotgov cache --clear
+//! $ wxdotgov serve --listen 127.0.0.1:8080
+//! $ wxdotgov exporter --location 98101 --location "Portland, OR" --interval 10m
+//! $ wxdotgov tui --location 98101 --location "Portland, OR"
+//! $ wxdotgov completions bash > ~/.local/share/bash-completion/completions/wxdotgov
+//! $ wxdotgov notify --location 98101 --min-severity moderate --webhook http://localhost:9000/hook
 //! ```
 //!
 //! Fetching lives here; deciding what the output says lives in [`render`].
 
+use std::future::Future;
+use std::path::PathBuf;
+use std::sync::Arc;
+
 use anyhow::{bail, Context, Result};
-use clap::{Parser, ValueEnum};
+use chrono::{FixedOffset, TimeDelta, Utc};
+use clap::{CommandFactory, Parser, Subcommand, ValueEnum};
 
+mod archive;
+mod cache;
+mod completions;
+mod dirs;
+mod exporter;
+mod filter;
 mod http;
+mod ics;
+mod metrics;
 mod nominatim;
+mod notify;
 mod render;
+mod serve;
+mod stations;
+mod status;
+mod tape;
+mod template;
+mod times;
+mod tui;
+mod units;
+mod watch;
 mod weatherdotgov;
+mod zones;
 
-use crate::nominatim::get_lat_lon;
-use crate::render::{render_detailed, render_hourly, Style, DEFAULT_LIMIT};
-use crate::weatherdotgov::{get_detailed_forecast, get_hourly_forecast, get_weather_point};
+use crate::cache::{Cache, CacheArgs};
+use crate::completions::CompletionsArgs;
+use crate::exporter::ExporterArgs;
+use crate::filter::{DayPart, PeriodFilter};
+use crate::ics::render_ics;
+use crate::nominatim::{get_lat_lon, search_locations, NominatimLocation};
+use crate::notify::NotifyArgs;
+use crate::render::{
+    limited, render_alerts, render_chart, render_detailed, render_detailed_html,
+    render_detailed_markdown, render_fire, render_forecast_check, render_hourly,
+    render_hourly_html, render_hourly_markdown, render_info, render_latest_observation,
+    render_marine, render_observation_chart, render_observations, render_product, render_stations,
+    truncation_lines, OutputFormat, RenderOptions, Style, DEFAULT_LIMIT,
+};
+use crate::serve::ServeArgs;
+use crate::stations::parse_station;
+use crate::status::{render_line, render_waybar, LineTemplate, CURRENT_TEMPLATE, DEFAULT_TEMPLATE};
+use crate::tape::Tape;
+use crate::template::{render_template, Place, TemplateContext, TemplateSource};
+use crate::times::{
+    parse_interval, parse_refresh_interval, zone_offset, DisplayZone, TimeDisplay, TimeFormat,
+    TimeSpec,
+};
+use crate::tui::TuiArgs;
+use crate::units::Units;
+use crate::watch::Watch;
+use crate::weatherdotgov::{
+    find_marine_zone, find_zone, get_active_alerts, get_checked_forecast,
+    get_checked_hourly_forecast, get_detailed_forecast, get_discussion, get_fire_weather,
+    get_hourly_forecast, get_latest_observation, get_observations, get_office, get_station,
+    get_stations, get_weather_point, get_zone_alerts, get_zone_forecast, parse_zone, Alert,
+    ForecastProperties, HourlyForecastProperties, Issuance, OnStale, Period, PointsProperties,
+    StaleCheck, Station, Zone,
+};
+use crate::zones::ZonesArgs;
 
-#[derive(Debug, PartialEq)]
+#[derive(Clone, Debug, PartialEq)]
 pub enum LocationInput {
     PostalCode(String),
     PostalCodePlusFour(String, String),
@@ -73,7 +160,8 @@ fn build_location_input(
     city: Option<String>,
     state: Option<String>,
 ) -> Result<LocationInput> {
-    // clap's required ArgGroup guarantees exactly one of zip/city is present.
+    // clap's required ArgGroup guarantees exactly one of zip/city/zone is
+    // present, and callers turn a zone away before getting here.
     match (zip, city) {
         (Some(zip), _) => {
             if state.is_some() {
@@ -98,64 +186,926 @@ fn build_location_input(
     about,
     long_about = None,
     arg_required_else_help = true,
-    group = clap::ArgGroup::new("location")
-        .required(true)
-        .args(["zip", "city"]),
+    args_conflicts_with_subcommands = true,
+    subcommand_negates_reqs = true
 )]
 struct Args {
+    #[command(subcommand)]
+    command: Option<Command>,
+
+    /// Forecast type to display. Options: detailed or hourly. Only without a
+    /// subcommand; `wxdotgov hourly` is the subcommand spelling of hourly.
+    #[arg(long, value_enum, default_value_t = ForecastType::Detailed)]
+    forecast_type: ForecastType,
+
+    #[command(flatten)]
+    forecast: ForecastArgs,
+
+    /// Save every upstream response, with the request that fetched it, under
+    /// DIR. Goes after the subcommand, like the other options.
+    #[arg(long, global = true, value_name = "DIR", conflicts_with = "replay")]
+    record: Option<PathBuf>,
+
+    /// Answer every upstream request from a --record directory instead of the
+    /// network, failing on any request that was not recorded.
+    #[arg(long, global = true, value_name = "DIR")]
+    replay: Option<PathBuf>,
+}
+
+/// The place to look up, taken by every subcommand that reports on one.
+#[derive(Debug, clap::Args)]
+#[group(skip)]
+#[command(group = clap::ArgGroup::new("location").required(true).args(["zip", "city", "zone", "point"]))]
+struct LocationArgs {
     /// ZIP code in the U.S.
-    #[arg(short, long, group = "location")]
+    #[arg(short, long)]
     zip: Option<String>,
 
     /// City name (when using city/state search)
-    #[arg(short, long, group = "location")]
+    #[arg(short, long)]
     city: Option<String>,
 
     /// State abbreviation (e.g., CA)
     #[arg(short, long)]
     state: Option<String>,
 
+    /// NWS zone ID (e.g., WAZ558), for the zone's forecast or alerts instead
+    /// of a point's. Find one with `wxdotgov zones search`.
+    #[arg(long, value_parser = parse_zone)]
+    zone: Option<String>,
+
+    /// Latitude and longitude (e.g., 47.7,-122.45), used as given. A point on
+    /// the water gets its marine zone's forecast.
+    #[arg(long, value_name = "LAT,LON", value_parser = parse_point, allow_hyphen_values = true)]
+    point: Option<Coordinates>,
+}
+
+/// A `--point`, kept as the strings the APIs are sent.
+#[derive(Clone, Debug, PartialEq)]
+struct Coordinates {
+    lat: String,
+    lon: String,
+}
+
+/// Parse `--point`: a latitude and longitude in decimal degrees, comma
+/// separated.
+fn parse_point(s: &str) -> Result<Coordinates> {
+    let Some((lat, lon)) = s.split_once(',') else {
+        bail!("'{s}' is not a point like 47.7,-122.45");
+    };
+    let (lat, lon) = (lat.trim(), lon.trim());
+    let in_range = |value: &str, limit: f64| value.parse::<f64>().is_ok_and(|v| v.abs() <= limit);
+    if !in_range(lat, 90.0) || !in_range(lon, 180.0) {
+        bail!("'{s}' is not a point like 47.7,-122.45");
+    }
+    Ok(Coordinates {
+        lat: lat.to_string(),
+        lon: lon.to_string(),
+    })
+}
+
+impl LocationArgs {
+    /// The location as given, e.g. `98101` or `Seattle, WA`, to key what is
+    /// remembered about it.
+    fn label(&self) -> String {
+        if let Some(point) = &self.point {
+            return format!("{},{}", point.lat, point.lon);
+        }
+        if let Some(zone) = &self.zone {
+            return zone.clone();
+        }
+        if let Some(zip) = &self.zip {
+            return zip.trim().to_string();
+        }
+        let city = self.city.as_deref().unwrap_or_default().trim();
+        match &self.state {
+            Some(state) => format!("{city}, {}", state.trim().to_ascii_uppercase()),
+            None => city.to_string(),
+        }
+    }
+
+    /// The place to geocode. Every report but `forecast` and `alerts` needs a
+    /// point, which a zone is not.
+    fn input(self) -> Result<LocationInput> {
+        if let Some(zone) = self.zone {
+            bail!("{zone} is a zone, not a point; --zone works with forecast and alerts only");
+        }
+        if self.point.is_some() {
+            bail!("--point is already coordinates, with nothing to look up");
+        }
+        build_location_input(self.zip, self.city, self.state)
+    }
+}
+
+/// How text output looks, shared like [`LocationArgs`].
+#[derive(Debug, clap::Args)]
+struct DisplayArgs {
     /// Enable pretty output with colors and formatting.
     #[arg(long)]
     pretty: bool,
 
-    /// Forecast type to display. Options: detailed or hourly.
-    #[arg(long, value_enum, default_value_t = ForecastType::Detailed)]
-    forecast_type: ForecastType,
+    /// Unit system for temperatures, wind speeds and other measurements.
+    #[arg(long, value_enum, env = "WXDOTGOV_UNITS", default_value_t = Units::Us)]
+    units: Units,
+}
+
+impl DisplayArgs {
+    fn render_options(&self) -> RenderOptions {
+        RenderOptions {
+            style: Style::from_pretty_flag(self.pretty),
+            limit: None,
+            units: self.units,
+            times: TimeDisplay::new(TimeFormat::TwelveHour, DisplayZone::Location),
+        }
+    }
+}
+
+/// A forecast and how to print it: the options of `forecast` and `hourly`,
+/// and of the flat form that predates them.
+#[derive(Debug, clap::Args)]
+struct ForecastArgs {
+    #[command(flatten)]
+    location: LocationArgs,
+
+    #[command(flatten)]
+    display: DisplayArgs,
+
+    /// Chart temperature and chance of precipitation instead of listing
+    /// periods. Always uses the hourly forecast; --limit sets the span.
+    #[arg(long, conflicts_with_all = ["format", "waybar", "template"])]
+    chart: bool,
+
+    /// Print one line from the current hour for status bars, e.g.
+    /// '{temp}°{unit} {short} {wind}'. Fields: temp, unit, short, wind,
+    /// wind_speed, wind_dir, pop, dewpoint, time.
+    #[arg(long, value_name = "TEMPLATE")]
+    format: Option<LineTemplate>,
+
+    /// Print waybar custom-module JSON: the --format line as text, the next
+    /// few hours and any active alerts as the tooltip, and a CSS class.
+    #[arg(long)]
+    waybar: bool,
+
+    /// Render through a MiniJinja template: a file path, or one of the bundled
+    /// templates detailed, hourly or slack.
+    #[arg(long, value_name = "PATH|NAME", conflicts_with_all = ["format", "waybar"])]
+    template: Option<TemplateSource>,
+
+    /// Write the forecast as terminal text, Markdown, a standalone HTML page,
+    /// or an iCalendar file of the detailed periods and active alerts.
+    /// --pretty only affects text.
+    #[arg(
+        long,
+        value_enum,
+        default_value_t = OutputFormat::Text,
+        conflicts_with_all = ["chart", "format", "waybar", "template"]
+    )]
+    output: OutputFormat,
+
+    /// Leave the active alerts out of --output ics, for a calendar of the
+    /// forecast alone.
+    #[arg(long)]
+    no_alerts: bool,
+
+    /// Keep the forecast on screen, refreshing every INTERVAL (e.g. 5m, 1h;
+    /// at least 1m) and listing what changed since the previous fetch.
+    #[arg(
+        long,
+        value_name = "INTERVAL",
+        num_args = 0..=1,
+        default_missing_value = "10m",
+        value_parser = parse_refresh_interval,
+        conflicts_with_all = ["chart", "format", "waybar", "template", "output"]
+    )]
+    watch: Option<std::time::Duration>,
 
     /// Maximum number of forecast periods to print. Use 0 for all of them.
     #[arg(short = 'n', long, default_value_t = DEFAULT_LIMIT)]
     limit: usize,
+
+    /// How forecast times are written.
+    #[arg(long, value_enum, default_value_t = TimeFormat::TwelveHour)]
+    time_format: TimeFormat,
+
+    /// Show times in the forecast location's zone or this machine's.
+    #[arg(long, value_enum, default_value_t = DisplayZone::Location)]
+    time_zone: DisplayZone,
+
+    /// Only show periods ending after this time: now, today, tonight,
+    /// tomorrow, +6h, +2d, 2024-01-28 or 2024-01-28T18:00.
+    #[arg(long, value_name = "WHEN")]
+    from: Option<TimeSpec>,
+
+    /// Only show periods starting before this time. Accepts the same forms as
+    /// --from.
+    #[arg(long, value_name = "WHEN")]
+    until: Option<TimeSpec>,
+
+    /// Only show daytime periods.
+    #[arg(long, conflicts_with = "night_only")]
+    daytime_only: bool,
+
+    /// Only show nighttime periods.
+    #[arg(long)]
+    night_only: bool,
+
+    /// Warn when the forecast was last updated longer ago than AGE (e.g. 6h,
+    /// 90m), or comes back with no periods.
+    #[arg(
+        long,
+        value_name = "AGE",
+        default_value = "12h",
+        value_parser = parse_interval,
+        conflicts_with = "watch"
+    )]
+    stale_after: std::time::Duration,
+
+    /// What to do about a stale forecast besides warning.
+    #[arg(long, value_enum, default_value_t = OnStale::Warn, conflicts_with = "watch")]
+    on_stale: OnStale,
 }
 
-#[derive(Clone, Debug, PartialEq, ValueEnum)]
+impl ForecastArgs {
+    /// Charts and status lines are drawn from hourly data whatever forecast
+    /// was `requested`, and calendars from the detailed periods.
+    fn forecast_type(&self, requested: ForecastType) -> ForecastType {
+        let hourly_template = self.template.as_ref().is_some_and(|t| t.wants_hourly());
+        if self.chart || self.format.is_some() || self.waybar || hourly_template {
+            ForecastType::Hourly
+        } else if self.output == OutputFormat::Ics {
+            ForecastType::Detailed
+        } else {
+            requested
+        }
+    }
+
+    /// The time filters, applied to whichever period list is fetched.
+    fn period_filter(&self) -> PeriodFilter {
+        let part = match (self.daytime_only, self.night_only) {
+            (true, _) => Some(DayPart::Day),
+            (_, true) => Some(DayPart::Night),
+            _ => None,
+        };
+        PeriodFilter {
+            from: self.from.clone(),
+            until: self.until.clone(),
+            part,
+        }
+    }
+
+    fn stale_check(&self) -> StaleCheck {
+        StaleCheck {
+            max_age: self.stale_after,
+            on_stale: self.on_stale,
+        }
+    }
+
+    fn render_options(&self) -> RenderOptions {
+        RenderOptions {
+            // 0 is the "no limit" spelling; every other value is taken literally.
+            limit: (self.limit != 0).then_some(self.limit),
+            times: TimeDisplay::new(self.time_format, self.time_zone),
+            ..self.display.render_options()
+        }
+    }
+}
+
+/// The options of the subcommands that report on one place without
+/// forecast periods to shape.
+#[derive(Debug, clap::Args)]
+struct LookupArgs {
+    #[command(flatten)]
+    location: LocationArgs,
+
+    #[command(flatten)]
+    display: DisplayArgs,
+}
+
+#[derive(Debug, clap::Args)]
+struct AlertsArgs {
+    #[command(flatten)]
+    lookup: LookupArgs,
+
+    /// Only show marine alerts, such as Small Craft Advisories and Gale
+    /// Warnings.
+    #[arg(long)]
+    marine: bool,
+}
+
+#[derive(Debug, clap::Args)]
+struct FireArgs {
+    #[command(flatten)]
+    lookup: LookupArgs,
+
+    /// How many calendar days to summarize, today first.
+    #[arg(long, default_value_t = 3, value_parser = clap::value_parser!(u32).range(1..=7))]
+    days: u32,
+}
+
+#[derive(Debug, clap::Args)]
+struct HistoryArgs {
+    #[command(flatten)]
+    lookup: LookupArgs,
+
+    /// How many hours back to go. Stations keep about a week.
+    #[arg(long, default_value_t = 24, value_parser = clap::value_parser!(u32).range(1..=168))]
+    hours: u32,
+
+    /// Chart the temperature instead of listing every observation.
+    #[arg(long)]
+    chart: bool,
+
+    /// Compare observed highs and lows with the forecasts recorded when
+    /// `forecast` ran for the place before.
+    #[arg(long)]
+    compare: bool,
+
+    /// Read this station (e.g., KSEA) rather than the location's own.
+    #[arg(long, value_parser = parse_station)]
+    station: Option<String>,
+}
+
+#[derive(Debug, clap::Args)]
+struct CurrentArgs {
+    #[command(flatten)]
+    lookup: LookupArgs,
+
+    /// Read the latest observation from this station (e.g., KSEA) rather
+    /// than the one chosen with `wxdotgov stations --use`, or the nearest.
+    #[arg(long, value_parser = parse_station)]
+    station: Option<String>,
+}
+
+#[derive(Debug, clap::Args)]
+struct StationsArgs {
+    #[command(flatten)]
+    lookup: LookupArgs,
+
+    /// Maximum number of stations to list; 0 lists them all.
+    #[arg(short = 'n', long, default_value_t = 10)]
+    limit: usize,
+
+    /// Remember this station (e.g., KSEA) for the location, for `current`
+    /// and `history`.
+    #[arg(long = "use", value_name = "STATION", value_parser = parse_station)]
+    use_station: Option<String>,
+
+    /// Forget the station remembered for the location.
+    #[arg(long, conflicts_with = "use_station")]
+    forget: bool,
+}
+
+#[derive(Debug, clap::Args)]
+struct LocationsArgs {
+    #[command(flatten)]
+    location: LocationArgs,
+
+    /// Maximum number of matches to list.
+    #[arg(short = 'n', long, default_value_t = 5)]
+    limit: usize,
+}
+
+/// What to do. Without a subcommand the options above print the forecast, as
+/// `forecast` does, so scripts written before the subcommands keep working.
+#[derive(Debug, Subcommand)]
+enum Command {
+    /// Print the detailed forecast, or the hourly one for --chart, --format,
+    /// --waybar and hourly templates.
+    Forecast(ForecastArgs),
+    /// Print the hourly forecast.
+    Hourly(ForecastArgs),
+    /// Print the alerts in effect for a location.
+    Alerts(AlertsArgs),
+    /// Print the forecast for the hour in progress, and the latest
+    /// observation when a station is chosen.
+    Current(CurrentArgs),
+    /// Print the latest Area Forecast Discussion from the location's forecast
+    /// office.
+    Discussion(LookupArgs),
+    /// Print the location's forecast office, zones, county, radar station and
+    /// time zone.
+    Info(LookupArgs),
+    /// Print fire weather: Red Flag Warnings and Fire Weather Watches, and
+    /// each day's humidity, gusts, mixing height, transport wind and indices.
+    Fire(FireArgs),
+    /// Print what the nearest station observed over the last hours, and how
+    /// the forecasts recorded for the place compare.
+    History(HistoryArgs),
+    /// List the observation stations near a location, with distance and
+    /// bearing, or choose the one `current` and `history` use.
+    Stations(StationsArgs),
+    /// List the places a ZIP code or city name matches, to check which one a
+    /// forecast would use.
+    Locations(LocationsArgs),
+    /// Find NWS zone IDs for --zone.
+    Zones(ZonesArgs),
+    /// Show where responses are cached and how much is there, or clear it.
+    Cache(CacheArgs),
+    /// Serve forecasts and alerts over HTTP for dashboards and other local
+    /// clients, sharing one cache across requests.
+    Serve(ServeArgs),
+    /// Export current forecast readings and active alerts for one or more
+    /// locations as Prometheus metrics.
+    Exporter(ExporterArgs),
+    /// Poll active alerts for one or more locations and deliver each new,
+    /// updated, cancelled or ended alert once, to stdout and any sinks given.
+    Notify(NotifyArgs),
+    /// Browse the forecast, hourly chart, current conditions and alerts for
+    /// one or more locations in a full-screen terminal UI.
+    Tui(TuiArgs),
+    /// Print a shell completion script.
+    Completions(CompletionsArgs),
+    /// Print the man page, in roff.
+    Man,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, ValueEnum)]
 enum ForecastType {
     Detailed,
     Hourly,
 }
 
+impl ForecastType {
+    fn as_str(&self) -> &'static str {
+        match self {
+            ForecastType::Detailed => "detailed",
+            ForecastType::Hourly => "hourly",
+        }
+    }
+}
+
+/// What a `--template` context holds besides the periods and alerts.
+struct Fetched<'a> {
+    location: &'a NominatimLocation,
+    point: &'a PointsProperties,
+    forecast_type: ForecastType,
+    issuance: &'a Issuance,
+}
+
+/// Render `periods` through `--template`, fetching the alerts the template
+/// context includes.
+async fn templated<T: serde::Serialize>(
+    source: &TemplateSource,
+    fetched: &Fetched<'_>,
+    periods: &[T],
+    filtered: usize,
+    opts: &RenderOptions,
+) -> Result<String> {
+    let Fetched {
+        location,
+        point,
+        forecast_type,
+        issuance,
+    } = fetched;
+    let alerts = get_active_alerts(&location.lat, &location.lon, None).await?;
+    let shown = limited(periods, opts.limit);
+    let context = TemplateContext {
+        location: Place {
+            name: &location.display_name,
+            lat: &location.lat,
+            lon: &location.lon,
+        },
+        point,
+        alerts: &alerts,
+        forecast_type: forecast_type.as_str(),
+        issuance,
+        periods: shown,
+        filtered,
+        withheld: periods.len() - shown.len(),
+        notes: truncation_lines(filtered, periods.len(), shown.len()),
+    };
+    render_template(source, &context, opts)
+}
+
 #[tokio::main]
 async fn main() -> Result<()> {
     // Parse command-line arguments.
     let args = Args::parse();
+    if let Some(dir) = args.record {
+        http::set_tape(Tape::Record(dir));
+    } else if let Some(dir) = args.replay {
+        http::set_tape(Tape::Replay(dir));
+    }
+    let (forecast, requested) = match args.command {
+        Some(Command::Serve(serve_args)) => return serve::run(serve_args).await,
+        Some(Command::Exporter(exporter_args)) => return exporter::run(exporter_args).await,
+        Some(Command::Notify(notify_args)) => return notify::run(notify_args).await,
+        Some(Command::Tui(tui_args)) => return tui::run(tui_args).await,
+        Some(Command::Completions(completions_args)) => {
+            let mut command = Args::command();
+            return completions::run(&completions_args, &mut command, &mut std::io::stdout());
+        }
+        Some(Command::Man) => {
+            return completions::write_man(Args::command(), &mut std::io::stdout())
+        }
+        Some(Command::Zones(zones_args)) => return one_shot(zones::run(zones_args)).await,
+        Some(Command::Cache(cache_args)) => return cache::run(cache_args),
+        Some(Command::Alerts(lookup)) => return one_shot(print_alerts(lookup)).await,
+        Some(Command::Current(lookup)) => return one_shot(print_current(lookup)).await,
+        Some(Command::Discussion(lookup)) => return one_shot(print_discussion(lookup)).await,
+        Some(Command::Info(lookup)) => return one_shot(print_info(lookup)).await,
+        Some(Command::Fire(fire)) => return one_shot(print_fire(fire)).await,
+        Some(Command::History(history)) => return one_shot(print_history(history)).await,
+        Some(Command::Stations(stations)) => return one_shot(print_stations(stations)).await,
+        Some(Command::Locations(locations)) => return one_shot(print_locations(locations)).await,
+        Some(Command::Forecast(forecast)) => (forecast, ForecastType::Detailed),
+        Some(Command::Hourly(forecast)) => (forecast, ForecastType::Hourly),
+        None => (args.forecast, args.forecast_type),
+    };
+    one_shot(print_forecast(forecast, requested)).await
+}
 
-    // Build the location input.
-    let location_input = build_location_input(args.zip, args.city, args.state)?;
+/// Run a one-shot command with the on-disk cache, so repeated runs share
+/// lookups that are still fresh.
+///
+/// Not while recording or replaying: a response served from the disk cache
+/// would be missing from the recording, or hide what the tape holds.
+async fn one_shot(command: impl Future<Output = Result<()>>) -> Result<()> {
+    let cache = if http::taping() {
+        Cache::default()
+    } else {
+        Cache::persistent()
+    };
+    cache::scope(Arc::new(cache), command).await
+}
+
+/// Geocode the location given on the command line.
+async fn locate(args: LocationArgs) -> Result<NominatimLocation> {
+    if let Some(point) = args.point {
+        let display_name = format!("{}, {}", point.lat, point.lon);
+        return Ok(NominatimLocation {
+            lat: point.lat,
+            lon: point.lon,
+            display_name,
+        });
+    }
+    let location_input = args.input()?;
 
-    // Step 1: Geocode with Nominatim.
-    //
     // Progress lines go to stderr so stdout carries only the forecast: piping
     // this to a file previously interleaved a resolved place name and a raw
-    // API URL above the output. The `--state` warning below already used
-    // stderr, so the two streams were being mixed inconsistently.
+    // API URL above the output. The `--state` warning already used stderr, so
+    // the two streams were being mixed inconsistently.
     let location = get_lat_lon(location_input, None).await?;
     eprintln!("Location found: {}", location.display_name);
+    Ok(location)
+}
+
+/// Look up the zone `--zone` gave, naming it the way [`locate`] names a
+/// place.
+async fn locate_zone(zone: &str) -> Result<Zone> {
+    let found = find_zone(zone, None).await?;
+    eprintln!("Zone found: {} ({})", found.name, found.id);
+    Ok(found)
+}
+
+/// `wxdotgov alerts`.
+async fn print_alerts(args: AlertsArgs) -> Result<()> {
+    let lookup = args.lookup;
+    let mut alerts = match &lookup.location.zone {
+        Some(zone) => {
+            locate_zone(zone).await?;
+            get_zone_alerts(zone, None).await?
+        }
+        None => {
+            let location = locate(lookup.location).await?;
+            get_active_alerts(&location.lat, &location.lon, None).await?
+        }
+    };
+    if args.marine {
+        alerts.retain(Alert::is_marine);
+    }
+    print!(
+        "{}",
+        render_alerts(&alerts, &lookup.display.render_options())
+    );
+    Ok(())
+}
+
+/// `wxdotgov current`: the hour in progress, from the hourly forecast.
+async fn print_current(args: CurrentArgs) -> Result<()> {
+    let label = args.lookup.location.label();
+    let opts = args.lookup.display.render_options();
+    let location = locate(args.lookup.location).await?;
+    let point = get_weather_point(&location.lat, &location.lon, None)
+        .await?
+        .properties;
+    let url = point
+        .forecast_hourly
+        .as_ref()
+        .context("Hourly forecast not available for this location")?;
+    let periods = get_hourly_forecast(url, opts.units)
+        .await?
+        .properties
+        .periods;
+    let template: LineTemplate = CURRENT_TEMPLATE.parse()?;
+    let mut out = render_line(&periods, &template, &opts)?;
+
+    let station = observing_station(args.station, &label, &point).await?;
+    let mut observation = get_latest_observation(&station.station_identifier, None).await?;
+    let offset = opts.times.offset(periods.first().map(|p| &p.start_time));
+    observation.timestamp = observation.timestamp.with_timezone(&offset);
+    out.push_str(&render_latest_observation(&station, &observation, &opts));
+    print!("{out}");
+    Ok(())
+}
+
+/// `wxdotgov discussion`.
+async fn print_discussion(args: LookupArgs) -> Result<()> {
+    let location = locate(args.location).await?;
+    let point = get_weather_point(&location.lat, &location.lon, None)
+        .await?
+        .properties;
+    let product = get_discussion(&point.grid_id, None).await?;
+    print!(
+        "{}",
+        render_product(&product, &args.display.render_options())
+    );
+    Ok(())
+}
+
+/// `wxdotgov info`: the office and zones behind a location's forecast.
+async fn print_info(args: LookupArgs) -> Result<()> {
+    let location = locate(args.location).await?;
+    let point = get_weather_point(&location.lat, &location.lon, None)
+        .await?
+        .properties;
+    let office = get_office(point.office_id(), None).await?;
+    print!(
+        "{}",
+        render_info(&point, &office, &args.display.render_options())
+    );
+    Ok(())
+}
+
+/// `wxdotgov fire`: the fire weather alerts for a location's fire weather
+/// zone, and its grid square's fire weather elements day by day.
+async fn print_fire(args: FireArgs) -> Result<()> {
+    let opts = args.lookup.display.render_options();
+    let location = locate(args.lookup.location).await?;
+    let point = get_weather_point(&location.lat, &location.lon, None)
+        .await?
+        .properties;
+    let fire = get_fire_weather(&point, None).await?;
+    let offset = location_offset(&point, &opts);
+
+    let zone = point.fire_weather_zone_id();
+    let mut alerts = match zone {
+        Some(zone) => get_zone_alerts(zone, None).await?,
+        None => get_active_alerts(&location.lat, &location.lon, None).await?,
+    };
+    alerts.retain(Alert::is_fire);
+    print!(
+        "{}",
+        render_fire(&fire, &alerts, zone, offset, args.days, &opts)
+    );
+    Ok(())
+}
+
+/// `wxdotgov history`: a station's recent observations, and optionally the
+/// recorded forecasts' error against them.
+async fn print_history(args: HistoryArgs) -> Result<()> {
+    let label = args.lookup.location.label();
+    let opts = args.lookup.display.render_options();
+    let location = locate(args.lookup.location).await?;
+    let point = get_weather_point(&location.lat, &location.lon, None)
+        .await?
+        .properties;
+    let station = observing_station(args.station, &label, &point).await?;
+    let end = Utc::now();
+    let start = end - TimeDelta::hours(i64::from(args.hours));
+    let mut observations = get_observations(&station.station_identifier, start, end, None).await?;
+    let offset = location_offset(&point, &opts);
+    for observation in &mut observations {
+        observation.timestamp = observation.timestamp.with_timezone(&offset);
+    }
+
+    let mut out = if args.chart {
+        render_observation_chart(&station, &observations, &opts)
+    } else {
+        render_observations(&station, &observations, &opts)
+    };
+    if args.compare {
+        let recorded = archive::recorded(&point)?;
+        out.push_str(&render_forecast_check(&recorded, &observations, &opts));
+    }
+    print!("{out}");
+    Ok(())
+}
+
+/// The observation station nearest `point`, named on stderr the way
+/// [`locate`] names a place.
+async fn nearest_station(point: &PointsProperties) -> Result<Station> {
+    let Some(url) = &point.observation_stations else {
+        bail!("Weather.gov lists no observation stations for this location");
+    };
+    let station = get_stations(url)
+        .await?
+        .into_iter()
+        .next()
+        .context("No observation stations near this location")?;
+    announce_station(&station);
+    Ok(station)
+}
+
+/// The station `--station` names, else the one remembered for `label` with
+/// `stations --use`, else the one nearest `point`.
+async fn observing_station(
+    requested: Option<String>,
+    label: &str,
+    point: &PointsProperties,
+) -> Result<Station> {
+    let id = match requested {
+        Some(id) => id,
+        None => match stations::remembered(label)? {
+            Some(id) => id,
+            None => return nearest_station(point).await,
+        },
+    };
+    let station = get_station(&id, None).await?;
+    announce_station(&station);
+    Ok(station)
+}
+
+/// Name the station observations come from, the way [`locate`] names a
+/// place.
+fn announce_station(station: &Station) {
+    eprintln!("Station: {} ({})", station.name, station.station_identifier);
+}
+
+/// `wxdotgov stations`: the stations near a location, or `--use` and
+/// `--forget` to choose one for it.
+async fn print_stations(args: StationsArgs) -> Result<()> {
+    let label = args.lookup.location.label();
+    let opts = RenderOptions {
+        limit: (args.limit != 0).then_some(args.limit),
+        ..args.lookup.display.render_options()
+    };
+    // Located even to --use or --forget, so a place that cannot be found is
+    // never remembered.
+    let location = locate(args.lookup.location).await?;
+
+    if args.forget || args.use_station.is_some() {
+        let Some(path) = stations::default_file() else {
+            bail!(
+                "Neither XDG_STATE_HOME nor HOME is set, so there is nowhere to remember a station"
+            );
+        };
+        let mut choices = dirs::load_state::<stations::Choices>(&path)?;
+        match args.use_station {
+            Some(id) => {
+                let station = get_station(&id, None).await?;
+                choices.insert(label.clone(), station.station_identifier.clone());
+                dirs::save_state(&path, &choices)?;
+                println!(
+                    "Using {} ({}) for {label}",
+                    station.name, station.station_identifier
+                );
+            }
+            None if choices.remove(&label).is_some() => {
+                dirs::save_state(&path, &choices)?;
+                println!("Forgot the station for {label}");
+            }
+            None => println!("No station was chosen for {label}"),
+        }
+        return Ok(());
+    }
+
+    let point = get_weather_point(&location.lat, &location.lon, None)
+        .await?
+        .properties;
+    let Some(url) = &point.observation_stations else {
+        bail!("Weather.gov lists no observation stations for this location");
+    };
+    let list = get_stations(url).await?;
+    let from = (
+        location.lat.parse().context("Unreadable latitude")?,
+        location.lon.parse().context("Unreadable longitude")?,
+    );
+    let chosen = stations::remembered(&label)?;
+    print!("{}", render_stations(&list, from, chosen.as_deref(), &opts));
+    Ok(())
+}
+
+/// The location's UTC offset now, from the time zone `/points` names.
+/// Gridpoint data and observations are timestamped in UTC. Without a zone
+/// this falls back to the machine's, as `--time-zone local` would.
+fn location_offset(point: &PointsProperties, opts: &RenderOptions) -> FixedOffset {
+    let now = opts.times.now;
+    let reference = point
+        .time_zone
+        .as_deref()
+        .and_then(|zone| zone_offset(zone, now))
+        .map(|offset| now.with_timezone(&offset));
+    opts.times.offset(reference.as_ref())
+}
+
+/// `wxdotgov locations`: every match, not only the one a forecast would use.
+async fn print_locations(args: LocationsArgs) -> Result<()> {
+    let input = args.location.input()?;
+    let places = search_locations(input, args.limit, None).await?;
+    if places.is_empty() {
+        bail!("No location found. Make sure your query is correct.");
+    }
+    for (i, place) in places.iter().enumerate() {
+        println!(
+            "{}. {} ({}, {})",
+            i + 1,
+            place.display_name,
+            place.lat,
+            place.lon
+        );
+    }
+    Ok(())
+}
+
+/// Say on stderr how stale a forecast is. Stale forecasts are still printed,
+/// and stdout is left alone so a status bar reading it is not broken by it.
+fn warn_stale(warning: Option<String>) {
+    if let Some(warning) = warning {
+        eprintln!("warning: {warning}");
+    }
+}
+
+/// Whether `args` can be met by a zone forecast, which has no times or
+/// figures to filter, chart or lay out as a document.
+fn check_zone_options(args: &ForecastArgs, forecast_type: ForecastType) -> Result<()> {
+    if forecast_type == ForecastType::Hourly {
+        bail!("Zones have no hourly forecast; hourly output, charts and status lines need a place on land");
+    }
+    if args.output != OutputFormat::Text || args.template.is_some() || args.watch.is_some() {
+        bail!(
+            "A zone forecast is plain text; --output, --template and --watch need a place on land"
+        );
+    }
+    if args.from.is_some() || args.until.is_some() || args.daytime_only || args.night_only {
+        bail!("Zone forecast periods carry no times; the time filters need a place on land");
+    }
+    Ok(())
+}
+
+/// A zone's own forecast, for `--zone` or a point on the water.
+async fn print_zone_forecast(zone: &Zone, opts: &RenderOptions) -> Result<()> {
+    let Some(zone_type) = zone.zone_type().filter(|t| t.has_forecast()) else {
+        bail!(
+            "{} is a {} zone, which has no forecast of its own; use a forecast or marine zone",
+            zone.id,
+            zone.kind
+        );
+    };
+    let forecast = get_zone_forecast(zone_type, &zone.id, None).await?;
+    let output = if zone_type.is_marine() {
+        render_marine(&forecast.periods, opts)
+    } else {
+        render_detailed(&forecast.periods, 0, opts)?
+    };
+    print!("{output}");
+    Ok(())
+}
+
+/// Keep the forecast's highs and lows for `history --compare`. Failing to
+/// record them is no reason to withhold the forecast, so it is only a warning.
+fn record_forecast(point: &PointsProperties, periods: &[Period], units: Units) {
+    if let Err(e) = archive::record(point, periods, units) {
+        eprintln!("warning: the forecast was not recorded for `history --compare`: {e:#}");
+    }
+}
+
+/// `wxdotgov forecast`, `wxdotgov hourly`, and the flat form.
+async fn print_forecast(args: ForecastArgs, requested: ForecastType) -> Result<()> {
+    let filter = args.period_filter();
+    let forecast_type = args.forecast_type(requested);
+    let opts = args.render_options();
+    let check = args.stale_check();
+    let zone_options = check_zone_options(&args, forecast_type);
+
+    if let Some(zone) = &args.location.zone {
+        zone_options?;
+        let zone = locate_zone(zone).await?;
+        return print_zone_forecast(&zone, &opts).await;
+    }
+    let by_point = args.location.point.is_some();
+
+    // Step 1: Geocode with Nominatim.
+    let location = locate(args.location).await?;
 
     // Step 2: Get points data from Weather.gov.
-    let points_resp = get_weather_point(&location.lat, &location.lon, None).await?;
+    let points_resp = match get_weather_point(&location.lat, &location.lon, None).await {
+        Ok(points) => points,
+        // /points answers 404 on the water, where the forecast is the marine
+        // zone's. Only a --point can be offshore; a geocoded place is land.
+        Err(e) if by_point => {
+            let Some(zone) = find_marine_zone(&location.lat, &location.lon, None).await? else {
+                return Err(e);
+            };
+            zone_options?;
+            eprintln!("Marine zone: {} ({})", zone.name, zone.id);
+            return print_zone_forecast(&zone, &opts).await;
+        }
+        Err(e) => return Err(e),
+    };
 
     // Select the forecast URL based on the chosen forecast type.
-    let forecast_url = match args.forecast_type {
+    let forecast_url = match forecast_type {
         ForecastType::Hourly => points_resp
             .properties
             .forecast_hourly
@@ -166,19 +1116,125 @@ async fn main() -> Result<()> {
 
     eprintln!("Fetching forecast from: {forecast_url}");
 
-    let style = Style::from_pretty_flag(args.pretty);
-    // 0 is the "no limit" spelling; every other value is taken literally.
-    let limit = (args.limit != 0).then_some(args.limit);
+    if let Some(interval) = args.watch {
+        let watch = Watch {
+            lat: &location.lat,
+            lon: &location.lon,
+            filter,
+            opts,
+            interval,
+        };
+        let units = args.display.units;
+        return match forecast_type {
+            ForecastType::Detailed => {
+                let point = &points_resp.properties;
+                let fetch = || async move {
+                    let forecast = get_detailed_forecast(forecast_url, units).await?;
+                    record_forecast(point, &forecast.properties.periods, units);
+                    Ok(forecast.properties.periods)
+                };
+                watch.run(fetch, render_detailed).await
+            }
+            ForecastType::Hourly => {
+                let fetch = || async move {
+                    let forecast = get_hourly_forecast(forecast_url, units).await?;
+                    Ok(forecast.properties.periods)
+                };
+                watch.run(fetch, render_hourly).await
+            }
+        };
+    }
 
-    // Step 3: Fetch and display the forecast.
-    let output = match args.forecast_type {
+    // Step 3: Fetch, filter and display the forecast.
+    let output = match forecast_type {
         ForecastType::Detailed => {
-            let forecast_resp = get_detailed_forecast(forecast_url).await?;
-            render_detailed(&forecast_resp.properties.periods, style, limit)
+            let checked = get_checked_forecast(forecast_url, args.display.units, &check).await?;
+            warn_stale(checked.warning);
+            let ForecastProperties {
+                issuance,
+                mut periods,
+            } = checked.forecast.properties;
+            record_forecast(&points_resp.properties, &periods, args.display.units);
+            let filtered = filter.apply(&mut periods, &opts.times)?;
+            match &args.template {
+                Some(source) => {
+                    templated(
+                        source,
+                        &Fetched {
+                            location: &location,
+                            point: &points_resp.properties,
+                            forecast_type,
+                            issuance: &issuance,
+                        },
+                        &periods,
+                        filtered,
+                        &opts,
+                    )
+                    .await?
+                }
+                None => match args.output {
+                    OutputFormat::Text => render_detailed(&periods, filtered, &opts)?,
+                    OutputFormat::Markdown => render_detailed_markdown(&periods, filtered, &opts),
+                    OutputFormat::Html => render_detailed_html(&periods, filtered, &opts),
+                    OutputFormat::Ics => {
+                        let alerts = if args.no_alerts {
+                            Vec::new()
+                        } else {
+                            get_active_alerts(&location.lat, &location.lon, None).await?
+                        };
+                        render_ics(
+                            &periods,
+                            &alerts,
+                            &points_resp.properties,
+                            &location.display_name,
+                            &opts,
+                        )
+                    }
+                },
+            }
         }
         ForecastType::Hourly => {
-            let hourly_forecast_resp = get_hourly_forecast(forecast_url).await?;
-            render_hourly(&hourly_forecast_resp.properties.periods, style, limit)
+            let checked =
+                get_checked_hourly_forecast(forecast_url, args.display.units, &check).await?;
+            warn_stale(checked.warning);
+            let HourlyForecastProperties {
+                issuance,
+                mut periods,
+            } = checked.forecast.properties;
+            let filtered = filter.apply(&mut periods, &opts.times)?;
+            if let Some(source) = &args.template {
+                templated(
+                    source,
+                    &Fetched {
+                        location: &location,
+                        point: &points_resp.properties,
+                        forecast_type,
+                        issuance: &issuance,
+                    },
+                    &periods,
+                    filtered,
+                    &opts,
+                )
+                .await?
+            } else if args.waybar {
+                let alerts = get_active_alerts(&location.lat, &location.lon, None).await?;
+                let template = match &args.format {
+                    Some(template) => template.clone(),
+                    None => DEFAULT_TEMPLATE.parse()?,
+                };
+                render_waybar(&periods, &alerts, &location.display_name, &template, &opts)?
+            } else if let Some(template) = &args.format {
+                render_line(&periods, template, &opts)?
+            } else if args.chart {
+                render_chart(&periods, filtered, &opts)
+            } else {
+                match args.output {
+                    OutputFormat::Text => render_hourly(&periods, filtered, &opts)?,
+                    OutputFormat::Markdown => render_hourly_markdown(&periods, filtered, &opts),
+                    OutputFormat::Html => render_hourly_html(&periods, filtered, &opts),
+                    OutputFormat::Ics => unreachable!("calendars use the detailed forecast"),
+                }
+            }
         }
     };
     print!("{output}");
@@ -190,7 +1246,23 @@ async fn main() -> Result<()> {
 mod tests {
     mod api_tests;
     mod app_tests;
+    mod archive_tests;
+    mod cache_tests;
+    mod completions_tests;
+    mod exporter_tests;
+    mod filter_tests;
+    mod ics_tests;
     mod integration_tests;
     mod location_tests;
+    mod notify_tests;
     mod render_tests;
+    mod serve_tests;
+    mod stations_tests;
+    mod status_tests;
+    mod tape_tests;
+    mod template_tests;
+    mod times_tests;
+    mod tui_tests;
+    mod units_tests;
+    mod watch_tests;
 }
@@ -0,0 +1,47 @@
+//! Prometheus text exposition.
+//!
+//! The exporter's `/metrics` is a handful of gauges and counters, which the
+//! text format makes easy to write directly; a metrics crate would bring a
+//! registry and a global recorder for what is a page of `name{labels} value`
+//! lines.
+//!
+//! Format reference:
+//! <https://prometheus.io/docs/instrumenting/exposition_formats/#text-based-format>
+
+/// A `/metrics` page being written.
+#[derive(Default)]
+pub struct Exposition {
+    out: String,
+}
+
+impl Exposition {
+    /// Start a metric family. Its samples must follow before the next family.
+    pub fn family(&mut self, name: &str, kind: &str, help: &str) {
+        self.out.push_str(&format!("# HELP {name} {help}\n"));
+        self.out.push_str(&format!("# TYPE {name} {kind}\n"));
+    }
+
+    pub fn sample(&mut self, name: &str, labels: &[(&str, &str)], value: f64) {
+        self.out.push_str(name);
+        if !labels.is_empty() {
+            let labels: Vec<String> = labels
+                .iter()
+                .map(|(k, v)| format!("{k}=\"{}\"", escape_label(v)))
+                .collect();
+            self.out.push_str(&format!("{{{}}}", labels.join(",")));
+        }
+        self.out.push_str(&format!(" {value}\n"));
+    }
+
+    pub fn finish(self) -> String {
+        self.out
+    }
+}
+
+/// Label values may hold anything; backslash, quote and newline are escaped.
+fn escape_label(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\n', "\\n")
+}
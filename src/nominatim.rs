@@ -13,16 +13,20 @@
 //!
 //! ", USA" is appended to keep results inside the United States, since
 //! weather.gov only covers US locations. Nominatim answers with an array
-//! ordered by relevance; only the first result is used.
+//! ordered by relevance. Forecasts use only the first result; `wxdotgov
+//! locations` asks for several, to show what an ambiguous name matches.
 //!
 //! Two operational notes:
 //!
 //! - The usage policy requires an identifying User-Agent with real contact
 //!   details and permits blocking clients without one. That header comes from
 //!   [`crate::http`], shared with the weather.gov client.
-//! - The policy also caps clients at one request per second. This tool makes a
-//!   single geocoding request per invocation, so it does not rate-limit
-//!   internally; a caller looping over it would need to.
+//! - The policy also caps clients at one request per second. Every request is
+//!   paced by [`crate::http::send`], whose bucket for this host is shared
+//!   by concurrent tasks under `serve` and by back-to-back runs from a shell
+//!   loop, so no caller needs to pace itself. Results are also cached for a
+//!   day, so a place is looked up once rather than on every dashboard refresh
+//!   or status bar tick.
 //!
 //! Errors carry the HTTP status. A blocked or rate-limited request is answered
 //! with an HTML error page rather than JSON, so the status is checked before
@@ -30,12 +34,19 @@
 //!
 //! API docs: <https://nominatim.org/release-docs/develop/api/Search/>
 
+use std::time::Duration;
+
 use anyhow::{bail, Context, Result};
 use serde::Deserialize;
 
+use crate::cache;
 use crate::http;
 use crate::LocationInput;
 
+/// How long a geocoding result may be served from [`crate::cache`]. Places do
+/// not move, and the policy asks clients to cache results.
+const MAX_AGE: Duration = Duration::from_secs(24 * 60 * 60);
+
 #[derive(Debug, Deserialize, Clone)]
 pub struct NominatimLocation {
     pub lat: String,
@@ -47,9 +58,22 @@ pub async fn get_lat_lon(
     input: LocationInput,
     base_url: Option<&str>,
 ) -> Result<NominatimLocation> {
+    search_locations(input, 1, base_url)
+        .await?
+        .into_iter()
+        .next()
+        .context("No location found. Make sure your query is correct.")
+}
+
+/// Up to `limit` places matching `input`, most relevant first. Empty when
+/// nothing matches.
+pub async fn search_locations(
+    input: LocationInput,
+    limit: usize,
+    base_url: Option<&str>,
+) -> Result<Vec<NominatimLocation>> {
     let default_base_url = "https://nominatim.openstreetmap.org";
     let base_url = base_url.unwrap_or(default_base_url);
-    let client = http::client()?;
 
     let query = match input {
         LocationInput::PostalCode(code) => format!("{code}, USA"),
@@ -59,15 +83,27 @@ pub async fn get_lat_lon(
     };
 
     let url = format!("{base_url}/search");
+    let limit = limit.to_string();
+    let params = [("q", query.as_str()), ("format", "json"), ("limit", &limit)];
+    let key = cache::key(&url, &params);
 
-    let response = client
-        .get(&url)
-        .query(&[
-            ("q", &query),
-            ("format", &"json".to_string()),
-            ("limit", &"1".to_string()),
-        ])
-        .send()
+    let body = match cache::lookup(&key, MAX_AGE) {
+        Some(body) => body,
+        None => {
+            let body = search(&url, &params).await?;
+            cache::store(&key, &body);
+            body
+        }
+    };
+
+    serde_json::from_str(&body).context("Error parsing JSON from Nominatim response")
+}
+
+/// Send the search, returning the body of a successful response.
+async fn search(url: &str, params: &[(&str, &str)]) -> Result<String> {
+    let client = http::client()?;
+
+    let response = http::send(http::Upstream::Nominatim, client.get(url).query(params))
         .await
         .context("Error sending request to Nominatim")?;
 
@@ -89,16 +125,5 @@ pub async fn get_lat_lon(
         bail!("Nominatim returned an error (HTTP {status}): {body}");
     }
 
-    let body = response
-        .text()
-        .await
-        .context("Error reading response body")?;
-
-    let locations: Vec<NominatimLocation> =
-        serde_json::from_str(&body).context("Error parsing JSON from Nominatim response")?;
-
-    locations
-        .into_iter()
-        .next()
-        .context("No location found. Make sure your query is correct.")
+    response.text().await.context("Error reading response body")
 }
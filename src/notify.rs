@@ -0,0 +1,377 @@
+//! `wxdotgov notify`: deliver each NWS alert once, as it is issued.
+//!
+//! Polls `/alerts/active` for every `--location` and compares the answer with
+//! what was active last time. An alert not seen before is delivered as
+//! `new`, `update` or `cancel` according to its `messageType` (the NWS issues
+//! updates and cancellations as alerts of their own, with new ids); an alert
+//! that has dropped off the active list is delivered as `ended`.
+//!
+//! What was active is kept in a state file, so restarting the daemon does not
+//! repeat every alert in effect:
+//!
+//! ```json
+//! {"98101": [{"id": "urn:oid:2.49.0.1.840.0.abc", "event": "Wind Advisory", ...}]}
+//! ```
+//!
+//! Every notification is printed to stdout, and also sent to each sink given:
+//!
+//! - `--command`: run with `sh -c`, the alert in `WXDOTGOV_*` environment
+//!   variables (see [`command_env`]). `notify-send` makes this a desktop
+//!   notification.
+//! - `--webhook`: POSTed as JSON, `{"location", "status", "alert"}`.
+//! - `--slack-webhook`: POSTed as a Slack `{"text"}` message.
+//!
+//! A sink that fails is reported and skipped; the alert still counts as
+//! delivered, so a webhook that is down for an hour does not receive that
+//! hour's alerts in a burst once it recovers.
+
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::Duration;
+
+use anyhow::{bail, Context, Result};
+use serde::Serialize;
+
+use crate::cache::{self, Cache};
+use crate::dirs;
+use crate::exporter::Target;
+use crate::http;
+use crate::nominatim::get_lat_lon;
+use crate::times::parse_refresh_interval;
+use crate::weatherdotgov::{get_active_alerts, Alert, Severity};
+
+#[derive(Debug, clap::Args)]
+pub struct NotifyArgs {
+    /// A location to watch: a ZIP code, "City" or "City, ST". Repeat for
+    /// more than one.
+    #[arg(long = "location", value_name = "PLACE", required = true)]
+    locations: Vec<Target>,
+
+    /// How often to poll, e.g. 90s, 2m, 1h. At least 1m.
+    #[arg(long, default_value = "2m", value_parser = parse_refresh_interval)]
+    interval: Duration,
+
+    /// Where to remember which alerts were delivered. Defaults to
+    /// $XDG_STATE_HOME/wxdotgov/notify.json.
+    #[arg(long, value_name = "PATH")]
+    state_file: Option<PathBuf>,
+
+    /// Only deliver alerts at least this severe.
+    #[arg(long, value_enum, value_name = "SEVERITY")]
+    min_severity: Option<Severity>,
+
+    /// Only deliver this event type, e.g. "Flood Warning". Repeat for more
+    /// than one.
+    #[arg(long = "event", value_name = "EVENT")]
+    events: Vec<String>,
+
+    /// Run this shell command for each notification, with the alert in
+    /// WXDOTGOV_* environment variables.
+    #[arg(long, value_name = "COMMAND")]
+    command: Option<String>,
+
+    /// POST each notification to this URL as JSON.
+    #[arg(long, value_name = "URL")]
+    webhook: Option<String>,
+
+    /// POST each notification to this Slack incoming-webhook URL.
+    #[arg(long, value_name = "URL")]
+    slack_webhook: Option<String>,
+
+    /// Poll once and exit, e.g. to run from cron.
+    #[arg(long)]
+    once: bool,
+}
+
+/// Why an alert is being delivered.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Status {
+    New,
+    Update,
+    Cancel,
+    Ended,
+}
+
+impl Status {
+    fn of(alert: &Alert) -> Self {
+        match alert.message_type.as_deref() {
+            Some("Update") => Status::Update,
+            Some("Cancel") => Status::Cancel,
+            _ => Status::New,
+        }
+    }
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Status::New => "new",
+            Status::Update => "update",
+            Status::Cancel => "cancel",
+            Status::Ended => "ended",
+        }
+    }
+}
+
+/// What is delivered to each sink, and the `--webhook` body.
+#[derive(Debug, Serialize)]
+pub struct Notification<'a> {
+    pub location: &'a str,
+    pub status: Status,
+    pub alert: &'a Alert,
+}
+
+impl Notification<'_> {
+    /// One line, for stdout and Slack.
+    pub fn summary(&self) -> String {
+        let alert = self.alert;
+        let mut line = format!(
+            "{}: {} {} ({})",
+            self.location,
+            self.status.as_str(),
+            alert.event,
+            alert.severity.as_str()
+        );
+        if let Some(headline) = &alert.headline {
+            line.push_str(&format!(": {headline}"));
+        }
+        line
+    }
+}
+
+/// The alerts to deliver, and why, given what was active last time.
+///
+/// Each message is delivered once, so an update is a change of its own. But
+/// the alert it replaces drops off the active list when it is issued, and
+/// has not ended: an alert only ends when nothing active is the same alert
+/// ([`Alert::same_as`]).
+pub fn changes<'a>(seen: &'a [Alert], active: &'a [Alert]) -> Vec<(Status, &'a Alert)> {
+    let mut changes: Vec<(Status, &Alert)> = active
+        .iter()
+        .filter(|alert| !seen.iter().any(|a| a.id == alert.id))
+        .map(|alert| (Status::of(alert), alert))
+        .collect();
+    changes.extend(
+        seen.iter()
+            .filter(|alert| !active.iter().any(|a| a.same_as(alert)))
+            .map(|alert| (Status::Ended, alert)),
+    );
+    changes
+}
+
+/// `--min-severity` and `--event`. The default passes everything.
+#[derive(Clone, Debug, Default)]
+pub struct AlertFilter {
+    pub min_severity: Option<Severity>,
+    pub events: Vec<String>,
+}
+
+impl AlertFilter {
+    pub fn wants(&self, alert: &Alert) -> bool {
+        let severe_enough = self.min_severity.is_none_or(|min| alert.severity >= min);
+        let event_listed = self.events.is_empty()
+            || self
+                .events
+                .iter()
+                .any(|e| e.trim().eq_ignore_ascii_case(&alert.event));
+        severe_enough && event_listed
+    }
+}
+
+/// The environment a `--command` runs with.
+pub fn command_env(n: &Notification) -> Vec<(&'static str, String)> {
+    let alert = n.alert;
+    let time = |t: &Option<chrono::DateTime<chrono::FixedOffset>>| {
+        t.map(|t| t.to_rfc3339()).unwrap_or_default()
+    };
+    vec![
+        ("WXDOTGOV_LOCATION", n.location.to_string()),
+        ("WXDOTGOV_STATUS", n.status.as_str().to_string()),
+        ("WXDOTGOV_ALERT_ID", alert.id.clone()),
+        ("WXDOTGOV_ALERT_EVENT", alert.event.clone()),
+        (
+            "WXDOTGOV_ALERT_HEADLINE",
+            alert.headline.clone().unwrap_or_default(),
+        ),
+        (
+            "WXDOTGOV_ALERT_SEVERITY",
+            alert.severity.as_str().to_string(),
+        ),
+        ("WXDOTGOV_ALERT_ONSET", time(&alert.onset)),
+        ("WXDOTGOV_ALERT_ENDS", time(&alert.ends)),
+    ]
+}
+
+/// Somewhere a notification is sent, besides stdout.
+#[derive(Clone, Debug)]
+pub enum Sink {
+    Command(String),
+    Webhook(String),
+    Slack(String),
+}
+
+#[derive(Serialize)]
+struct SlackMessage {
+    text: String,
+}
+
+impl Sink {
+    async fn deliver(&self, n: &Notification<'_>) -> Result<()> {
+        match self {
+            Sink::Command(command) => {
+                let mut process = std::process::Command::new("sh");
+                process.arg("-c").arg(command).envs(command_env(n));
+                // The command may be slow; keep it off the runtime's threads.
+                let status = tokio::task::spawn_blocking(move || process.status())
+                    .await
+                    .context("Error waiting for --command")?
+                    .context("Error running --command")?;
+                if !status.success() {
+                    bail!("--command exited with {status}");
+                }
+                Ok(())
+            }
+            Sink::Webhook(url) => post(url, n).await,
+            Sink::Slack(url) => {
+                let message = SlackMessage {
+                    text: format!("⚠ {}", n.summary()),
+                };
+                post(url, &message).await
+            }
+        }
+    }
+}
+
+async fn post<T: Serialize>(url: &str, body: &T) -> Result<()> {
+    let response = http::client()?
+        .post(url)
+        .json(body)
+        .send()
+        .await
+        .with_context(|| format!("Error sending to {url}"))?;
+    let status = response.status();
+    if !status.is_success() {
+        let body = http::error_body(response).await;
+        bail!("{url} returned an error (HTTP {status}): {body}");
+    }
+    Ok(())
+}
+
+/// The alerts active at each location when it was last polled, by label.
+type State = BTreeMap<String, Vec<Alert>>;
+
+/// `$XDG_STATE_HOME/wxdotgov/notify.json`, or its `~/.local/state` default.
+fn default_state_file() -> Option<PathBuf> {
+    Some(dirs::state_dir()?.join("notify.json"))
+}
+
+/// The locations being watched and where their notifications go.
+pub struct Notifier {
+    targets: Vec<Target>,
+    filter: AlertFilter,
+    sinks: Vec<Sink>,
+    state_file: PathBuf,
+    cache: Arc<Cache>,
+    /// Upstream base URLs, `None` for the real services. Tests point these at
+    /// a mock server.
+    pub nominatim_url: Option<String>,
+    pub weather_url: Option<String>,
+}
+
+impl Notifier {
+    pub fn new(
+        targets: Vec<Target>,
+        filter: AlertFilter,
+        sinks: Vec<Sink>,
+        state_file: PathBuf,
+    ) -> Self {
+        Notifier {
+            targets,
+            filter,
+            sinks,
+            state_file,
+            cache: Arc::default(),
+            nominatim_url: None,
+            weather_url: None,
+        }
+    }
+
+    async fn active(&self, target: &Target) -> Result<Vec<Alert>> {
+        let location = get_lat_lon(target.input.clone(), self.nominatim_url.as_deref()).await?;
+        let alerts =
+            get_active_alerts(&location.lat, &location.lon, self.weather_url.as_deref()).await?;
+        Ok(alerts
+            .into_iter()
+            .filter(|alert| self.filter.wants(alert))
+            .collect())
+    }
+
+    /// Poll every location once and deliver what changed, returning how many
+    /// notifications were sent. A location that cannot be polled is reported
+    /// and keeps its previous state, so nothing is mistaken for having ended.
+    pub async fn poll(&self) -> Result<usize> {
+        let mut state: State = dirs::load_state(&self.state_file)?;
+        let mut sent = 0;
+        for target in &self.targets {
+            let active = match cache::scope(self.cache.clone(), self.active(target)).await {
+                Ok(active) => active,
+                Err(e) => {
+                    eprintln!("warning: polling {}: {e:#}", target.label);
+                    continue;
+                }
+            };
+            let seen = state.remove(&target.label).unwrap_or_default();
+            for (status, alert) in changes(&seen, &active) {
+                let notification = Notification {
+                    location: &target.label,
+                    status,
+                    alert,
+                };
+                println!("{}", notification.summary());
+                for sink in &self.sinks {
+                    if let Err(e) = sink.deliver(&notification).await {
+                        eprintln!("warning: {e:#}");
+                    }
+                }
+                sent += 1;
+            }
+            state.insert(target.label.clone(), active);
+        }
+        dirs::save_state(&self.state_file, &state)?;
+        Ok(sent)
+    }
+}
+
+/// Poll on a timer until the process is stopped, or once with `--once`.
+pub async fn run(args: NotifyArgs) -> Result<()> {
+    let state_file = match args.state_file.or_else(default_state_file) {
+        Some(path) => path,
+        None => bail!("Neither XDG_STATE_HOME nor HOME is set; give --state-file"),
+    };
+    let sinks = [
+        args.command.map(Sink::Command),
+        args.webhook.map(Sink::Webhook),
+        args.slack_webhook.map(Sink::Slack),
+    ]
+    .into_iter()
+    .flatten()
+    .collect();
+    let filter = AlertFilter {
+        min_severity: args.min_severity,
+        events: args.events,
+    };
+    let notifier = Notifier::new(args.locations, filter, sinks, state_file);
+
+    if args.once {
+        notifier.poll().await?;
+        return Ok(());
+    }
+    let mut timer = tokio::time::interval(args.interval);
+    timer.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+    loop {
+        timer.tick().await;
+        // Only the state file failing is fatal: polling without it would
+        // repeat every alert on the next start.
+        notifier.poll().await?;
+    }
+}
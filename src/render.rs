@@ -10,7 +10,10 @@
 //! arrangements of the same fields and nothing holding the two in parity --
 //! adding a field to one branch left the other silently printing the old set.
 //! Rendering both styles through one function makes that parity directly
-//! assertable (see `tests/render_tests.rs`).
+//! assertable (see `tests/render_tests.rs`). The detailed and hourly forecasts
+//! go further: each is one bundled template (see [`crate::template`]) that
+//! prints its fields once and lets [`paint`] and the `pretty` flag choose the
+//! style, so `--template detailed` is exactly what `wxdotgov forecast` prints.
 //!
 //! Note the tests deliberately assert on field *values*, never on ANSI escape
 //! codes. Asserting that `.cyan()` emits `\x1b[36m` tests the `colored` crate
@@ -18,10 +21,27 @@
 //! real users never encounter: `colored` suppresses escapes when stdout is not
 //! a TTY, so `wxdotgov --pretty > out.txt` correctly produces the pretty
 //! *layout* with no colour at all.
+//!
+//! `--output markdown` and `--output html` are documents rather than terminal
+//! output, so they have their own functions below instead of more `Style`
+//! arms, but they are held to the same parity: the tests check that every
+//! field the plain layout prints appears in both.
 
+use anyhow::Result;
+use chrono::{DateTime, FixedOffset, NaiveTime, TimeDelta};
+use clap::ValueEnum;
 use colored::*;
+use serde::Serialize;
 
-use crate::weatherdotgov::{HourlyPeriod, Period};
+use crate::archive::Recorded;
+use crate::stations::{bearing, compass, distance_km};
+use crate::template;
+use crate::times::{DisplayZone, TimeDisplay};
+use crate::units::{Measurement, QuantitativeValue, Units};
+use crate::weatherdotgov::{
+    Alert, FireWeather, GridLayer, HourlyPeriod, Observation, Office, Period, PointsProperties,
+    Product, Station, ZonePeriod,
+};
 
 /// How a forecast is laid out and coloured.
 #[derive(Clone, Copy, Debug, PartialEq, Eq)]
@@ -41,8 +61,64 @@ impl Style {
     }
 }
 
+/// What kind of document to produce. `Text` is the terminal output, plain or
+/// pretty as `--pretty` says; the others ignore `--pretty`.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, ValueEnum)]
+pub enum OutputFormat {
+    #[default]
+    Text,
+    Markdown,
+    Html,
+    /// An iCalendar file; see [`crate::ics`].
+    Ics,
+}
+
+/// How to render, as opposed to what: everything here comes from the command
+/// line rather than the API.
+#[derive(Clone, Copy, Debug)]
+pub struct RenderOptions {
+    pub style: Style,
+    /// `None` means "all of them".
+    pub limit: Option<usize>,
+    pub units: Units,
+    pub times: TimeDisplay,
+}
+
 const SEPARATOR: &str = "-------------------------------------";
 
+/// What a piece of text is, which decides its colour in the pretty style.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Paint {
+    Heading,
+    Day,
+    /// A period's name or time, which starts its entry.
+    Name,
+    Narrative,
+    Temperature,
+    /// Sky and wind.
+    Conditions,
+    /// Rules, notes and secondary readings.
+    Dim,
+}
+
+/// `text` in the colour of `paint` when `style` is pretty, and as it is
+/// otherwise.
+pub fn paint(text: &str, paint: Paint, style: Style) -> String {
+    if style == Style::Plain {
+        return text.to_string();
+    }
+    let painted = match paint {
+        Paint::Heading => text.bold().underline().bright_white(),
+        Paint::Day => text.bold().magenta(),
+        Paint::Name => text.bold().blue(),
+        Paint::Narrative => text.green(),
+        Paint::Temperature => text.yellow(),
+        Paint::Conditions => text.cyan(),
+        Paint::Dim => text.dimmed(),
+    };
+    painted.to_string()
+}
+
 /// The number of periods to print when `--limit` is not given.
 ///
 /// The NWS hourly endpoint returns a week-plus of hourly entries, and each one
@@ -52,7 +128,7 @@ const SEPARATOR: &str = "-------------------------------------";
 pub const DEFAULT_LIMIT: usize = 24;
 
 /// Take at most `limit` periods, where `None` means "all of them".
-fn limited<T>(periods: &[T], limit: Option<usize>) -> &[T] {
+pub fn limited<T>(periods: &[T], limit: Option<usize>) -> &[T] {
     match limit {
         Some(n) => &periods[..n.min(periods.len())],
         None => periods,
@@ -60,92 +136,1059 @@ fn limited<T>(periods: &[T], limit: Option<usize>) -> &[T] {
 }
 
 fn header(text: &str, style: Style) -> String {
+    format!("\n{}\n\n", paint(text, Paint::Heading, style))
+}
+
+/// The heading printed above the first period of each calendar day.
+fn day_heading(text: &str, style: Style) -> String {
     match style {
-        Style::Pretty => format!("\n{}\n\n", text.bold().underline().bright_white()),
-        Style::Plain => format!("\n{text}\n\n"),
+        Style::Pretty => format!("{}\n", paint(text, Paint::Day, style)),
+        Style::Plain => format!("== {text} ==\n"),
     }
 }
 
 fn separator(style: Style) -> String {
-    match style {
-        Style::Pretty => format!("{}\n", SEPARATOR.dimmed()),
-        Style::Plain => format!("{SEPARATOR}\n"),
-    }
+    format!("{}\n", paint(SEPARATOR, Paint::Dim, style))
 }
 
 /// Tell the reader that output was cut short, and how to see the rest.
 ///
 /// Silently dropping periods would be indistinguishable from the API having
-/// returned fewer of them.
-fn truncation_note(total: usize, shown: usize, style: Style) -> String {
-    if shown >= total {
-        return String::new();
-    }
-    let text = format!(
-        "... {} more period(s) not shown (use --limit 0 to show all)",
-        total - shown
-    );
-    match style {
-        Style::Pretty => format!("{}\n", text.dimmed()),
-        Style::Plain => format!("{text}\n"),
+/// returned fewer of them. Periods removed by the time filters and periods cut
+/// by `--limit` are reported separately, because each has a different remedy.
+/// `total` is the count that survived filtering; `filtered` is how many did not.
+fn truncation_note(filtered: usize, total: usize, shown: usize, style: Style) -> String {
+    truncation_lines(filtered, total, shown)
+        .into_iter()
+        .map(|text| format!("{}\n", paint(&text, Paint::Dim, style)))
+        .collect()
+}
+
+/// The text of [`truncation_note`], one entry per line, for the document
+/// formats and templates to mark up their own way.
+pub fn truncation_lines(filtered: usize, total: usize, shown: usize) -> Vec<String> {
+    let mut lines = Vec::new();
+    if filtered > 0 {
+        lines.push(format!(
+            "... {filtered} period(s) outside the requested time window not shown"
+        ));
+    }
+    if shown < total {
+        lines.push(format!(
+            "... {} more period(s) not shown (use --limit 0 to show all)",
+            total - shown
+        ));
     }
+    lines
+}
+
+/// Render the daily/detailed forecast, grid or zone, through the bundled
+/// `detailed` template.
+///
+/// `periods` is the list left after filtering, and `filtered` how many
+/// periods the filters removed from it.
+pub fn render_detailed<P: Serialize>(
+    periods: &[P],
+    filtered: usize,
+    opts: &RenderOptions,
+) -> Result<String> {
+    template::render_layout(&template::DETAILED, periods, filtered, opts)
 }
 
-/// Render the daily/detailed forecast.
-pub fn render_detailed(periods: &[Period], style: Style, limit: Option<usize>) -> String {
-    let shown = limited(periods, limit);
-    let mut out = header("Weather Forecast:", style);
+/// Render a marine zone forecast.
+///
+/// Marine text packs wind, seas and weather into one paragraph per period.
+/// Each sentence is sorted under `Wind` (anything in knots), `Seas` (waves,
+/// swell or seas) or left as it was, so the two figures a skipper looks for
+/// first line up down the page. Nothing is reworded or converted: the NWS
+/// writes marine forecasts in knots and feet whatever `--units` says.
+pub fn render_marine(periods: &[ZonePeriod], opts: &RenderOptions) -> String {
+    let style = opts.style;
+    let shown = limited(periods, opts.limit);
+    let mut out = header("Marine Forecast:", style);
 
     for period in shown {
+        let (mut wind, mut seas, mut other) = (Vec::new(), Vec::new(), Vec::new());
+        for sentence in sentences(&period.detailed_forecast) {
+            let lower = sentence.to_lowercase();
+            if lower.split_whitespace().any(|word| word == "kt") {
+                wind.push(sentence);
+            } else if ["seas", "waves", "swell"].iter().any(|w| lower.contains(w)) {
+                seas.push(sentence);
+            } else {
+                other.push(sentence);
+            }
+        }
+
         match style {
-            Style::Pretty => {
-                out.push_str(&format!("{}\n", period.name.bold().blue()));
-                out.push_str(&format!("{}\n", period.detailed_forecast.green()));
+            Style::Pretty => out.push_str(&format!("{}\n", period.name.bold().blue())),
+            Style::Plain => out.push_str(&format!("{}\n", period.name)),
+        }
+        for (label, group) in [("Wind:", &wind), ("Seas:", &seas)] {
+            if group.is_empty() {
+                continue;
             }
-            Style::Plain => {
-                out.push_str(&format!("{}: {}\n", period.name, period.detailed_forecast));
+            let text = format!("{}.", group.join(". "));
+            match style {
+                Style::Pretty => out.push_str(&format!("  {} {}\n", label.bold(), text.green())),
+                Style::Plain => out.push_str(&format!("  {label} {text}\n")),
             }
         }
+        if !other.is_empty() {
+            out.push_str(&format!("  {}.\n", other.join(". ")));
+        }
         out.push_str(&separator(style));
     }
 
-    out.push_str(&truncation_note(periods.len(), shown.len(), style));
+    out.push_str(&truncation_note(0, periods.len(), shown.len(), style));
     out
 }
 
-/// Render the hourly forecast.
-pub fn render_hourly(periods: &[HourlyPeriod], style: Style, limit: Option<usize>) -> String {
-    let shown = limited(periods, limit);
-    let mut out = header("Hourly Weather Forecast:", style);
+/// The sentences of forecast text, without their full stops.
+fn sentences(text: &str) -> Vec<&str> {
+    text.split(". ")
+        .map(|sentence| sentence.trim().trim_end_matches('.'))
+        .filter(|sentence| !sentence.is_empty())
+        .collect()
+}
 
-    for period in shown {
+/// Render the alerts in effect, or say there are none.
+pub fn render_alerts(alerts: &[Alert], opts: &RenderOptions) -> String {
+    let style = opts.style;
+    let mut out = header("Active Alerts:", style);
+    if alerts.is_empty() {
+        out.push_str("No active alerts.\n");
+        return out;
+    }
+
+    for alert in alerts {
+        out.push_str(&alert_block(alert, opts));
+    }
+    out
+}
+
+/// One alert as [`render_alerts`] lists it: event, headline and end.
+fn alert_block(alert: &Alert, opts: &RenderOptions) -> String {
+    let style = opts.style;
+    let title = format!("⚠ {} ({})", alert.event, alert.severity.as_str());
+    let mut out = match style {
+        Style::Pretty => format!("{}\n", title.bold().red()),
+        Style::Plain => format!("{title}\n"),
+    };
+    if let Some(headline) = &alert.headline {
+        out.push_str(&format!("  {headline}\n"));
+    }
+    if let Some(ends) = &alert.ends {
+        out.push_str(&format!("  Until {}\n", opts.times.label(ends)));
+    }
+    out.push_str(&separator(style));
+    out
+}
+
+/// Render the fire weather alerts in effect, then each of the next `days`
+/// calendar days at its worst: the lowest humidity and the highest gusts,
+/// mixing height, transport wind and indices, each with when it comes.
+///
+/// Gridpoint times are all UTC, so the days, and the times within them, are
+/// found in `offset`: the location's, or the machine's under `--time-zone
+/// local`, as the caller picks it. The display zone is not applied again on
+/// top, which would label a day by the date its first hour falls on elsewhere.
+pub fn render_fire(
+    fire: &FireWeather,
+    alerts: &[Alert],
+    zone: Option<&str>,
+    offset: FixedOffset,
+    days: u32,
+    opts: &RenderOptions,
+) -> String {
+    let style = opts.style;
+    let mut out = header("Fire Weather:", style);
+    if let Some(zone) = zone {
+        out.push_str(&format!("Fire weather zone: {zone}\n"));
+    }
+    if alerts.is_empty() {
+        out.push_str("No Red Flag Warnings or Fire Weather Watches.\n");
+    }
+    out.push_str(&separator(style));
+    for alert in alerts {
+        out.push_str(&alert_block(alert, opts));
+    }
+
+    let times = TimeDisplay {
+        zone: DisplayZone::Location,
+        ..opts.times
+    };
+    let now = times.now.with_timezone(&offset);
+    let midnight = now - now.time().signed_duration_since(NaiveTime::MIN);
+    let haines: Option<fn(f64) -> &'static str> = Some(haines_risk);
+    let rows: [(&str, &GridLayer, bool, _); 6] = [
+        ("Min humidity:", &fire.relative_humidity, false, None),
+        ("Max wind gust:", &fire.wind_gust, true, None),
+        ("Mixing height:", &fire.mixing_height, true, None),
+        ("Transport wind:", &fire.transport_wind_speed, true, None),
+        ("Haines index:", &fire.haines_index, true, haines),
+        ("Red Flag threat:", &fire.red_flag_threat_index, true, None),
+    ];
+    for day in 0..days {
+        let end = midnight + TimeDelta::days(i64::from(day) + 1);
+        let start = (end - TimeDelta::days(1)).max(now);
+        let mut lines = Vec::new();
+        for (label, layer, highest, classify) in rows {
+            let Some((at, value)) = layer.extreme(start, end, highest) else {
+                continue;
+            };
+            let Some(shown) = fire_value(&value, classify, opts.units) else {
+                continue;
+            };
+            let at = times.label(&at.with_timezone(&offset));
+            lines.push(match style {
+                Style::Pretty => format!(
+                    "  {} {} ({at})\n",
+                    format!("{label:<17}").bold(),
+                    shown.green()
+                ),
+                Style::Plain => format!("  {label:<17} {shown} ({at})\n"),
+            });
+        }
+        // The grid runs about a week out; past that there is nothing to say.
+        if lines.is_empty() {
+            break;
+        }
+        out.push_str(&day_heading(&times.day_label(&start), style));
+        out.push_str(&lines.concat());
+        out.push_str(&separator(style));
+    }
+    out
+}
+
+/// A fire weather value as printed: a measurement in `units`, or for the
+/// unitless indices the number alone, with its class where `classify` gives
+/// one.
+fn fire_value(
+    value: &QuantitativeValue,
+    classify: Option<fn(f64) -> &'static str>,
+    units: Units,
+) -> Option<String> {
+    if !value.unit_code.is_empty() {
+        return value.to_units(units).map(|m| m.to_string());
+    }
+    let v = value.value?;
+    Some(match classify {
+        Some(classify) => format!("{v:.0} ({})", classify(v)),
+        None => format!("{v:.0}"),
+    })
+}
+
+/// The fire danger a Haines index stands for.
+fn haines_risk(index: f64) -> &'static str {
+    match index.round() as i64 {
+        ..=3 => "very low",
+        4 => "low",
+        5 => "moderate",
+        _ => "high",
+    }
+}
+
+/// Render a text product such as the Area Forecast Discussion. The text is
+/// already laid out by the forecaster, so it is printed as issued.
+pub fn render_product(product: &Product, opts: &RenderOptions) -> String {
+    let style = opts.style;
+    let mut out = header(&format!("{}:", product.product_name), style);
+    let issued = format!(
+        "Issued {} by {}",
+        opts.times.label(&product.issuance_time),
+        product.issuing_office
+    );
+    match style {
+        Style::Pretty => out.push_str(&format!("{}\n", issued.dimmed())),
+        Style::Plain => out.push_str(&format!("{issued}\n")),
+    }
+    out.push_str(&separator(style));
+    out.push_str(product.product_text.trim_end());
+    out.push('\n');
+    out
+}
+
+/// Render what `/points` and `/offices` say about a location: the office that
+/// forecasts for it and the zones it is in. Fields the API left out are
+/// skipped rather than printed blank.
+pub fn render_info(point: &PointsProperties, office: &Office, opts: &RenderOptions) -> String {
+    let style = opts.style;
+    let mut out = header("Forecast Office:", style);
+    out.push_str(&format!("{} ({})\n", office.name, office.id));
+    if let Some(address) = &office.address {
+        out.push_str(&format!(
+            "{}, {}, {} {}\n",
+            address.street_address,
+            address.address_locality,
+            address.address_region,
+            address.postal_code
+        ));
+    }
+    for contact in [&office.telephone, &office.email].into_iter().flatten() {
+        out.push_str(&format!("{contact}\n"));
+    }
+    out.push_str(&separator(style));
+
+    let grid = format!("{} {},{}", point.grid_id, point.grid_x, point.grid_y);
+    let fields = [
+        ("Grid", Some(grid.as_str())),
+        ("Forecast zone", point.forecast_zone_id()),
+        ("County", point.county_id()),
+        ("Fire weather zone", point.fire_weather_zone_id()),
+        ("Radar station", point.radar_station.as_deref()),
+        ("Time zone", point.time_zone.as_deref()),
+    ];
+    for (label, value) in fields {
+        let Some(value) = value else { continue };
+        let label = format!("{label}:");
         match style {
-            Style::Pretty => {
-                out.push_str(&format!("{}\n", period.start_time.bold().blue()));
-                out.push_str(&format!(
-                    "{}°{} | {} | Wind: {} {}\n",
-                    period.temperature.to_string().yellow(),
-                    period.temperature_unit.yellow(),
-                    period.short_forecast.cyan(),
-                    period.wind_speed.cyan(),
-                    period.wind_direction.cyan()
-                ));
+            Style::Pretty => out.push_str(&format!("{} {value}\n", format!("{label:<18}").bold())),
+            Style::Plain => out.push_str(&format!("{label:<18} {value}\n")),
+        }
+    }
+    out
+}
+
+/// An optional API value in `units`, or `None` if there is nothing to show.
+fn reading(value: &Option<QuantitativeValue>, units: Units) -> Option<Measurement> {
+    value.as_ref().and_then(|v| v.to_units(units))
+}
+
+/// Render the hourly forecast through the bundled `hourly` template.
+///
+/// A week of hourly periods is easy to lose your place in, so each calendar
+/// day (in the zone times are displayed in) opens with a heading. `periods`
+/// and `filtered` are as for [`render_detailed`].
+pub fn render_hourly(
+    periods: &[HourlyPeriod],
+    filtered: usize,
+    opts: &RenderOptions,
+) -> Result<String> {
+    template::render_layout(&template::HOURLY, periods, filtered, opts)
+}
+
+/// Rows in the temperature chart. Each row is one character tall, and each
+/// character resolves eighths in the Unicode style.
+const TEMPERATURE_ROWS: usize = 8;
+const PRECIP_ROWS: usize = 4;
+
+/// Columns a chart may span before hours are merged into one column. Wide
+/// enough for three days at one column per hour; a week merges pairs.
+const MAX_CHART_COLUMNS: usize = 84;
+
+/// Columns to the left of a chart's bars: the value labels, a space, and the
+/// axis itself.
+const GUTTER: usize = 6;
+
+/// One column of a chart, covering one or more consecutive periods.
+struct Column<'a> {
+    first: &'a HourlyPeriod,
+    temperature: f64,
+    precip: f64,
+}
+
+/// Group periods into at most [`MAX_CHART_COLUMNS`] columns. A merged column
+/// shows the mean temperature and the *highest* chance of precipitation, since
+/// averaging a 90% hour with a dry one would hide the rain.
+fn chart_columns(periods: &[HourlyPeriod]) -> Vec<Column<'_>> {
+    let per_column = periods.len().div_ceil(MAX_CHART_COLUMNS).max(1);
+    periods
+        .chunks(per_column)
+        .map(|chunk| Column {
+            first: &chunk[0],
+            temperature: chunk.iter().map(|p| f64::from(p.temperature)).sum::<f64>()
+                / chunk.len() as f64,
+            precip: chunk.iter().map(precip_chance).fold(0.0, f64::max),
+        })
+        .collect()
+}
+
+fn precip_chance(period: &HourlyPeriod) -> f64 {
+    period
+        .probability_of_precipitation
+        .as_ref()
+        .and_then(|p| p.value)
+        .unwrap_or(0.0)
+}
+
+/// The character for a cell filled to `eighths` of its height.
+///
+/// Plain output is ASCII only, so a partly filled cell there is approximated
+/// with `.` (under half) and `:` (half or more).
+fn bar_cell(eighths: usize, style: Style) -> char {
+    const BLOCKS: [char; 9] = [' ', '▁', '▂', '▃', '▄', '▅', '▆', '▇', '█'];
+    match style {
+        Style::Pretty => BLOCKS[eighths.min(8)],
+        Style::Plain => match eighths {
+            0 => ' ',
+            1..=3 => '.',
+            4..=7 => ':',
+            _ => '#',
+        },
+    }
+}
+
+/// Colour band for a temperature, so a glance at the chart reads warm or cold
+/// before any number does. Thresholds are in Fahrenheit.
+fn temperature_colour(fahrenheit: f64) -> Color {
+    match fahrenheit {
+        t if t < 32.0 => Color::BrightBlue,
+        t if t < 50.0 => Color::Cyan,
+        t if t < 70.0 => Color::Green,
+        t if t < 85.0 => Color::Yellow,
+        _ => Color::Red,
+    }
+}
+
+fn precip_colour(percent: f64) -> Color {
+    match percent {
+        p if p < 30.0 => Color::White,
+        p if p < 60.0 => Color::Cyan,
+        _ => Color::Blue,
+    }
+}
+
+/// Draw `values` as vertical bars between `lo` and `hi`, top row first, with
+/// the extremes labelled in the gutter.
+///
+/// `floor` is the height, in eighths, a value of `lo` is drawn at. The
+/// temperature chart uses 1 so its coldest column is still visible; the
+/// precipitation chart uses 0 so a dry hour is blank rather than a sliver.
+fn bars(
+    values: &[f64],
+    (lo, hi): (f64, f64),
+    rows: usize,
+    floor: usize,
+    colour: impl Fn(f64) -> Color,
+    style: Style,
+) -> String {
+    let span = (hi - lo).max(f64::EPSILON);
+    let scale = (rows * 8 - floor) as f64;
+    let heights: Vec<usize> = values
+        .iter()
+        .map(|v| floor + (((v - lo) / span).clamp(0.0, 1.0) * scale).round() as usize)
+        .collect();
+
+    let mut out = String::new();
+    for row in (0..rows).rev() {
+        let label = match row {
+            r if r == rows - 1 => format!("{hi:.0}"),
+            0 => format!("{lo:.0}"),
+            _ => String::new(),
+        };
+        out.push_str(&format!("{label:>width$} |", width = GUTTER - 2));
+        for (value, height) in values.iter().zip(&heights) {
+            let cell = bar_cell(height.saturating_sub(row * 8), style).to_string();
+            match style {
+                Style::Pretty => out.push_str(&cell.color(colour(*value)).to_string()),
+                Style::Plain => out.push_str(&cell),
             }
-            Style::Plain => {
-                out.push_str(&format!(
-                    "{}: {}°{} | {} | Wind: {} {}\n",
-                    period.start_time,
-                    period.temperature,
-                    period.temperature_unit,
-                    period.short_forecast,
-                    period.wind_speed,
-                    period.wind_direction,
-                ));
+        }
+        out.push('\n');
+    }
+    out
+}
+
+/// The axis under a chart: a rule, then each day's abbreviated name at the
+/// column where it starts. `starts` is when each column starts.
+fn day_axis(starts: &[DateTime<FixedOffset>], times: &TimeDisplay, style: Style) -> String {
+    let (corner, rule) = match style {
+        Style::Pretty => ('└', '─'),
+        Style::Plain => ('+', '-'),
+    };
+    let mut out = format!("{:width$}{corner}", "", width = GUTTER - 1);
+    out.extend(std::iter::repeat_n(rule, starts.len()));
+    out.push('\n');
+
+    let mut labels = vec![' '; starts.len()];
+    let mut previous = None;
+    for (i, start) in starts.iter().enumerate() {
+        let day = times.in_zone(start);
+        if previous == Some(day.date_naive()) {
+            continue;
+        }
+        previous = Some(day.date_naive());
+        labels[i] = '|';
+        for (offset, c) in day.format("%a").to_string().chars().enumerate() {
+            match labels.get_mut(i + 1 + offset) {
+                Some(slot) if *slot == ' ' => *slot = c,
+                _ => break,
             }
         }
-        out.push_str(&separator(style));
     }
+    out.push_str(&format!("{:width$}", "", width = GUTTER));
+    out.extend(labels);
+    out.push('\n');
+    out
+}
+
+/// The period holding the extreme of `key`, first one winning ties.
+fn extreme_by(
+    periods: &[HourlyPeriod],
+    key: impl Fn(&HourlyPeriod) -> f64,
+    highest: bool,
+) -> Option<&HourlyPeriod> {
+    periods.iter().reduce(|best, p| {
+        let better = if highest {
+            key(p) > key(best)
+        } else {
+            key(p) < key(best)
+        };
+        if better {
+            p
+        } else {
+            best
+        }
+    })
+}
+
+/// Chart the hourly forecast: temperature bars, then chance of precipitation
+/// on a fixed 0–100% scale, each with its extremes and when they occur.
+///
+/// One column is one hour until the span exceeds [`MAX_CHART_COLUMNS`], so
+/// `--limit` sets how far ahead the chart reaches (24 hours by default,
+/// `--limit 0` for everything the API returned). The precipitation chart is
+/// left out entirely when the API sent no chance of precipitation at all.
+pub fn render_chart(periods: &[HourlyPeriod], filtered: usize, opts: &RenderOptions) -> String {
+    let (style, times) = (opts.style, &opts.times);
+    let shown = limited(periods, opts.limit);
+    let mut out = header("Hourly Forecast Chart:", style);
+
+    let (Some(warmest), Some(coldest)) = (
+        extreme_by(shown, |p| f64::from(p.temperature), true),
+        extreme_by(shown, |p| f64::from(p.temperature), false),
+    ) else {
+        out.push_str(&truncation_note(filtered, periods.len(), 0, style));
+        return out;
+    };
+
+    let columns = chart_columns(shown);
+    let starts: Vec<_> = columns.iter().map(|c| c.first.start_time).collect();
+    let unit = &warmest.temperature_unit;
+    let to_fahrenheit = |t: f64| match unit.as_str() {
+        "C" => crate::units::celsius_to_fahrenheit(t),
+        _ => t,
+    };
+
+    let summary = format!(
+        "Temperature (°{unit}): high {}° {}, low {}° {}",
+        warmest.temperature,
+        times.label(&warmest.start_time),
+        coldest.temperature,
+        times.label(&coldest.start_time),
+    );
+    out.push_str(&chart_title(&summary, style));
+    let temperatures: Vec<f64> = columns.iter().map(|c| c.temperature).collect();
+    out.push_str(&bars(
+        &temperatures,
+        (
+            f64::from(coldest.temperature),
+            f64::from(warmest.temperature),
+        ),
+        TEMPERATURE_ROWS,
+        1,
+        |t| temperature_colour(to_fahrenheit(t)),
+        style,
+    ));
+    out.push_str(&day_axis(&starts, times, style));
+
+    let has_precip = shown
+        .iter()
+        .any(|p| p.probability_of_precipitation.is_some());
+    if has_precip {
+        let wettest = extreme_by(shown, precip_chance, true).expect("shown is non-empty");
+        let summary = format!(
+            "Chance of precipitation (%): peak {:.0}% {}",
+            precip_chance(wettest),
+            times.label(&wettest.start_time),
+        );
+        out.push('\n');
+        out.push_str(&chart_title(&summary, style));
+        let precip: Vec<f64> = columns.iter().map(|c| c.precip).collect();
+        out.push_str(&bars(
+            &precip,
+            (0.0, 100.0),
+            PRECIP_ROWS,
+            0,
+            precip_colour,
+            style,
+        ));
+        out.push_str(&day_axis(&starts, times, style));
+    }
+
+    out.push('\n');
+    out.push_str(&truncation_note(
+        filtered,
+        periods.len(),
+        shown.len(),
+        style,
+    ));
+    out
+}
+
+/// Render what `station` observed, one line per observation under a heading
+/// for each day. Readings the station did not report are left out.
+pub fn render_observations(
+    station: &Station,
+    observations: &[Observation],
+    opts: &RenderOptions,
+) -> String {
+    let (style, units, times) = (opts.style, opts.units, &opts.times);
+    let title = format!(
+        "Observed at {} ({}):",
+        station.name, station.station_identifier
+    );
+    let mut out = header(&title, style);
+    if observations.is_empty() {
+        out.push_str("No observations in that time.\n");
+        return out;
+    }
+
+    let mut previous = None;
+    for observation in observations {
+        if let Some(day) = times.day_break(previous, &observation.timestamp) {
+            out.push_str(&day_heading(&day, style));
+        }
+        previous = Some(&observation.timestamp);
+
+        let when = times.label(&observation.timestamp);
+        let fields = observation_fields(observation, units);
+        match style {
+            Style::Pretty => out.push_str(&format!("{} {}\n", when.bold().blue(), fields)),
+            Style::Plain => out.push_str(&format!("{when}: {fields}\n")),
+        }
+    }
+    out
+}
+
+/// What an observation reports, `|`-separated, leaving out what it does not.
+fn observation_fields(observation: &Observation, units: Units) -> String {
+    let mut fields = Vec::new();
+    if let Some(temperature) = reading(&observation.temperature, units) {
+        fields.push(temperature.to_string());
+    }
+    if let Some(text) = observation.text_description.as_deref() {
+        if !text.is_empty() {
+            fields.push(text.to_string());
+        }
+    }
+    let labelled = [
+        ("Wind", &observation.wind_speed),
+        ("Dewpoint", &observation.dewpoint),
+        ("Humidity", &observation.relative_humidity),
+    ];
+    for (label, value) in labelled {
+        if let Some(value) = reading(value, units) {
+            fields.push(format!("{label}: {value}"));
+        }
+    }
+    fields.join(" | ")
+}
+
+/// The latest observation from `station`, as a paragraph to follow the
+/// current hour's forecast.
+pub fn render_latest_observation(
+    station: &Station,
+    observation: &Observation,
+    opts: &RenderOptions,
+) -> String {
+    let title = format!(
+        "Observed at {} ({}), {}:",
+        station.name,
+        station.station_identifier,
+        opts.times.label(&observation.timestamp)
+    );
+    let fields = observation_fields(observation, opts.units);
+    match opts.style {
+        Style::Pretty => format!("\n{}\n{fields}\n", title.bold()),
+        Style::Plain => format!("\n{title}\n{fields}\n"),
+    }
+}
 
-    out.push_str(&truncation_note(periods.len(), shown.len(), style));
+const KM_PER_MILE: f64 = 1.609_344;
+
+/// List `stations` with how far each is from `from`, a `(latitude,
+/// longitude)`, and in which direction. The one `chosen` with `stations
+/// --use` is marked with `*`.
+pub fn render_stations(
+    stations: &[Station],
+    from: (f64, f64),
+    chosen: Option<&str>,
+    opts: &RenderOptions,
+) -> String {
+    let style = opts.style;
+    let shown = limited(stations, opts.limit);
+    let mut out = header("Observation Stations:", style);
+    for station in shown {
+        let mark = if chosen == Some(station.station_identifier.as_str()) {
+            '*'
+        } else {
+            ' '
+        };
+        let whereabouts = match station.position {
+            Some(to) => {
+                let km = distance_km(from, to);
+                let distance = match opts.units {
+                    Units::Us => format!("{:.1} mi", km / KM_PER_MILE),
+                    Units::Si => format!("{km:.1} km"),
+                };
+                format!("{distance:>8} {:<3}", compass(bearing(from, to)))
+            }
+            None => format!("{:>8}    ", "?"),
+        };
+        let id = format!("{:<6}", station.station_identifier);
+        match style {
+            Style::Pretty => out.push_str(&format!(
+                "{mark} {} {} {}\n",
+                id.bold(),
+                whereabouts.cyan(),
+                station.name
+            )),
+            Style::Plain => out.push_str(&format!("{mark} {id} {whereabouts} {}\n", station.name)),
+        }
+    }
+    if shown.len() < stations.len() {
+        let note = format!(
+            "... {} more station(s) not shown (use --limit 0 to show all)",
+            stations.len() - shown.len()
+        );
+        match style {
+            Style::Pretty => out.push_str(&format!("{}\n", note.dimmed())),
+            Style::Plain => out.push_str(&format!("{note}\n")),
+        }
+    }
+    if let Some(chosen) = chosen {
+        let listed = stations.iter().any(|s| s.station_identifier == chosen);
+        if !listed {
+            out.push_str(&format!("\nUsing {chosen}, which is not listed here.\n"));
+        }
+    }
+    out
+}
+
+/// Chart the temperatures `station` observed, as [`render_chart`] charts the
+/// hourly forecast.
+pub fn render_observation_chart(
+    station: &Station,
+    observations: &[Observation],
+    opts: &RenderOptions,
+) -> String {
+    let (style, times) = (opts.style, &opts.times);
+    let title = format!(
+        "Observed at {} ({}):",
+        station.name, station.station_identifier
+    );
+    let mut out = header(&title, style);
+    let readings: Vec<(DateTime<FixedOffset>, Measurement)> = observations
+        .iter()
+        .filter_map(|o| Some((o.timestamp, reading(&o.temperature, opts.units)?)))
+        .collect();
+    let warmest = readings
+        .iter()
+        .max_by(|a, b| a.1.value.total_cmp(&b.1.value));
+    let coldest = readings
+        .iter()
+        .min_by(|a, b| a.1.value.total_cmp(&b.1.value));
+    let (Some(warmest), Some(coldest)) = (warmest, coldest) else {
+        out.push_str("No temperatures observed in that time.\n");
+        return out;
+    };
+
+    let per_column = readings.len().div_ceil(MAX_CHART_COLUMNS).max(1);
+    let chunks: Vec<_> = readings.chunks(per_column).collect();
+    let starts: Vec<_> = chunks.iter().map(|chunk| chunk[0].0).collect();
+    let temperatures: Vec<f64> = chunks
+        .iter()
+        .map(|chunk| chunk.iter().map(|(_, m)| m.value).sum::<f64>() / chunk.len() as f64)
+        .collect();
+    let unit = &warmest.1.unit;
+    let to_fahrenheit = |t: f64| match unit.as_str() {
+        "°C" => crate::units::celsius_to_fahrenheit(t),
+        _ => t,
+    };
+
+    let summary = format!(
+        "Temperature ({unit}): high {:.0}° {}, low {:.0}° {}",
+        warmest.1.value,
+        times.label(&warmest.0),
+        coldest.1.value,
+        times.label(&coldest.0),
+    );
+    out.push_str(&chart_title(&summary, style));
+    out.push_str(&bars(
+        &temperatures,
+        (coldest.1.value, warmest.1.value),
+        TEMPERATURE_ROWS,
+        1,
+        |t| temperature_colour(to_fahrenheit(t)),
+        style,
+    ));
+    out.push_str(&day_axis(&starts, times, style));
     out
 }
+
+/// Compare each forecast period that has ended with what was observed: the
+/// high for a daytime period, the low for a night. `recorded` is the
+/// forecasts kept by [`crate::archive`], one per period.
+pub fn render_forecast_check(
+    recorded: &[Recorded],
+    observations: &[Observation],
+    opts: &RenderOptions,
+) -> String {
+    let (style, units, times) = (opts.style, opts.units, &opts.times);
+    let mut out = header("Forecast vs. Observed:", style);
+
+    let observed_from = observations.first().map(|o| o.timestamp);
+    let mut errors = Vec::new();
+    for period in recorded {
+        let ended = period.end_time <= times.now;
+        let observed = observed_from.is_some_and(|from| from <= period.start_time);
+        if !ended || !observed {
+            continue;
+        }
+        let temperatures = observations
+            .iter()
+            .filter(|o| o.timestamp >= period.start_time && o.timestamp < period.end_time)
+            .filter_map(|o| reading(&o.temperature, units))
+            .map(|m| m.value);
+        let (kind, actual) = if period.is_daytime {
+            ("high", temperatures.reduce(f64::max))
+        } else {
+            ("low", temperatures.reduce(f64::min))
+        };
+        let Some(actual) = actual else {
+            continue;
+        };
+        let forecast = period.temperature(units);
+        let error = forecast - actual;
+        errors.push(error);
+        let line = format!(
+            "{} ({}): forecast {kind} {forecast:.0}°{unit}, observed {actual:.0}°{unit} ({error:+.0}°)",
+            period.name,
+            times.label(&period.start_time),
+            unit = units.temperature_unit(),
+        );
+        match style {
+            Style::Pretty => out.push_str(&format!("{}\n", line.cyan())),
+            Style::Plain => out.push_str(&format!("{line}\n")),
+        }
+    }
+
+    if errors.is_empty() {
+        out.push_str(
+            "No recorded forecast covers the observed hours. Forecasts are recorded each \
+             time `wxdotgov forecast` runs for this place, so run it to have some next time.\n",
+        );
+        return out;
+    }
+    let mean = errors.iter().map(|e| e.abs()).sum::<f64>() / errors.len() as f64;
+    out.push_str(&separator(style));
+    out.push_str(&format!(
+        "Mean absolute error: {mean:.1}°{} over {} periods\n",
+        units.temperature_unit(),
+        errors.len()
+    ));
+    out
+}
+
+fn chart_title(text: &str, style: Style) -> String {
+    match style {
+        Style::Pretty => format!("{}\n", text.bold()),
+        Style::Plain => format!("{text}\n"),
+    }
+}
+
+/// Group hourly periods under the calendar day each starts on, in display
+/// order, keyed by the same day label the text layout uses as a heading.
+fn by_day<'a>(
+    periods: &'a [HourlyPeriod],
+    times: &TimeDisplay,
+) -> Vec<(String, Vec<&'a HourlyPeriod>)> {
+    let mut days: Vec<(String, Vec<&HourlyPeriod>)> = Vec::new();
+    let mut previous = None;
+    for period in periods {
+        if let Some(day) = times.day_break(previous, &period.start_time) {
+            days.push((day, Vec::new()));
+        }
+        previous = Some(&period.start_time);
+        if let Some((_, day)) = days.last_mut() {
+            day.push(period);
+        }
+    }
+    days
+}
+
+/// The cells of one hourly table row, shared by the Markdown and HTML tables.
+fn hourly_cells(period: &HourlyPeriod, opts: &RenderOptions) -> [String; 6] {
+    let show = |m: Option<Measurement>| m.map(|m| m.to_string()).unwrap_or_default();
+    [
+        opts.times.label(&period.start_time),
+        format!("{}°{}", period.temperature, period.temperature_unit),
+        period.short_forecast.clone(),
+        format!("{} {}", period.wind_speed, period.wind_direction),
+        show(reading(&period.dewpoint, opts.units)),
+        show(reading(&period.probability_of_precipitation, opts.units)),
+    ]
+}
+
+const HOURLY_COLUMNS: [&str; 6] = ["Time", "Temp", "Forecast", "Wind", "Dewpoint", "Precip"];
+
+/// Escape text for a Markdown table cell: a `|` would end the cell and a line
+/// break would end the row.
+fn markdown_cell(text: &str) -> String {
+    text.replace('|', "\\|").replace('\n', " ")
+}
+
+fn markdown_notes(filtered: usize, total: usize, shown: usize) -> String {
+    truncation_lines(filtered, total, shown)
+        .into_iter()
+        .map(|line| format!("_{line}_\n\n"))
+        .collect()
+}
+
+/// Render the detailed forecast as Markdown: one section per period.
+pub fn render_detailed_markdown(
+    periods: &[Period],
+    filtered: usize,
+    opts: &RenderOptions,
+) -> String {
+    let shown = limited(periods, opts.limit);
+    let mut out = String::from("# Weather Forecast\n\n");
+    for period in shown {
+        out.push_str(&format!(
+            "## {}\n\n{}\n\n",
+            period.name, period.detailed_forecast
+        ));
+    }
+    out.push_str(&markdown_notes(filtered, periods.len(), shown.len()));
+    out
+}
+
+/// Render the hourly forecast as Markdown: a table per calendar day.
+pub fn render_hourly_markdown(
+    periods: &[HourlyPeriod],
+    filtered: usize,
+    opts: &RenderOptions,
+) -> String {
+    let shown = limited(periods, opts.limit);
+    let mut out = String::from("# Hourly Weather Forecast\n\n");
+    for (day, hours) in by_day(shown, &opts.times) {
+        out.push_str(&format!("## {day}\n\n"));
+        out.push_str(&format!("| {} |\n", HOURLY_COLUMNS.join(" | ")));
+        out.push_str(&format!("|{}\n", "---|".repeat(HOURLY_COLUMNS.len())));
+        for period in hours {
+            let cells = hourly_cells(period, opts).map(|cell| markdown_cell(&cell));
+            out.push_str(&format!("| {} |\n", cells.join(" | ")));
+        }
+        out.push('\n');
+    }
+    out.push_str(&markdown_notes(filtered, periods.len(), shown.len()));
+    out
+}
+
+/// Styles for the HTML page, inlined so the file can be mailed or attached
+/// on its own. The icons are the one outside reference: they load from
+/// `api.weather.gov`.
+const HTML_STYLE: &str = "\
+body { font-family: system-ui, sans-serif; max-width: 48rem; margin: 2rem auto; padding: 0 1rem; color: #1d2a36; }
+h1 { border-bottom: 2px solid #1d2a36; padding-bottom: .25rem; }
+h2 { margin-top: 1.5rem; color: #0b5394; }
+.period { display: flex; gap: 1rem; align-items: flex-start; }
+.period img { flex: none; border-radius: 4px; }
+table { border-collapse: collapse; width: 100%; }
+th, td { text-align: left; padding: .25rem .5rem; border-bottom: 1px solid #d0d7de; }
+td img { vertical-align: middle; border-radius: 4px; }
+.note { color: #57606a; font-style: italic; }
+";
+
+fn escape_html(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => out.push_str("&amp;"),
+            '<' => out.push_str("&lt;"),
+            '>' => out.push_str("&gt;"),
+            '"' => out.push_str("&quot;"),
+            '\'' => out.push_str("&#39;"),
+            c => out.push(c),
+        }
+    }
+    out
+}
+
+/// An `<img>` for a period's icon, or nothing if the API sent none.
+fn html_icon(icon: &Option<String>, alt: &str, size: u32) -> String {
+    match icon {
+        Some(src) => format!(
+            r#"<img src="{}" alt="{}" width="{size}" height="{size}">"#,
+            escape_html(src),
+            escape_html(alt),
+        ),
+        None => String::new(),
+    }
+}
+
+/// Wrap `body` in a complete page titled `title`.
+fn html_page(title: &str, body: &str, notes: Vec<String>) -> String {
+    let mut out = format!(
+        "<!DOCTYPE html>\n<html lang=\"en\">\n<head>\n<meta charset=\"utf-8\">\n\
+         <title>{title}</title>\n<style>\n{HTML_STYLE}</style>\n</head>\n<body>\n\
+         <h1>{title}</h1>\n{body}",
+        title = escape_html(title),
+    );
+    for note in notes {
+        out.push_str(&format!("<p class=\"note\">{}</p>\n", escape_html(&note)));
+    }
+    out.push_str("</body>\n</html>\n");
+    out
+}
+
+/// Render the detailed forecast as a standalone HTML page.
+pub fn render_detailed_html(periods: &[Period], filtered: usize, opts: &RenderOptions) -> String {
+    let shown = limited(periods, opts.limit);
+    let mut body = String::new();
+    for period in shown {
+        body.push_str(&format!(
+            "<section class=\"period\">\n{}\n<div>\n<h2>{}</h2>\n<p>{}</p>\n</div>\n</section>\n",
+            html_icon(&period.icon, &period.name, 86),
+            escape_html(&period.name),
+            escape_html(&period.detailed_forecast),
+        ));
+    }
+    let notes = truncation_lines(filtered, periods.len(), shown.len());
+    html_page("Weather Forecast", &body, notes)
+}
+
+/// Render the hourly forecast as a standalone HTML page, a table per day.
+pub fn render_hourly_html(
+    periods: &[HourlyPeriod],
+    filtered: usize,
+    opts: &RenderOptions,
+) -> String {
+    let shown = limited(periods, opts.limit);
+    let mut body = String::new();
+    for (day, hours) in by_day(shown, &opts.times) {
+        body.push_str(&format!(
+            "<h2>{}</h2>\n<table>\n<thead><tr><th></th>",
+            escape_html(&day)
+        ));
+        for column in HOURLY_COLUMNS {
+            body.push_str(&format!("<th>{column}</th>"));
+        }
+        body.push_str("</tr></thead>\n<tbody>\n");
+        for period in hours {
+            body.push_str(&format!(
+                "<tr><td>{}</td>",
+                html_icon(&period.icon, &period.short_forecast, 32)
+            ));
+            for cell in hourly_cells(period, opts) {
+                body.push_str(&format!("<td>{}</td>", escape_html(&cell)));
+            }
+            body.push_str("</tr>\n");
+        }
+        body.push_str("</tbody>\n</table>\n");
+    }
+    let notes = truncation_lines(filtered, periods.len(), shown.len());
+    html_page("Hourly Weather Forecast", &body, notes)
+}
@@ -0,0 +1,380 @@
+//! `wxdotgov serve`: forecasts over local HTTP.
+//!
+//! For dashboards and other programs that want a forecast without shelling
+//! out to the CLI. One long-running process answers every client, so the
+//! upstream load is one process's worth: responses are shared through one
+//! [`crate::cache`], and requests to Nominatim are paced by
+//! [`crate::http::send`].
+//!
+//! ```text
+//! GET /forecast?zip=98101&type=hourly&format=json
+//! GET /forecast?city=Seattle&state=WA&format=html
+//! GET /forecast?lat=47.61&lon=-122.33&format=ics
+//! GET /alerts?lat=47.61&lon=-122.33
+//! GET /healthz
+//! ```
+//!
+//! A place is given as `zip`, as `city` with an optional `state`, or as `lat`
+//! and `lon`, which skips geocoding. `/forecast` also takes:
+//!
+//! - `type`: `detailed` (the default) or `hourly`.
+//! - `format`: `json` (the default), or any `--output` value: `text`,
+//!   `markdown`, `html`, `ics`. The JSON is the context `--template` renders,
+//!   so its fields are documented once, in [`crate::template`].
+//! - `units` and `limit`, as on the command line.
+//! - `alerts=false`, to leave active alerts out of `ics` and the JSON, as
+//!   `--no-alerts` does.
+//!
+//! Errors are JSON, `{"error": "..."}`: 400 for a bad query, 502 when an
+//! upstream request fails.
+//!
+//! The server binds to localhost by default. It has no authentication, and
+//! every request it accepts can cost an upstream request, so exposing it more
+//! widely is a deliberate `--listen` away.
+
+use std::net::SocketAddr;
+use std::sync::Arc;
+
+use anyhow::{Context, Result};
+use axum::extract::{Query, State};
+use axum::http::{header, StatusCode};
+use axum::response::{IntoResponse, Response};
+use axum::routing::get;
+use axum::{Json, Router};
+use clap::ValueEnum;
+use serde::{Deserialize, Serialize};
+
+use crate::cache::{self, Cache};
+use crate::ics::render_ics;
+use crate::nominatim::{get_lat_lon, NominatimLocation};
+use crate::render::{
+    limited, render_detailed, render_detailed_html, render_detailed_markdown, render_hourly,
+    render_hourly_html, render_hourly_markdown, truncation_lines, OutputFormat, RenderOptions,
+    Style, DEFAULT_LIMIT,
+};
+use crate::template::{Place, TemplateContext};
+use crate::times::{DisplayZone, TimeDisplay, TimeFormat};
+use crate::units::Units;
+use crate::weatherdotgov::{
+    get_active_alerts, get_detailed_forecast, get_hourly_forecast, get_weather_point, Alert,
+    ForecastProperties, HourlyForecastProperties, Issuance, PointsProperties,
+};
+use crate::{build_location_input, parse_point, ForecastType};
+
+#[derive(Debug, clap::Args)]
+pub struct ServeArgs {
+    /// Address to listen on.
+    #[arg(long, default_value = "127.0.0.1:8080")]
+    listen: SocketAddr,
+}
+
+/// What every request handler shares.
+#[derive(Default)]
+pub struct ServeState {
+    pub cache: Arc<Cache>,
+    /// Upstream base URLs, `None` for the real services. Tests point these at
+    /// a mock server.
+    pub nominatim_url: Option<String>,
+    pub weather_url: Option<String>,
+}
+
+/// An error response: a status and a message, sent as `{"error": message}`.
+#[derive(Debug)]
+pub struct ApiError {
+    status: StatusCode,
+    message: String,
+}
+
+impl ApiError {
+    fn bad_request(message: impl Into<String>) -> Self {
+        ApiError {
+            status: StatusCode::BAD_REQUEST,
+            message: message.into(),
+        }
+    }
+}
+
+/// Anything that fails past query validation is an upstream failure.
+impl From<anyhow::Error> for ApiError {
+    fn from(e: anyhow::Error) -> Self {
+        ApiError {
+            status: StatusCode::BAD_GATEWAY,
+            message: format!("{e:#}"),
+        }
+    }
+}
+
+#[derive(Serialize)]
+struct ErrorBody {
+    error: String,
+}
+
+impl IntoResponse for ApiError {
+    fn into_response(self) -> Response {
+        let body = ErrorBody {
+            error: self.message,
+        };
+        (self.status, Json(body)).into_response()
+    }
+}
+
+/// Where, in any of the forms the CLI accepts, or as coordinates.
+#[derive(Debug, Deserialize)]
+pub struct PlaceQuery {
+    zip: Option<String>,
+    city: Option<String>,
+    state: Option<String>,
+    lat: Option<String>,
+    lon: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ForecastQuery {
+    #[serde(flatten)]
+    place: PlaceQuery,
+    #[serde(rename = "type")]
+    forecast_type: Option<String>,
+    format: Option<String>,
+    units: Option<String>,
+    limit: Option<usize>,
+    alerts: Option<String>,
+}
+
+/// Parse a query value with the same names clap accepts for the flag.
+fn choice<T: ValueEnum>(value: Option<&str>, name: &str, default: T) -> Result<T, ApiError> {
+    match value {
+        None => Ok(default),
+        Some(value) => T::from_str(value, true).map_err(|_| {
+            let known: Vec<String> = T::value_variants()
+                .iter()
+                .filter_map(|v| v.to_possible_value())
+                .map(|v| v.get_name().to_string())
+                .collect();
+            ApiError::bad_request(format!(
+                "Unknown {name} '{value}'; expected one of: {}",
+                known.join(", ")
+            ))
+        }),
+    }
+}
+
+/// Resolve the place, geocoding unless coordinates were given.
+async fn resolve(query: &PlaceQuery, state: &ServeState) -> Result<NominatimLocation, ApiError> {
+    if let (Some(lat), Some(lon)) = (&query.lat, &query.lon) {
+        // Checked as `--point` is, since they go into the upstream URL path.
+        let point = parse_point(&format!("{lat},{lon}")).map_err(|_| {
+            ApiError::bad_request("lat and lon must be decimal degrees, e.g. lat=47.7&lon=-122.45")
+        })?;
+        let display_name = format!("{},{}", point.lat, point.lon);
+        return Ok(NominatimLocation {
+            lat: point.lat,
+            lon: point.lon,
+            display_name,
+        });
+    }
+    if query.zip.is_none() && query.city.is_none() {
+        return Err(ApiError::bad_request(
+            "Give a place as zip, city (with an optional state), or lat and lon",
+        ));
+    }
+    let input = build_location_input(query.zip.clone(), query.city.clone(), query.state.clone())
+        .map_err(|e| ApiError::bad_request(e.to_string()))?;
+    Ok(get_lat_lon(input, state.nominatim_url.as_deref()).await?)
+}
+
+/// The requested response format: JSON, or one of the CLI's outputs.
+#[derive(Clone, Copy)]
+enum Format {
+    Json,
+    Output(OutputFormat),
+}
+
+impl Format {
+    fn parse(value: Option<&str>) -> Result<Self, ApiError> {
+        match value {
+            None | Some("json") => Ok(Format::Json),
+            Some(other) => choice(Some(other), "format", OutputFormat::Text)
+                .map(Format::Output)
+                .map_err(|e| ApiError {
+                    message: format!("{}, json", e.message),
+                    ..e
+                }),
+        }
+    }
+
+    fn content_type(self) -> &'static str {
+        match self {
+            Format::Json => "application/json",
+            Format::Output(OutputFormat::Text) => "text/plain; charset=utf-8",
+            Format::Output(OutputFormat::Markdown) => "text/markdown; charset=utf-8",
+            Format::Output(OutputFormat::Html) => "text/html; charset=utf-8",
+            Format::Output(OutputFormat::Ics) => "text/calendar; charset=utf-8",
+        }
+    }
+}
+
+/// What a JSON response is built from, whichever period type was fetched.
+struct Fetched<'a> {
+    location: &'a NominatimLocation,
+    point: &'a PointsProperties,
+    alerts: &'a [Alert],
+    forecast_type: &'a ForecastType,
+}
+
+/// The server applies no time filters, so nothing is ever `filtered`.
+fn json_body<T: Serialize>(
+    fetched: &Fetched,
+    issuance: &Issuance,
+    periods: &[T],
+    opts: &RenderOptions,
+) -> Result<String> {
+    let shown = limited(periods, opts.limit);
+    let context = TemplateContext {
+        location: Place {
+            name: &fetched.location.display_name,
+            lat: &fetched.location.lat,
+            lon: &fetched.location.lon,
+        },
+        point: fetched.point,
+        alerts: fetched.alerts,
+        forecast_type: fetched.forecast_type.as_str(),
+        issuance,
+        periods: shown,
+        filtered: 0,
+        withheld: periods.len() - shown.len(),
+        notes: truncation_lines(0, periods.len(), shown.len()),
+    };
+    serde_json::to_string(&context).context("Error serializing forecast")
+}
+
+async fn forecast_body(query: ForecastQuery, state: &ServeState) -> Result<Response, ApiError> {
+    let forecast_type = choice(
+        query.forecast_type.as_deref(),
+        "type",
+        ForecastType::Detailed,
+    )?;
+    let format = Format::parse(query.format.as_deref())?;
+    let units = choice(query.units.as_deref(), "units", Units::Us)?;
+    let with_alerts = match query.alerts.as_deref() {
+        None | Some("true") => true,
+        Some("false") => false,
+        Some(other) => {
+            return Err(ApiError::bad_request(format!(
+                "Unknown alerts '{other}'; expected true or false"
+            )))
+        }
+    };
+    if forecast_type == ForecastType::Hourly && matches!(format, Format::Output(OutputFormat::Ics))
+    {
+        return Err(ApiError::bad_request(
+            "format=ics is built from the detailed forecast; drop type=hourly",
+        ));
+    }
+
+    let location = resolve(&query.place, state).await?;
+    let point = get_weather_point(&location.lat, &location.lon, state.weather_url.as_deref())
+        .await?
+        .properties;
+    let alerts = match format {
+        Format::Json | Format::Output(OutputFormat::Ics) if with_alerts => {
+            get_active_alerts(&location.lat, &location.lon, state.weather_url.as_deref()).await?
+        }
+        _ => Vec::new(),
+    };
+    let opts = RenderOptions {
+        style: Style::Plain,
+        limit: match query.limit {
+            Some(0) => None,
+            limit => Some(limit.unwrap_or(DEFAULT_LIMIT)),
+        },
+        units,
+        times: TimeDisplay::new(TimeFormat::TwelveHour, DisplayZone::Location),
+    };
+    let fetched = Fetched {
+        location: &location,
+        point: &point,
+        alerts: &alerts,
+        forecast_type: &forecast_type,
+    };
+
+    let body = match forecast_type {
+        ForecastType::Detailed => {
+            let ForecastProperties { issuance, periods } =
+                get_detailed_forecast(&point.forecast, units)
+                    .await?
+                    .properties;
+            match format {
+                Format::Json => json_body(&fetched, &issuance, &periods, &opts)?,
+                Format::Output(OutputFormat::Text) => render_detailed(&periods, 0, &opts)?,
+                Format::Output(OutputFormat::Markdown) => {
+                    render_detailed_markdown(&periods, 0, &opts)
+                }
+                Format::Output(OutputFormat::Html) => render_detailed_html(&periods, 0, &opts),
+                Format::Output(OutputFormat::Ics) => {
+                    render_ics(&periods, &alerts, &point, &location.display_name, &opts)
+                }
+            }
+        }
+        ForecastType::Hourly => {
+            let url = point
+                .forecast_hourly
+                .as_ref()
+                .context("Hourly forecast not available for this location")?;
+            let HourlyForecastProperties { issuance, periods } =
+                get_hourly_forecast(url, units).await?.properties;
+            match format {
+                Format::Json => json_body(&fetched, &issuance, &periods, &opts)?,
+                Format::Output(OutputFormat::Text) => render_hourly(&periods, 0, &opts)?,
+                Format::Output(OutputFormat::Markdown) => {
+                    render_hourly_markdown(&periods, 0, &opts)
+                }
+                Format::Output(OutputFormat::Html) => render_hourly_html(&periods, 0, &opts),
+                Format::Output(OutputFormat::Ics) => unreachable!("rejected above"),
+            }
+        }
+    };
+    Ok(([(header::CONTENT_TYPE, format.content_type())], body).into_response())
+}
+
+async fn forecast(
+    State(state): State<Arc<ServeState>>,
+    Query(query): Query<ForecastQuery>,
+) -> Result<Response, ApiError> {
+    cache::scope(state.cache.clone(), forecast_body(query, &state)).await
+}
+
+async fn alerts(
+    State(state): State<Arc<ServeState>>,
+    Query(query): Query<PlaceQuery>,
+) -> Result<Json<Vec<Alert>>, ApiError> {
+    cache::scope(state.cache.clone(), async {
+        let location = resolve(&query, &state).await?;
+        let alerts =
+            get_active_alerts(&location.lat, &location.lon, state.weather_url.as_deref()).await?;
+        Ok(Json(alerts))
+    })
+    .await
+}
+
+async fn healthz() -> &'static str {
+    "ok\n"
+}
+
+pub fn router(state: ServeState) -> Router {
+    Router::new()
+        .route("/forecast", get(forecast))
+        .route("/alerts", get(alerts))
+        .route("/healthz", get(healthz))
+        .with_state(Arc::new(state))
+}
+
+/// Serve until the process is stopped.
+pub async fn run(args: ServeArgs) -> Result<()> {
+    let listener = tokio::net::TcpListener::bind(args.listen)
+        .await
+        .with_context(|| format!("Error listening on {}", args.listen))?;
+    eprintln!("Listening on http://{}", args.listen);
+    axum::serve(listener, router(ServeState::default()))
+        .await
+        .context("Error serving HTTP")
+}
@@ -0,0 +1,73 @@
+//! Which observation station a location's observations come from.
+//!
+//! `/points` lists the stations near a point, nearest first, and the nearest
+//! is not always the one worth reading: an airport on the water may say
+//! little about a town a few miles inland. `wxdotgov stations` lists them with
+//! how far away each is and in which direction, and `--use` remembers one for
+//! the location, which `current` and `history` then read from. `--station`
+//! overrides either for a single run.
+//!
+//! Choices are kept in a state file, keyed by the location as it was given
+//! on the command line (`98101`, `Seattle, WA`, `47.6,-122.3`), so the same
+//! place spelled differently is a different location.
+
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+
+use anyhow::{bail, Result};
+
+use crate::dirs;
+
+/// The station remembered for each location, by label.
+pub type Choices = BTreeMap<String, String>;
+
+/// `$XDG_STATE_HOME/wxdotgov/stations.json`, or its `~/.local/state` default.
+pub fn default_file() -> Option<PathBuf> {
+    Some(dirs::state_dir()?.join("stations.json"))
+}
+
+/// The station remembered for `label`, if any. Without a state directory
+/// nothing can have been remembered.
+pub fn remembered(label: &str) -> Result<Option<String>> {
+    let Some(path) = default_file() else {
+        return Ok(None);
+    };
+    Ok(dirs::load_state::<Choices>(&path)?.remove(label))
+}
+
+/// Parse `--station` and `--use`: an NWS station identifier such as `KSEA`.
+pub fn parse_station(s: &str) -> Result<String> {
+    let id = s.trim().to_ascii_uppercase();
+    if !(3..=8).contains(&id.len()) || !id.chars().all(|c| c.is_ascii_alphanumeric()) {
+        bail!("'{s}' is not a station ID like KSEA; list them with `wxdotgov stations`");
+    }
+    Ok(id)
+}
+
+const EARTH_RADIUS_KM: f64 = 6371.0;
+
+/// The great-circle distance between two `(latitude, longitude)` points.
+pub fn distance_km(from: (f64, f64), to: (f64, f64)) -> f64 {
+    let (lat1, lat2) = (from.0.to_radians(), to.0.to_radians());
+    let (dlat, dlon) = ((to.0 - from.0).to_radians(), (to.1 - from.1).to_radians());
+    let a = (dlat / 2.0).sin().powi(2) + lat1.cos() * lat2.cos() * (dlon / 2.0).sin().powi(2);
+    2.0 * EARTH_RADIUS_KM * a.sqrt().asin()
+}
+
+/// The initial bearing from `from` to `to`, in degrees clockwise from north.
+pub fn bearing(from: (f64, f64), to: (f64, f64)) -> f64 {
+    let (lat1, lat2) = (from.0.to_radians(), to.0.to_radians());
+    let dlon = (to.1 - from.1).to_radians();
+    let y = dlon.sin() * lat2.cos();
+    let x = lat1.cos() * lat2.sin() - lat1.sin() * lat2.cos() * dlon.cos();
+    y.atan2(x).to_degrees().rem_euclid(360.0)
+}
+
+/// A bearing as one of the 16 compass points, e.g. `SSW`.
+pub fn compass(degrees: f64) -> &'static str {
+    const POINTS: [&str; 16] = [
+        "N", "NNE", "NE", "ENE", "E", "ESE", "SE", "SSE", "S", "SSW", "SW", "WSW", "W", "WNW",
+        "NW", "NNW",
+    ];
+    POINTS[((degrees.rem_euclid(360.0) / 22.5).round() as usize) % 16]
+}
@@ -0,0 +1,279 @@
+//! One-line output for status bars and shell prompts.
+//!
+//! tmux, i3bar and waybar all want a single short line, refreshed on a timer,
+//! rather than the multi-line forecast [`crate::render`] produces. `--format`
+//! takes a template naming fields of the current hourly period:
+//!
+//! ```text
+//! wxdotgov --zip 98101 --format '{temp}°{unit} {short} {wind}'
+//! 51°F Partly Cloudy 5 mph SSW
+//! ```
+//!
+//! `--waybar` wraps that line in the JSON a waybar `custom` module reads, with
+//! a tooltip of the next few hours and a CSS class for styling:
+//!
+//! ```json
+//! {"text": "51°F Partly Cloudy", "tooltip": "...", "class": "cloudy"}
+//! ```
+//!
+//! The class is the most severe active alert's (`alert-severe`) when there is
+//! one, and otherwise a coarse condition taken from the short forecast
+//! (`rain`, `snow`, `clear`, ...).
+//!
+//! Waybar module docs: <https://github.com/Alexays/Waybar/wiki/Module:-Custom>
+
+use std::str::FromStr;
+
+use anyhow::{bail, Result};
+use serde::Serialize;
+
+use crate::render::RenderOptions;
+use crate::weatherdotgov::{Alert, HourlyPeriod};
+
+/// The template used when `--waybar` is given without `--format`.
+pub const DEFAULT_TEMPLATE: &str = "{temp}°{unit} {short}";
+
+/// The hour in progress as a short report, for `wxdotgov current` and the
+/// TUI's "Now" tab. Not a status line, but the same fields say it.
+pub const CURRENT_TEMPLATE: &str = "{time}\n\n{temp}°{unit}  {short}\n\nWind: {wind}\n\
+                                    Dewpoint: {dewpoint}\nChance of precipitation: {pop}";
+
+/// How many upcoming hours the waybar tooltip lists.
+const TOOLTIP_HOURS: usize = 6;
+
+/// A value a template can name.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Field {
+    Temp,
+    Unit,
+    Short,
+    Wind,
+    WindSpeed,
+    WindDir,
+    Pop,
+    Dewpoint,
+    Time,
+}
+
+impl Field {
+    const ALL: [(&'static str, Field); 9] = [
+        ("temp", Field::Temp),
+        ("unit", Field::Unit),
+        ("short", Field::Short),
+        ("wind", Field::Wind),
+        ("wind_speed", Field::WindSpeed),
+        ("wind_dir", Field::WindDir),
+        ("pop", Field::Pop),
+        ("dewpoint", Field::Dewpoint),
+        ("time", Field::Time),
+    ];
+
+    fn value(self, period: &HourlyPeriod, opts: &RenderOptions) -> String {
+        match self {
+            Field::Temp => period.temperature.to_string(),
+            Field::Unit => period.temperature_unit.clone(),
+            Field::Short => period.short_forecast.clone(),
+            Field::Wind => format!("{} {}", period.wind_speed, period.wind_direction),
+            Field::WindSpeed => period.wind_speed.clone(),
+            Field::WindDir => period.wind_direction.clone(),
+            // Missing optional values become empty rather than an error: a
+            // status bar that goes blank over one absent field is worse.
+            Field::Pop => period
+                .probability_of_precipitation
+                .as_ref()
+                .and_then(|p| p.to_units(opts.units))
+                .map(|m| m.to_string())
+                .unwrap_or_default(),
+            Field::Dewpoint => period
+                .dewpoint
+                .as_ref()
+                .and_then(|d| d.to_units(opts.units))
+                .map(|m| m.to_string())
+                .unwrap_or_default(),
+            Field::Time => opts.times.label(&period.start_time),
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+enum Piece {
+    Text(String),
+    Field(Field),
+}
+
+/// A parsed `--format` template.
+///
+/// Parsed once, when the arguments are, so a typo in a field name is reported
+/// before any request is made rather than printed into a status bar.
+#[derive(Clone, Debug, PartialEq)]
+pub struct LineTemplate {
+    pieces: Vec<Piece>,
+}
+
+impl FromStr for LineTemplate {
+    type Err = anyhow::Error;
+
+    /// `{name}` is a field; `{{` and `}}` are literal braces.
+    fn from_str(s: &str) -> Result<Self> {
+        let mut pieces = Vec::new();
+        let mut text = String::new();
+        let mut chars = s.chars().peekable();
+
+        while let Some(c) = chars.next() {
+            match c {
+                '{' if chars.peek() == Some(&'{') => {
+                    chars.next();
+                    text.push('{');
+                }
+                '}' if chars.peek() == Some(&'}') => {
+                    chars.next();
+                    text.push('}');
+                }
+                '{' => {
+                    let mut name = String::new();
+                    loop {
+                        match chars.next() {
+                            Some('}') => break,
+                            Some(c) => name.push(c),
+                            None => bail!("unclosed '{{{name}' in format"),
+                        }
+                    }
+                    let Some(&(_, field)) = Field::ALL.iter().find(|(n, _)| *n == name) else {
+                        let known: Vec<&str> = Field::ALL.iter().map(|(n, _)| *n).collect();
+                        bail!(
+                            "unknown field '{{{name}}}' in format; expected one of: {}",
+                            known.join(", ")
+                        );
+                    };
+                    if !text.is_empty() {
+                        pieces.push(Piece::Text(std::mem::take(&mut text)));
+                    }
+                    pieces.push(Piece::Field(field));
+                }
+                '}' => bail!("unmatched '}}' in format; write '}}}}' for a literal brace"),
+                c => text.push(c),
+            }
+        }
+        if !text.is_empty() {
+            pieces.push(Piece::Text(text));
+        }
+        Ok(LineTemplate { pieces })
+    }
+}
+
+impl LineTemplate {
+    pub fn render(&self, period: &HourlyPeriod, opts: &RenderOptions) -> String {
+        self.pieces
+            .iter()
+            .map(|piece| match piece {
+                Piece::Text(text) => text.clone(),
+                Piece::Field(field) => field.value(period, opts),
+            })
+            .collect()
+    }
+}
+
+/// The period in progress at `now`, or the first one if none is (the list was
+/// filtered to start later, or the forecast is stale).
+pub fn current_period<'a>(
+    periods: &'a [HourlyPeriod],
+    opts: &RenderOptions,
+) -> Option<&'a HourlyPeriod> {
+    let now = opts.times.now;
+    periods
+        .iter()
+        .find(|p| p.start_time <= now && now < p.end_time)
+        .or_else(|| periods.first())
+}
+
+/// Render the one-line status, followed by a newline.
+pub fn render_line(
+    periods: &[HourlyPeriod],
+    template: &LineTemplate,
+    opts: &RenderOptions,
+) -> Result<String> {
+    let Some(period) = current_period(periods, opts) else {
+        bail!("The forecast has no periods to show");
+    };
+    Ok(format!("{}\n", template.render(period, opts)))
+}
+
+#[derive(Debug, Serialize)]
+struct Waybar {
+    text: String,
+    tooltip: String,
+    class: String,
+}
+
+/// A coarse condition for styling, read from the short forecast. Checked most
+/// specific first: "Chance Rain And Snow" is snow weather as far as a status
+/// bar colour is concerned.
+pub fn condition_class(short_forecast: &str) -> &'static str {
+    let text = short_forecast.to_ascii_lowercase();
+    let has = |words: &[&str]| words.iter().any(|w| text.contains(w));
+
+    if has(&["thunder", "t-storm"]) {
+        "thunderstorm"
+    } else if has(&["snow", "sleet", "ice", "flurries", "blizzard"]) {
+        "snow"
+    } else if has(&["rain", "shower", "drizzle"]) {
+        "rain"
+    } else if has(&["fog", "haze", "smoke"]) {
+        "fog"
+    } else if has(&["cloudy", "overcast"]) {
+        "cloudy"
+    } else if has(&["sunny", "clear"]) {
+        "clear"
+    } else {
+        "unknown"
+    }
+}
+
+/// Render waybar's JSON: the templated line, a tooltip, and a class.
+///
+/// `place` heads the tooltip; it is the geocoded name the progress output
+/// already reports.
+pub fn render_waybar(
+    periods: &[HourlyPeriod],
+    alerts: &[Alert],
+    place: &str,
+    template: &LineTemplate,
+    opts: &RenderOptions,
+) -> Result<String> {
+    let Some(period) = current_period(periods, opts) else {
+        bail!("The forecast has no periods to show");
+    };
+
+    let mut tooltip = vec![place.to_string()];
+    for alert in alerts {
+        tooltip.push(format!(
+            "⚠ {}",
+            alert.headline.as_deref().unwrap_or(&alert.event)
+        ));
+    }
+    let start = periods
+        .iter()
+        .position(|p| std::ptr::eq(p, period))
+        .unwrap_or(0);
+    for p in periods.iter().skip(start).take(TOOLTIP_HOURS) {
+        tooltip.push(format!(
+            "{}  {}°{}  {}",
+            opts.times.label(&p.start_time),
+            p.temperature,
+            p.temperature_unit,
+            p.short_forecast
+        ));
+    }
+
+    let class = match alerts.iter().map(|a| a.severity).max() {
+        Some(severity) => format!("alert-{}", severity.as_str()),
+        None => condition_class(&period.short_forecast).to_string(),
+    };
+
+    let waybar = Waybar {
+        text: template.render(period, opts),
+        tooltip: tooltip.join("\n"),
+        class,
+    };
+    Ok(format!("{}\n", serde_json::to_string(&waybar)?))
+}
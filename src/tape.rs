@@ -0,0 +1,178 @@
+//! `--record` and `--replay`: upstream traffic saved to, and served from, a
+//! directory.
+//!
+//! Recording sends every request as usual and writes the exchange to a file:
+//! the method, URL and request headers, and the response's status, headers and
+//! body. Replaying reads the file for each request instead of sending it, so a
+//! recorded run can be repeated without a network, for demos and for tests
+//! against payloads captured from the real services.
+//!
+//! ```text
+//! $ wxdotgov --zip 98101 --record ./tape
+//! $ wxdotgov --zip 98101 --replay ./tape
+//! ```
+//!
+//! Both hook into [`crate::http::send`], so every Nominatim and Weather.gov
+//! request is covered and nothing above that layer can tell a replayed
+//! response from a live one. A request with no recording is an error naming
+//! the request, never a silent fall back to the network.
+//!
+//! Exchanges are matched on method and full URL, query included. Recording
+//! the same request twice keeps the later response.
+
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+use anyhow::{bail, Context, Result};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::cache;
+
+/// Where upstream traffic goes instead of, or as well as, the network.
+#[derive(Clone, Debug)]
+pub enum Tape {
+    Record(PathBuf),
+    Replay(PathBuf),
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+pub struct RecordedRequest {
+    pub method: String,
+    pub url: String,
+    pub headers: BTreeMap<String, String>,
+}
+
+impl RecordedRequest {
+    pub fn of(request: &reqwest::Request) -> Self {
+        RecordedRequest {
+            method: request.method().to_string(),
+            url: request.url().to_string(),
+            headers: header_map(request.headers()),
+        }
+    }
+
+    /// The file this request is recorded in. The host and path lead, so a
+    /// tape can be browsed by eye; the hash tells apart queries on one path.
+    fn file_name(&self) -> String {
+        let readable: String = self
+            .url
+            .split(['?', '#'])
+            .next()
+            .unwrap_or_default()
+            .trim_start_matches("https://")
+            .trim_start_matches("http://")
+            .chars()
+            .map(|c| {
+                if c.is_ascii_alphanumeric() || c == '.' {
+                    c
+                } else {
+                    '-'
+                }
+            })
+            .take(80)
+            .collect();
+        let hash = cache::stable_hash(&format!("{} {}", self.method, self.url));
+        format!("{readable}-{hash:016x}.json")
+    }
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+pub struct RecordedResponse {
+    pub status: u16,
+    pub headers: BTreeMap<String, String>,
+    pub body: String,
+}
+
+/// One request and its response, as stored on disk.
+#[derive(Debug, Deserialize, Serialize)]
+pub struct Exchange {
+    pub recorded: DateTime<Utc>,
+    pub request: RecordedRequest,
+    pub response: RecordedResponse,
+}
+
+/// Headers as a map, for a file that reads well. A repeated header is joined
+/// with commas, which HTTP defines as equivalent.
+fn header_map(headers: &reqwest::header::HeaderMap) -> BTreeMap<String, String> {
+    let mut map = BTreeMap::<String, String>::new();
+    for (name, value) in headers {
+        let value = String::from_utf8_lossy(value.as_bytes());
+        map.entry(name.to_string())
+            .and_modify(|joined| {
+                joined.push_str(", ");
+                joined.push_str(&value);
+            })
+            .or_insert_with(|| value.into_owned());
+    }
+    map
+}
+
+/// Turn a stored response back into the type [`crate::http::send`] returns.
+fn to_response(recorded: RecordedResponse) -> Result<reqwest::Response> {
+    let mut builder = ::http::Response::builder().status(recorded.status);
+    for (name, value) in &recorded.headers {
+        builder = builder.header(name, value);
+    }
+    let response = builder
+        .body(recorded.body)
+        .context("Error rebuilding a recorded response")?;
+    Ok(reqwest::Response::from(response))
+}
+
+/// Read `response` whole, write the exchange under `dir`, and hand back an
+/// equivalent response for the caller to read as if it were live.
+pub async fn record(
+    dir: &Path,
+    request: RecordedRequest,
+    response: reqwest::Response,
+) -> Result<reqwest::Response> {
+    let recorded = RecordedResponse {
+        status: response.status().as_u16(),
+        headers: header_map(response.headers()),
+        body: response
+            .text()
+            .await
+            .context("Error reading a response to record")?,
+    };
+    let exchange = Exchange {
+        recorded: Utc::now(),
+        request,
+        response: recorded,
+    };
+
+    std::fs::create_dir_all(dir).with_context(|| format!("Error creating {}", dir.display()))?;
+    let path = dir.join(exchange.request.file_name());
+    let json = serde_json::to_string_pretty(&exchange)?;
+    std::fs::write(&path, json).with_context(|| format!("Error writing {}", path.display()))?;
+    to_response(exchange.response)
+}
+
+/// The recorded response to `request`, or an error saying it was never
+/// recorded.
+pub fn replay(dir: &Path, request: &RecordedRequest) -> Result<reqwest::Response> {
+    let path = dir.join(request.file_name());
+    let text = match std::fs::read_to_string(&path) {
+        Ok(text) => text,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => bail!(
+            "No recording of {} {} in {}; record it with --record",
+            request.method,
+            request.url,
+            dir.display()
+        ),
+        Err(e) => return Err(e).with_context(|| format!("Error reading {}", path.display())),
+    };
+    let exchange: Exchange = serde_json::from_str(&text)
+        .with_context(|| format!("Error parsing recording {}", path.display()))?;
+    if exchange.request.method != request.method || exchange.request.url != request.url {
+        bail!(
+            "Recording {} is of {} {}, not {} {}",
+            path.display(),
+            exchange.request.method,
+            exchange.request.url,
+            request.method,
+            request.url
+        );
+    }
+    to_response(exchange.response)
+}
@@ -0,0 +1,274 @@
+//! User-defined output templates.
+//!
+//! `--template` renders the forecast through a [MiniJinja] template instead of
+//! one of the built-in layouts, so a Slack message, an email body or a wiki
+//! snippet does not need a change to this crate. The argument is either a path
+//! or the name of a bundled template:
+//!
+//! - `detailed` and `hourly` are the layouts `wxdotgov forecast` and
+//!   `wxdotgov hourly` print, plain or `--pretty`: [`crate::render`] renders
+//!   them through these templates, which makes them a working starting point
+//!   for a custom layout.
+//! - `slack` writes Slack `mrkdwn` for either forecast type.
+//!
+//! The context a template sees:
+//!
+//! | Name            | Contents                                                  |
+//! |-----------------|-----------------------------------------------------------|
+//! | `location`      | `name`, `lat`, `lon` from geocoding                       |
+//! | `point`         | The `/points` properties: `gridId`, `gridX`, `gridY`, ... |
+//! | `alerts`        | Active alerts: `event`, `headline`, `onset`, `ends`, ...  |
+//! | `forecast_type` | `"detailed"` or `"hourly"`                                |
+//! | `issuance`      | `updateTime`, `generatedAt`, `validTimes`, ...            |
+//! | `periods`       | The periods left after filtering and `--limit`            |
+//! | `filtered`      | How many periods the time filters removed                 |
+//! | `withheld`      | How many more `--limit` cut                               |
+//! | `notes`         | The lines saying so that the built-in layouts print       |
+//!
+//! Period and alert fields keep the API's camelCase names (`startTime`,
+//! `shortForecast`), so the NWS documentation doubles as the template's.
+//! Three filters apply the same `--time-format`, `--time-zone` and `--units`
+//! choices the built-in layouts do: `time` and `day` on a timestamp, and
+//! `measure` on a quantitative value such as `dewpoint`. A fourth, `paint`,
+//! colours text the way `--pretty` does, by what it is: `heading`, `day`,
+//! `name`, `narrative`, `temperature`, `conditions` or `dim`. Without
+//! `--pretty` it leaves the text alone, and the global `pretty` is false, for
+//! a template that lays the two styles out differently.
+//!
+//! Undefined names are errors rather than blanks, so a misspelt field fails
+//! loudly instead of quietly printing nothing.
+//!
+//! [MiniJinja]: https://docs.rs/minijinja
+
+use std::borrow::Cow;
+use std::path::PathBuf;
+use std::str::FromStr;
+
+use anyhow::{Context, Result};
+use chrono::{DateTime, FixedOffset};
+use minijinja::value::{Value, ViaDeserialize};
+use minijinja::{Environment, ErrorKind, UndefinedBehavior};
+use serde::Serialize;
+
+use crate::render::{limited, paint, truncation_lines, Paint, RenderOptions, Style};
+use crate::units::QuantitativeValue;
+use crate::weatherdotgov::{Alert, Issuance, PointsProperties};
+
+/// A template shipped inside the binary.
+#[derive(Debug, PartialEq)]
+pub struct Bundled {
+    pub name: &'static str,
+    source: &'static str,
+    /// Whether the template only makes sense over hourly periods.
+    pub hourly: bool,
+}
+
+/// The detailed forecast's layout; see [`crate::render::render_detailed`].
+pub const DETAILED: Bundled = Bundled {
+    name: "detailed",
+    source: include_str!("templates/detailed.j2"),
+    hourly: false,
+};
+
+/// The hourly forecast's layout; see [`crate::render::render_hourly`].
+pub const HOURLY: Bundled = Bundled {
+    name: "hourly",
+    source: include_str!("templates/hourly.j2"),
+    hourly: true,
+};
+
+pub const BUNDLED: &[Bundled] = &[
+    DETAILED,
+    HOURLY,
+    Bundled {
+        name: "slack",
+        source: include_str!("templates/slack.j2"),
+        hourly: false,
+    },
+];
+
+/// Where `--template` reads from.
+#[derive(Clone, Debug, PartialEq)]
+pub enum TemplateSource {
+    Bundled(&'static Bundled),
+    File(PathBuf),
+}
+
+impl FromStr for TemplateSource {
+    type Err = std::convert::Infallible;
+
+    /// A bundled name wins over a file of the same name in the working
+    /// directory; write `./slack` to mean the file.
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        Ok(match BUNDLED.iter().find(|b| b.name == s) {
+            Some(bundled) => TemplateSource::Bundled(bundled),
+            None => TemplateSource::File(PathBuf::from(s)),
+        })
+    }
+}
+
+impl TemplateSource {
+    pub fn wants_hourly(&self) -> bool {
+        matches!(self, TemplateSource::Bundled(b) if b.hourly)
+    }
+
+    fn name(&self) -> Cow<'_, str> {
+        match self {
+            TemplateSource::Bundled(b) => Cow::Borrowed(b.name),
+            TemplateSource::File(path) => path.to_string_lossy(),
+        }
+    }
+
+    fn load(&self) -> Result<Cow<'static, str>> {
+        match self {
+            TemplateSource::Bundled(b) => Ok(Cow::Borrowed(b.source)),
+            TemplateSource::File(path) => std::fs::read_to_string(path)
+                .map(Cow::Owned)
+                .with_context(|| format!("Error reading template {}", path.display())),
+        }
+    }
+}
+
+/// The geocoded location, as templates see it.
+#[derive(Debug, Serialize)]
+pub struct Place<'a> {
+    pub name: &'a str,
+    pub lat: &'a str,
+    pub lon: &'a str,
+}
+
+/// Everything a template can refer to. See the module docs for the fields.
+#[derive(Debug, Serialize)]
+pub struct TemplateContext<'a, T: Serialize> {
+    pub location: Place<'a>,
+    pub point: &'a PointsProperties,
+    pub alerts: &'a [Alert],
+    pub forecast_type: &'a str,
+    /// When the forecast was made, each field null when the API leaves it out.
+    pub issuance: &'a Issuance,
+    pub periods: &'a [T],
+    pub filtered: usize,
+    pub withheld: usize,
+    /// The notes the built-in layouts print under the periods, one per line.
+    pub notes: Vec<String>,
+}
+
+/// What the built-in layouts are rendered with: the periods and the notes
+/// under them, none of the fetched context a `--template` also gets.
+#[derive(Serialize)]
+struct Layout<'a, T: Serialize> {
+    periods: &'a [T],
+    notes: Vec<String>,
+}
+
+/// Parse a timestamp handed to a filter. Periods serialize their times as RFC
+/// 3339, so anything else is a template passing the wrong value.
+fn timestamp(value: &str) -> std::result::Result<DateTime<FixedOffset>, minijinja::Error> {
+    DateTime::parse_from_rfc3339(value).map_err(|e| {
+        minijinja::Error::new(
+            ErrorKind::InvalidOperation,
+            format!("'{value}' is not a timestamp: {e}"),
+        )
+    })
+}
+
+/// Build the environment with this crate's filters, bound to `opts`.
+fn environment<'source>(opts: &RenderOptions) -> Environment<'source> {
+    let mut env = Environment::new();
+    env.set_undefined_behavior(UndefinedBehavior::Strict);
+    // Block tags on their own line leave no trace in the output, so templates
+    // can be laid out readably without every tag needing `-` markers.
+    env.set_trim_blocks(true);
+    env.set_lstrip_blocks(true);
+    env.set_keep_trailing_newline(true);
+    env.add_global("pretty", opts.style == Style::Pretty);
+
+    let style = opts.style;
+    env.add_filter("paint", move |value: Value, role: &str| {
+        // A filter taking any value is handed undefined ones too, which
+        // strict mode would otherwise let through as blanks.
+        if value.is_undefined() {
+            return Err(minijinja::Error::from(ErrorKind::UndefinedError));
+        }
+        let role = match role {
+            "heading" => Paint::Heading,
+            "day" => Paint::Day,
+            "name" => Paint::Name,
+            "narrative" => Paint::Narrative,
+            "temperature" => Paint::Temperature,
+            "conditions" => Paint::Conditions,
+            "dim" => Paint::Dim,
+            _ => {
+                return Err(minijinja::Error::new(
+                    ErrorKind::InvalidOperation,
+                    format!("'{role}' is not something paint colours"),
+                ))
+            }
+        };
+        Ok(paint(&value.to_string(), role, style))
+    });
+
+    let times = opts.times;
+    env.add_filter("time", move |value: String| {
+        Ok::<_, minijinja::Error>(times.label(&timestamp(&value)?))
+    });
+    env.add_filter("day", move |value: String| {
+        Ok::<_, minijinja::Error>(times.day_label(&timestamp(&value)?))
+    });
+
+    // Empty for a missing or null value, so `{% if x | measure %}` works as
+    // the test for "is there a reading".
+    let units = opts.units;
+    env.add_filter(
+        "measure",
+        move |value: Option<ViaDeserialize<QuantitativeValue>>| {
+            value
+                .and_then(|v| v.0.to_units(units))
+                .map(|m| m.to_string())
+                .unwrap_or_default()
+        },
+    );
+    env
+}
+
+/// Render `context` through the template at `source`.
+pub fn render_template<T: Serialize>(
+    source: &TemplateSource,
+    context: &TemplateContext<'_, T>,
+    opts: &RenderOptions,
+) -> Result<String> {
+    let text = source.load()?;
+    render_str(&source.name(), &text, context, opts)
+}
+
+/// Render `periods` through the bundled `layout`, [`DETAILED`] or [`HOURLY`],
+/// in `opts.style`. `periods` and `filtered` are as for
+/// [`crate::render::render_detailed`].
+pub fn render_layout<T: Serialize>(
+    layout: &Bundled,
+    periods: &[T],
+    filtered: usize,
+    opts: &RenderOptions,
+) -> Result<String> {
+    let shown = limited(periods, opts.limit);
+    let context = Layout {
+        periods: shown,
+        notes: truncation_lines(filtered, periods.len(), shown.len()),
+    };
+    render_str(layout.name, layout.source, &context, opts)
+}
+
+fn render_str<C: Serialize>(
+    name: &str,
+    text: &str,
+    context: &C,
+    opts: &RenderOptions,
+) -> Result<String> {
+    let env = environment(opts);
+    let template = env
+        .template_from_named_str(name, text)
+        .with_context(|| format!("Error parsing template {name}"))?;
+    template
+        .render(context)
+        .with_context(|| format!("Error rendering template {name}"))
+}
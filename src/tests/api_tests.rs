@@ -1,7 +1,15 @@
 #[cfg(test)]
 mod tests {
-    use crate::nominatim::get_lat_lon;
-    use crate::weatherdotgov::{get_detailed_forecast, get_hourly_forecast, get_weather_point};
+    use crate::cache::{self, Cache};
+    use crate::nominatim::{get_lat_lon, search_locations};
+    use crate::units::Units;
+    use crate::weatherdotgov::{
+        find_marine_zone, find_zone, get_active_alerts, get_checked_forecast,
+        get_detailed_forecast, get_discussion, get_fire_weather, get_hourly_forecast,
+        get_latest_observation, get_observations, get_office, get_station, get_stations,
+        get_weather_point, get_zone_alerts, get_zone_forecast, search_zones, zone_id, Alert,
+        ForecastResponse, OnStale, PointsProperties, Severity, StaleCheck, Staleness, ZoneType,
+    };
     use crate::LocationInput;
     use mockito::Server;
 
@@ -10,6 +18,9 @@ mod tests {
         let mut server = Server::new_async().await;
         let mock_response = r#"{
             "properties": {
+                "gridId": "SEW",
+                "gridX": 115,
+                "gridY": 68,
                 "forecast": "https://api.weather.gov/gridpoints/SEW/115,68/forecast",
                 "forecastHourly": "https://api.weather.gov/gridpoints/SEW/115,68/forecast/hourly"
             }
@@ -24,6 +35,11 @@ mod tests {
 
         let result = get_weather_point("47.5619", "-122.625", Some(&server.url())).await;
         let response = result.expect("points lookup should succeed against the mock");
+        assert_eq!(response.properties.grid_id, "SEW");
+        assert_eq!(
+            (response.properties.grid_x, response.properties.grid_y),
+            (115, 68)
+        );
         assert!(response.properties.forecast.contains("/forecast"));
         assert!(response
             .properties
@@ -37,6 +53,76 @@ mod tests {
         mock.assert();
     }
 
+    #[tokio::test]
+    async fn test_points_carry_the_office_and_zones() {
+        let mut server = Server::new_async().await;
+        server
+            .mock("GET", "/points/47.6,-122.3")
+            .with_status(200)
+            .with_body(
+                r#"{
+                    "properties": {
+                        "gridId": "SEW",
+                        "gridX": 124,
+                        "gridY": 67,
+                        "forecast": "https://api.weather.gov/gridpoints/SEW/124,67/forecast",
+                        "cwa": "SEW",
+                        "forecastOffice": "https://api.weather.gov/offices/SEW",
+                        "forecastZone": "https://api.weather.gov/zones/forecast/WAZ558",
+                        "county": "https://api.weather.gov/zones/county/WAC033",
+                        "fireWeatherZone": "https://api.weather.gov/zones/fire/WAZ654",
+                        "radarStation": "KATX",
+                        "timeZone": "America/Los_Angeles"
+                    }
+                }"#,
+            )
+            .create();
+
+        let point = get_weather_point("47.6", "-122.3", Some(&server.url()))
+            .await
+            .unwrap()
+            .properties;
+        assert_eq!(point.office_id(), "SEW");
+        assert_eq!(point.forecast_zone_id(), Some("WAZ558"));
+        assert_eq!(point.county_id(), Some("WAC033"));
+        assert_eq!(point.fire_weather_zone_id(), Some("WAZ654"));
+        assert_eq!(point.radar_station.as_deref(), Some("KATX"));
+        assert_eq!(point.time_zone.as_deref(), Some("America/Los_Angeles"));
+        assert_eq!(zone_id("https://api.weather.gov/offices/SEW/"), "SEW");
+    }
+
+    #[tokio::test]
+    async fn test_get_office() {
+        let mut server = Server::new_async().await;
+        let mock = server
+            .mock("GET", "/offices/SEW")
+            .match_header("accept", "application/ld+json")
+            .with_status(200)
+            .with_body(
+                r#"{
+                    "@type": "GovernmentOrganization",
+                    "id": "SEW",
+                    "name": "Seattle, WA",
+                    "address": {
+                        "@type": "PostalAddress",
+                        "streetAddress": "7600 Sand Point Way NE",
+                        "addressLocality": "Seattle",
+                        "addressRegion": "WA",
+                        "postalCode": "98115-6349"
+                    },
+                    "telephone": "(206) 526-6087",
+                    "email": "w-sew.webmaster@noaa.gov"
+                }"#,
+            )
+            .create();
+
+        let office = get_office("SEW", Some(&server.url())).await.unwrap();
+        assert_eq!(office.name, "Seattle, WA");
+        assert_eq!(office.address.unwrap().postal_code, "98115-6349");
+        assert_eq!(office.telephone.as_deref(), Some("(206) 526-6087"));
+        mock.assert();
+    }
+
     #[tokio::test]
     async fn test_get_detailed_forecast() {
         let mut server = Server::new_async().await;
@@ -44,7 +130,11 @@ mod tests {
             "properties": {
                 "periods": [
                     {
+                        "number": 1,
                         "name": "Tonight",
+                        "startTime": "2024-01-28T18:00:00-08:00",
+                        "endTime": "2024-01-29T06:00:00-08:00",
+                        "isDaytime": false,
                         "detailedForecast": "Partly cloudy with a chance of rain"
                     }
                 ]
@@ -53,18 +143,24 @@ mod tests {
 
         server
             .mock("GET", "/gridpoints/SEW/115,68/forecast")
+            .match_query(mockito::Matcher::Any)
             .with_status(200)
             .with_header("content-type", "application/geo+json")
             .with_body(mock_response)
             .create();
 
-        let result =
-            get_detailed_forecast(&format!("{}/gridpoints/SEW/115,68/forecast", server.url()))
-                .await;
+        let result = get_detailed_forecast(
+            &format!("{}/gridpoints/SEW/115,68/forecast", server.url()),
+            Units::Us,
+        )
+        .await;
         assert!(result.is_ok());
         let response = result.unwrap();
         assert_eq!(response.properties.periods.len(), 1);
-        assert_eq!(response.properties.periods[0].name, "Tonight");
+        let period = &response.properties.periods[0];
+        assert_eq!(period.name, "Tonight");
+        assert_eq!(period.end_time.to_rfc3339(), "2024-01-29T06:00:00-08:00");
+        assert!(!period.is_daytime);
     }
 
     #[tokio::test]
@@ -93,15 +189,16 @@ mod tests {
 
         server
             .mock("GET", "/gridpoints/SEW/115,68/forecast/hourly")
+            .match_query(mockito::Matcher::Any)
             .with_status(200)
             .with_header("content-type", "application/geo+json")
             .with_body(mock_response)
             .create();
 
-        let result = get_hourly_forecast(&format!(
-            "{}/gridpoints/SEW/115,68/forecast/hourly",
-            server.url()
-        ))
+        let result = get_hourly_forecast(
+            &format!("{}/gridpoints/SEW/115,68/forecast/hourly", server.url()),
+            Units::Us,
+        )
         .await;
         let response = result.expect("hourly forecast should deserialize real NWS camelCase JSON");
         assert_eq!(response.properties.periods.len(), 1);
@@ -110,7 +207,7 @@ mod tests {
         // temperature would still pass if the camelCase renames were dropped,
         // because temperature is the one field whose name needs no remapping.
         let period = &response.properties.periods[0];
-        assert_eq!(period.start_time, "2024-01-29T02:32:45+00:00");
+        assert_eq!(period.start_time.to_rfc3339(), "2024-01-29T02:32:45+00:00");
         assert_eq!(period.temperature, 51);
         assert_eq!(period.temperature_unit, "F");
         assert_eq!(period.wind_speed, "5 mph");
@@ -118,6 +215,131 @@ mod tests {
         assert_eq!(period.short_forecast, "Partly Cloudy");
     }
 
+    #[tokio::test]
+    async fn test_forecast_requests_send_the_units_parameter() {
+        let mut server = Server::new_async().await;
+        let mock = server
+            .mock("GET", "/gridpoints/SEW/115,68/forecast")
+            .match_query(mockito::Matcher::UrlEncoded(
+                "units".to_string(),
+                "si".to_string(),
+            ))
+            .with_status(200)
+            .with_header("content-type", "application/geo+json")
+            .with_body(r#"{"properties":{"periods":[]}}"#)
+            .create();
+
+        get_detailed_forecast(
+            &format!("{}/gridpoints/SEW/115,68/forecast", server.url()),
+            Units::Si,
+        )
+        .await
+        .expect("request carrying units=si should match the mock");
+
+        mock.assert();
+    }
+
+    #[tokio::test]
+    async fn test_hourly_forecast_is_converted_when_the_server_ignores_units() {
+        // The server is asked for SI but answers in US units; the periods must
+        // still come back in the system that was requested.
+        let mut server = Server::new_async().await;
+        server
+            .mock("GET", "/gridpoints/SEW/115,68/forecast/hourly")
+            .match_query(mockito::Matcher::Any)
+            .with_status(200)
+            .with_header("content-type", "application/geo+json")
+            .with_body(
+                r#"{"properties":{"periods":[{
+                    "startTime": "2024-01-29T02:00:00+00:00",
+                    "endTime": "2024-01-29T03:00:00+00:00",
+                    "isDaytime": false,
+                    "temperature": 50,
+                    "temperatureUnit": "F",
+                    "windSpeed": "5 to 10 mph",
+                    "windDirection": "SSW",
+                    "shortForecast": "Partly Cloudy",
+                    "dewpoint": {"unitCode": "wmoUnit:degC", "value": 4.4},
+                    "probabilityOfPrecipitation": {"unitCode": "wmoUnit:percent", "value": 20}
+                }]}}"#,
+            )
+            .create();
+
+        let response = get_hourly_forecast(
+            &format!("{}/gridpoints/SEW/115,68/forecast/hourly", server.url()),
+            Units::Si,
+        )
+        .await
+        .expect("hourly forecast should deserialize");
+
+        let period = &response.properties.periods[0];
+        assert_eq!(period.temperature, 10);
+        assert_eq!(period.temperature_unit, "C");
+        assert_eq!(period.wind_speed, "8 to 16 km/h");
+        assert_eq!(
+            period.dewpoint.as_ref().and_then(|d| d.value),
+            Some(4.4),
+            "nested quantitative values are converted at display, not here"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_get_active_alerts() {
+        let mut server = Server::new_async().await;
+        let mock = server
+            .mock("GET", "/alerts/active")
+            .match_query(mockito::Matcher::UrlEncoded(
+                "point".to_string(),
+                "47.5619,-122.625".to_string(),
+            ))
+            .with_status(200)
+            .with_header("content-type", "application/geo+json")
+            .with_body(
+                r#"{
+                    "type": "FeatureCollection",
+                    "features": [
+                        {
+                            "properties": {
+                                "id": "urn:oid:2.49.0.1.840.0.abc.001.1",
+                                "event": "Wind Advisory",
+                                "headline": "Wind Advisory issued January 28",
+                                "severity": "Moderate",
+                                "onset": "2024-01-28T18:00:00-08:00",
+                                "ends": "2024-01-29T06:00:00-08:00"
+                            }
+                        },
+                        {
+                            "properties": {
+                                "event": "Special Weather Statement",
+                                "headline": null,
+                                "severity": "Some Future Value"
+                            }
+                        }
+                    ]
+                }"#,
+            )
+            .create();
+
+        let alerts = get_active_alerts("47.5619", "-122.625", Some(&server.url()))
+            .await
+            .expect("alerts should deserialize");
+
+        assert_eq!(alerts.len(), 2);
+        assert_eq!(alerts[0].event, "Wind Advisory");
+        assert_eq!(alerts[0].severity, Severity::Moderate);
+        assert_eq!(alerts[0].id, "urn:oid:2.49.0.1.840.0.abc.001.1");
+        assert_eq!(
+            alerts[0].ends.map(|t| t.to_rfc3339()).as_deref(),
+            Some("2024-01-29T06:00:00-08:00")
+        );
+        // Onset and end are optional in the API and must stay optional here.
+        assert!(alerts[1].onset.is_none() && alerts[1].ends.is_none());
+        // An unrecognised severity must not fail the whole list.
+        assert_eq!(alerts[1].severity, Severity::Unknown);
+        assert!(alerts[1].headline.is_none());
+        mock.assert();
+    }
+
     #[tokio::test]
     async fn test_get_lat_lon() {
         let mut server = Server::new_async().await;
@@ -181,7 +403,10 @@ mod tests {
             .match_header("user-agent", crate::http::user_agent().as_str())
             .with_status(200)
             .with_header("content-type", "application/geo+json")
-            .with_body(r#"{"properties":{"forecast":"https://example.invalid/forecast"}}"#)
+            .with_body(
+                r#"{"properties":{"gridId":"SEW","gridX":115,"gridY":68,
+                    "forecast":"https://example.invalid/forecast"}}"#,
+            )
             .create();
 
         get_weather_point("47.5619", "-122.625", Some(&server.url()))
@@ -248,15 +473,19 @@ mod tests {
         let mut server = Server::new_async().await;
         let mock = server
             .mock("GET", "/gridpoints/SEW/115,68/forecast")
+            .match_query(mockito::Matcher::Any)
             .match_header("accept", "application/geo+json")
             .with_status(200)
             .with_header("content-type", "application/geo+json")
             .with_body(r#"{"properties":{"periods":[]}}"#)
             .create();
 
-        get_detailed_forecast(&format!("{}/gridpoints/SEW/115,68/forecast", server.url()))
-            .await
-            .expect("forecast request should carry the GeoJSON Accept header");
+        get_detailed_forecast(
+            &format!("{}/gridpoints/SEW/115,68/forecast", server.url()),
+            Units::Us,
+        )
+        .await
+        .expect("forecast request should carry the GeoJSON Accept header");
 
         mock.assert();
     }
@@ -306,4 +535,614 @@ mod tests {
 
         assert!(result.is_err());
     }
+
+    #[tokio::test]
+    async fn test_search_locations_lists_every_match() {
+        let mut server = Server::new_async().await;
+        let mock = server
+            .mock("GET", "/search")
+            .match_query(mockito::Matcher::UrlEncoded(
+                "limit".to_string(),
+                "3".to_string(),
+            ))
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(
+                r#"[
+                    {"lat": "39.80", "lon": "-89.64", "display_name": "Springfield, Illinois"},
+                    {"lat": "42.10", "lon": "-72.59", "display_name": "Springfield, Massachusetts"}
+                ]"#,
+            )
+            .create();
+
+        let input = LocationInput::City("Springfield".to_string());
+        let places = search_locations(input, 3, Some(&server.url()))
+            .await
+            .expect("search should succeed against the mock");
+
+        let names: Vec<_> = places.iter().map(|p| p.display_name.as_str()).collect();
+        assert_eq!(
+            names,
+            ["Springfield, Illinois", "Springfield, Massachusetts"]
+        );
+        mock.assert();
+    }
+
+    #[tokio::test]
+    async fn test_get_discussion_fetches_the_latest_product() {
+        let mut server = Server::new_async().await;
+        let list = server
+            .mock("GET", "/products/types/AFD/locations/SEW")
+            .match_header("accept", "application/ld+json")
+            .with_status(200)
+            .with_header("content-type", "application/ld+json")
+            .with_body(r#"{"@graph": [{"id": "newest"}, {"id": "older"}]}"#)
+            .create();
+        let product = server
+            .mock("GET", "/products/newest")
+            .with_status(200)
+            .with_header("content-type", "application/ld+json")
+            .with_body(
+                r#"{
+                    "id": "newest",
+                    "issuingOffice": "KSEW",
+                    "issuanceTime": "2024-01-28T15:04:00+00:00",
+                    "productCode": "AFD",
+                    "productName": "Area Forecast Discussion",
+                    "productText": ".SYNOPSIS...Showers taper off tonight."
+                }"#,
+            )
+            .create();
+
+        let discussion = get_discussion("SEW", Some(&server.url()))
+            .await
+            .expect("discussion should be fetched from the mock");
+        assert_eq!(discussion.issuing_office, "KSEW");
+        assert!(discussion.product_text.contains("Showers taper off"));
+        list.assert();
+        product.assert();
+    }
+
+    #[tokio::test]
+    async fn test_get_discussion_with_none_issued() {
+        let mut server = Server::new_async().await;
+        server
+            .mock("GET", "/products/types/AFD/locations/XYZ")
+            .with_status(200)
+            .with_body(r#"{"@graph": []}"#)
+            .create();
+
+        let err = get_discussion("XYZ", Some(&server.url()))
+            .await
+            .expect_err("an empty product list has no discussion to show");
+        assert!(format!("{err}").contains("XYZ"));
+    }
+
+    /// A one-period detailed forecast last updated at `update_time`.
+    fn forecast_updated(update_time: &str) -> String {
+        format!(
+            r#"{{
+                "properties": {{
+                    "updated": "{update_time}",
+                    "updateTime": "{update_time}",
+                    "generatedAt": "{update_time}",
+                    "validTimes": "2024-01-28T12:00:00+00:00/P7DT13H",
+                    "periods": [
+                        {{
+                            "name": "Tonight",
+                            "startTime": "2024-01-28T18:00:00-08:00",
+                            "endTime": "2024-01-29T06:00:00-08:00",
+                            "isDaytime": false,
+                            "detailedForecast": "Partly cloudy"
+                        }}
+                    ]
+                }}
+            }}"#
+        )
+    }
+
+    fn check(on_stale: OnStale) -> StaleCheck {
+        StaleCheck {
+            max_age: std::time::Duration::from_secs(12 * 60 * 60),
+            on_stale,
+        }
+    }
+
+    #[test]
+    fn test_staleness_uses_the_older_of_update_and_generation() {
+        let now = chrono::Utc::now();
+        let fresh = now.to_rfc3339();
+        let forecast: ForecastResponse = serde_json::from_str(&forecast_updated(&fresh)).unwrap();
+        assert_eq!(check(OnStale::Warn).staleness(&forecast, now), None);
+        assert_eq!(
+            forecast.properties.issuance.valid_times.as_deref(),
+            Some("2024-01-28T12:00:00+00:00/P7DT13H")
+        );
+
+        // Updated recently, but served from a copy generated long ago.
+        let mut stuck = forecast;
+        let old = chrono::DateTime::parse_from_rfc3339("2024-01-28T00:00:00+00:00").unwrap();
+        stuck.properties.issuance.generated_at = Some(old);
+        assert_eq!(
+            check(OnStale::Warn).staleness(&stuck, now),
+            Some(Staleness::Old(old))
+        );
+
+        stuck.properties.periods.clear();
+        assert_eq!(
+            check(OnStale::Warn).staleness(&stuck, now),
+            Some(Staleness::Empty)
+        );
+    }
+
+    #[tokio::test]
+    async fn test_stale_forecast_is_warned_about() {
+        let mut server = Server::new_async().await;
+        server
+            .mock("GET", "/gridpoints/SEW/115,68/forecast")
+            .match_query(mockito::Matcher::Any)
+            .with_status(200)
+            .with_body(forecast_updated("2024-01-28T00:00:00+00:00"))
+            .create();
+        let url = format!("{}/gridpoints/SEW/115,68/forecast", server.url());
+
+        let checked = get_checked_forecast(&url, Units::Us, &check(OnStale::Warn))
+            .await
+            .unwrap();
+        assert_eq!(checked.forecast.properties.periods.len(), 1);
+        assert_eq!(
+            checked.warning.as_deref(),
+            Some("the forecast is stale as of 2024-01-28 00:00 UTC")
+        );
+    }
+
+    #[tokio::test]
+    async fn test_stale_forecast_is_retried_past_caches() {
+        let mut server = Server::new_async().await;
+        server
+            .mock("GET", "/gridpoints/SEW/115,68/forecast")
+            .match_query(mockito::Matcher::Any)
+            .match_header("cache-control", mockito::Matcher::Missing)
+            .with_status(200)
+            .with_body(forecast_updated("2024-01-28T00:00:00+00:00"))
+            .create();
+        let reload = server
+            .mock("GET", "/gridpoints/SEW/115,68/forecast")
+            .match_query(mockito::Matcher::Any)
+            .match_header("cache-control", "no-cache")
+            .with_status(200)
+            .with_body(forecast_updated(&chrono::Utc::now().to_rfc3339()))
+            .expect(1)
+            .create();
+        let url = format!("{}/gridpoints/SEW/115,68/forecast", server.url());
+
+        let checked = get_checked_forecast(&url, Units::Us, &check(OnStale::Retry))
+            .await
+            .unwrap();
+        assert_eq!(checked.warning, None);
+        reload.assert();
+    }
+
+    #[tokio::test]
+    async fn test_stale_forecast_falls_back_to_the_last_good_copy() {
+        let mut server = Server::new_async().await;
+        let good = forecast_updated(&chrono::Utc::now().to_rfc3339());
+        server
+            .mock("GET", "/gridpoints/SEW/115,68/forecast")
+            .match_query(mockito::Matcher::Any)
+            .match_header("cache-control", mockito::Matcher::Missing)
+            .with_status(200)
+            .with_body(&good)
+            .expect(1)
+            .create();
+        server
+            .mock("GET", "/gridpoints/SEW/115,68/forecast")
+            .match_query(mockito::Matcher::Any)
+            .match_header("cache-control", "no-cache")
+            .with_status(200)
+            .with_body(r#"{"properties":{"periods":[]}}"#)
+            .create();
+        let url = format!("{}/gridpoints/SEW/115,68/forecast", server.url());
+
+        let shared = std::sync::Arc::new(Cache::default());
+        let fallback = check(OnStale::Fallback);
+        // A good forecast, remembered as such.
+        let checked = cache::scope(
+            shared.clone(),
+            get_checked_forecast(&url, Units::Us, &fallback),
+        )
+        .await
+        .unwrap();
+        assert_eq!(checked.warning, None);
+
+        // Then the cached copy goes bad, and so does the retry.
+        shared.insert(
+            cache::key(&url, &[("units", "us")]),
+            r#"{"properties":{"periods":[]}}"#.to_string(),
+        );
+        let checked = cache::scope(
+            shared.clone(),
+            get_checked_forecast(&url, Units::Us, &fallback),
+        )
+        .await
+        .unwrap();
+        assert_eq!(checked.forecast.properties.periods.len(), 1);
+        let warning = checked.warning.unwrap();
+        assert!(
+            warning.starts_with(
+                "the forecast has no periods; showing the last good copy, stale as of"
+            ),
+            "{warning}"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_search_zones() {
+        let mut server = Server::new_async().await;
+        let mock = server
+            .mock("GET", "/zones")
+            .match_query(mockito::Matcher::AllOf(vec![
+                mockito::Matcher::UrlEncoded("type".into(), "forecast".into()),
+                mockito::Matcher::UrlEncoded("area".into(), "WA".into()),
+            ]))
+            .with_status(200)
+            .with_body(
+                r#"{
+                    "features": [
+                        {"properties": {"id": "WAZ558", "name": "City of Seattle", "state": "WA"}},
+                        {"properties": {"id": "WAZ507", "name": "Bellevue and Vicinity", "state": "WA"}}
+                    ]
+                }"#,
+            )
+            .create();
+
+        let zones = search_zones(ZoneType::Forecast, "WA", Some(&server.url()))
+            .await
+            .unwrap();
+        assert_eq!(zones.len(), 2);
+        assert_eq!(zones[0].id, "WAZ558");
+        assert_eq!(zones[0].name, "City of Seattle");
+        mock.assert();
+    }
+
+    #[tokio::test]
+    async fn test_zone_forecast_renders_with_the_detailed_renderer() {
+        use crate::render::{render_detailed, RenderOptions, Style};
+        use crate::times::{DisplayZone, TimeDisplay, TimeFormat};
+
+        let mut server = Server::new_async().await;
+        server
+            .mock("GET", "/zones/forecast/WAZ558/forecast")
+            .with_status(200)
+            .with_body(
+                r#"{
+                    "properties": {
+                        "zone": "https://api.weather.gov/zones/forecast/WAZ558",
+                        "updated": "2024-01-28T20:12:00-08:00",
+                        "periods": [
+                            {"number": 1, "name": "Tonight", "detailedForecast": "Showers likely. Lows around 45."},
+                            {"number": 2, "name": "Monday", "detailedForecast": "Rain. Highs around 52."}
+                        ]
+                    }
+                }"#,
+            )
+            .create();
+
+        let forecast = get_zone_forecast(ZoneType::Forecast, "WAZ558", Some(&server.url()))
+            .await
+            .unwrap();
+        let opts = RenderOptions {
+            style: Style::Plain,
+            limit: None,
+            units: Units::Us,
+            times: TimeDisplay::new(TimeFormat::TwelveHour, DisplayZone::Location),
+        };
+        let out = render_detailed(&forecast.periods, 0, &opts).unwrap();
+        assert!(
+            out.contains("Tonight: Showers likely. Lows around 45."),
+            "{out}"
+        );
+        assert!(out.contains("Monday: Rain. Highs around 52."), "{out}");
+    }
+
+    #[tokio::test]
+    async fn test_get_zone_alerts() {
+        let mut server = Server::new_async().await;
+        let mock = server
+            .mock("GET", "/alerts/active/zone/WAZ558")
+            .with_status(200)
+            .with_body(
+                r#"{
+                    "features": [
+                        {"properties": {
+                            "id": "urn:oid:2.49.0.1.840.0.1",
+                            "event": "Wind Advisory",
+                            "severity": "Moderate",
+                            "headline": "Wind Advisory until 4 AM PST"
+                        }}
+                    ]
+                }"#,
+            )
+            .create();
+
+        let alerts = get_zone_alerts("WAZ558", Some(&server.url()))
+            .await
+            .unwrap();
+        assert_eq!(alerts.len(), 1);
+        assert_eq!(alerts[0].event, "Wind Advisory");
+        mock.assert();
+    }
+
+    #[tokio::test]
+    async fn test_find_zone_and_its_marine_forecast() {
+        use crate::render::{render_marine, RenderOptions, Style};
+        use crate::times::{DisplayZone, TimeDisplay, TimeFormat};
+
+        let mut server = Server::new_async().await;
+        server
+            .mock("GET", "/zones")
+            .match_query(mockito::Matcher::UrlEncoded("id".into(), "PZZ135".into()))
+            .with_status(200)
+            .with_body(
+                r#"{
+                    "features": [
+                        {"properties": {"id": "PZZ135", "name": "Puget Sound and Hood Canal", "type": "coastal"}}
+                    ]
+                }"#,
+            )
+            .create();
+        server
+            .mock("GET", "/zones/coastal/PZZ135/forecast")
+            .with_status(200)
+            .with_body(
+                r#"{
+                    "properties": {
+                        "periods": [
+                            {"number": 1, "name": "Tonight", "detailedForecast": "S wind 10 to 15 kt. Wind waves 1 to 2 ft. A chance of rain."}
+                        ]
+                    }
+                }"#,
+            )
+            .create();
+        server
+            .mock("GET", "/zones")
+            .match_query(mockito::Matcher::UrlEncoded("id".into(), "PZZ999".into()))
+            .with_status(200)
+            .with_body(r#"{"features": []}"#)
+            .create();
+
+        let zone = find_zone("PZZ135", Some(&server.url())).await.unwrap();
+        assert_eq!(zone.zone_type(), Some(ZoneType::Coastal));
+        let forecast = get_zone_forecast(ZoneType::Coastal, &zone.id, Some(&server.url()))
+            .await
+            .unwrap();
+        let opts = RenderOptions {
+            style: Style::Plain,
+            limit: None,
+            units: Units::Us,
+            times: TimeDisplay::new(TimeFormat::TwelveHour, DisplayZone::Location),
+        };
+        let out = render_marine(&forecast.periods, &opts);
+        assert!(out.contains("  Wind: S wind 10 to 15 kt.\n"), "{out}");
+        assert!(out.contains("  Seas: Wind waves 1 to 2 ft.\n"), "{out}");
+        assert!(out.contains("  A chance of rain.\n"), "{out}");
+
+        let err = find_zone("PZZ999", Some(&server.url())).await.unwrap_err();
+        assert!(format!("{err}").contains("zones search"), "{err}");
+    }
+
+    #[tokio::test]
+    async fn test_find_marine_zone_skips_land_zones() {
+        let mut server = Server::new_async().await;
+        let mock = server
+            .mock("GET", "/zones")
+            .match_query(mockito::Matcher::UrlEncoded(
+                "point".into(),
+                "47.7,-122.45".into(),
+            ))
+            .with_status(200)
+            .with_body(
+                r#"{
+                    "features": [
+                        {"properties": {"id": "WAZ558", "name": "City of Seattle", "type": "public"}},
+                        {"properties": {"id": "PZZ135", "name": "Puget Sound and Hood Canal", "type": "coastal"}}
+                    ]
+                }"#,
+            )
+            .create();
+
+        let zone = find_marine_zone("47.7", "-122.45", Some(&server.url()))
+            .await
+            .unwrap();
+        assert_eq!(zone.map(|zone| zone.id).as_deref(), Some("PZZ135"));
+        mock.assert();
+    }
+
+    #[tokio::test]
+    async fn test_marine_alerts_are_told_apart() {
+        let mut server = Server::new_async().await;
+        server
+            .mock("GET", "/alerts/active/zone/PZZ135")
+            .with_status(200)
+            .with_body(
+                r#"{
+                    "features": [
+                        {"properties": {"event": "Small Craft Advisory", "severity": "Minor"}},
+                        {"properties": {"event": "Wind Advisory", "severity": "Moderate"}}
+                    ]
+                }"#,
+            )
+            .create();
+
+        let alerts = get_zone_alerts("PZZ135", Some(&server.url()))
+            .await
+            .unwrap();
+        let marine: Vec<_> = alerts.iter().filter(|alert| alert.is_marine()).collect();
+        assert_eq!(marine.len(), 1);
+        assert_eq!(marine[0].event, "Small Craft Advisory");
+    }
+
+    #[tokio::test]
+    async fn test_get_fire_weather() {
+        let mut server = Server::new_async().await;
+        let mock = server
+            .mock("GET", "/gridpoints/SEW/124,67")
+            .with_status(200)
+            .with_body(
+                r#"{
+                    "properties": {
+                        "temperature": {"uom": "wmoUnit:degC", "values": []},
+                        "relativeHumidity": {"uom": "wmoUnit:percent", "values": [
+                            {"validTime": "2024-07-15T18:00:00+00:00/PT2H", "value": 35},
+                            {"validTime": "2024-07-15T20:00:00+00:00/PT4H", "value": 21}
+                        ]},
+                        "redFlagThreatIndex": {"values": [
+                            {"validTime": "2024-07-15T18:00:00+00:00/PT6H", "value": null}
+                        ]}
+                    }
+                }"#,
+            )
+            .create();
+
+        let point = PointsProperties {
+            grid_id: "SEW".to_string(),
+            grid_x: 124,
+            grid_y: 67,
+            ..Default::default()
+        };
+        let fire = get_fire_weather(&point, Some(&server.url())).await.unwrap();
+        mock.assert();
+
+        let at = |s: &str| chrono::DateTime::parse_from_rfc3339(s).unwrap();
+        let (start, end) = (at("2024-07-15T19:00:00Z"), at("2024-07-16T00:00:00Z"));
+        let (when, lowest) = fire.relative_humidity.extreme(start, end, false).unwrap();
+        assert_eq!(
+            (when, lowest.value),
+            (at("2024-07-15T20:00:00Z"), Some(21.0))
+        );
+        // The first value started before the window, so it counts from its start.
+        let (when, highest) = fire.relative_humidity.extreme(start, end, true).unwrap();
+        assert_eq!((when, highest.value), (start, Some(35.0)));
+        // Nothing known is nothing to report, and absent layers are empty.
+        assert!(fire
+            .red_flag_threat_index
+            .extreme(start, end, true)
+            .is_none());
+        assert!(fire.mixing_height.values.is_empty());
+    }
+
+    #[test]
+    fn test_fire_alerts_are_told_apart() {
+        for (event, fire) in [
+            ("Red Flag Warning", true),
+            ("Fire Weather Watch", true),
+            ("Heat Advisory", false),
+        ] {
+            let alert: Alert =
+                serde_json::from_value(serde_json::json!({"event": event, "severity": "Moderate"}))
+                    .unwrap();
+            assert_eq!(alert.is_fire(), fire, "{event}");
+        }
+    }
+
+    #[tokio::test]
+    async fn test_get_stations_and_observations() {
+        let mut server = Server::new_async().await;
+        server
+            .mock("GET", "/gridpoints/SEW/124,67/stations")
+            .with_status(200)
+            .with_body(
+                r#"{
+                    "features": [
+                        {"geometry": {"type": "Point", "coordinates": [-122.31442, 47.44467]},
+                            "properties": {"stationIdentifier": "KSEA", "name": "Seattle-Tacoma International Airport"}},
+                        {"properties": {"stationIdentifier": "KBFI", "name": "Seattle, Boeing Field"}}
+                    ]
+                }"#,
+            )
+            .create();
+        let mock = server
+            .mock("GET", "/stations/KSEA/observations")
+            .match_query(mockito::Matcher::AllOf(vec![
+                mockito::Matcher::UrlEncoded("start".into(), "2024-07-15T00:00:00Z".into()),
+                mockito::Matcher::UrlEncoded("end".into(), "2024-07-16T00:00:00Z".into()),
+            ]))
+            .with_status(200)
+            .with_body(
+                r#"{
+                    "features": [
+                        {"properties": {"timestamp": "2024-07-15T23:53:00+00:00",
+                            "temperature": {"unitCode": "wmoUnit:degC", "value": 24.4}}},
+                        {"properties": {"timestamp": "2024-07-15T22:53:00+00:00",
+                            "temperature": {"unitCode": "wmoUnit:degC", "value": null}}}
+                    ]
+                }"#,
+            )
+            .create();
+
+        let url = format!("{}/gridpoints/SEW/124,67/stations", server.url());
+        let stations = get_stations(&url).await.unwrap();
+        assert_eq!(stations.len(), 2);
+        assert_eq!(stations[0].station_identifier, "KSEA");
+        // GeoJSON gives longitude first.
+        assert_eq!(stations[0].position, Some((47.44467, -122.31442)));
+        assert_eq!(stations[1].position, None);
+
+        let at = |s: &str| s.parse::<chrono::DateTime<chrono::Utc>>().unwrap();
+        let observations = get_observations(
+            "KSEA",
+            at("2024-07-15T00:00:00Z"),
+            at("2024-07-16T00:00:00Z"),
+            Some(&server.url()),
+        )
+        .await
+        .unwrap();
+        mock.assert();
+        // The API lists newest first; history reads oldest first.
+        assert_eq!(observations.len(), 2);
+        assert!(observations[0].timestamp < observations[1].timestamp);
+        assert_eq!(observations[0].temperature.as_ref().unwrap().value, None);
+    }
+
+    #[tokio::test]
+    async fn test_get_station_and_latest_observation() {
+        let mut server = Server::new_async().await;
+        server
+            .mock("GET", "/stations/KBFI")
+            .with_status(200)
+            .with_body(
+                r#"{
+                    "geometry": {"type": "Point", "coordinates": [-122.31442, 47.53]},
+                    "properties": {"stationIdentifier": "KBFI", "name": "Seattle, Boeing Field"}
+                }"#,
+            )
+            .create();
+        server
+            .mock("GET", "/stations/KBFI/observations/latest")
+            .with_status(200)
+            .with_body(
+                r#"{
+                    "properties": {"timestamp": "2024-07-15T23:53:00+00:00",
+                        "textDescription": "Clear",
+                        "temperature": {"unitCode": "wmoUnit:degC", "value": 24.4}}
+                }"#,
+            )
+            .create();
+        server
+            .mock("GET", "/stations/KXXX")
+            .with_status(404)
+            .with_body(r#"{"title": "Not Found", "detail": "Station not found"}"#)
+            .create();
+
+        let station = get_station("KBFI", Some(&server.url())).await.unwrap();
+        assert_eq!(station.name, "Seattle, Boeing Field");
+        assert_eq!(station.position, Some((47.53, -122.31442)));
+
+        let latest = get_latest_observation("KBFI", Some(&server.url()))
+            .await
+            .unwrap();
+        assert_eq!(latest.text_description.as_deref(), Some("Clear"));
+
+        assert!(get_station("KXXX", Some(&server.url())).await.is_err());
+    }
 }
@@ -1,5 +1,6 @@
 #[cfg(test)]
 mod tests {
+    use crate::render::OutputFormat;
     use crate::Args;
     use crate::ForecastType;
     use clap::Parser;
@@ -8,21 +9,564 @@ mod tests {
     async fn test_args_parsing() {
         let args = vec!["wxdotgov", "--zip", "12345"];
         let parsed = Args::try_parse_from(args).unwrap();
-        assert_eq!(parsed.zip.unwrap(), "12345");
-        assert!(parsed.city.is_none());
-        assert!(parsed.state.is_none());
-        assert!(!parsed.pretty);
+        assert_eq!(parsed.forecast.location.zip.unwrap(), "12345");
+        assert!(parsed.forecast.location.city.is_none());
+        assert!(parsed.forecast.location.state.is_none());
+        assert!(!parsed.forecast.display.pretty);
         assert_eq!(parsed.forecast_type, ForecastType::Detailed);
-        assert_eq!(parsed.limit, crate::render::DEFAULT_LIMIT);
+        assert_eq!(parsed.forecast.limit, crate::render::DEFAULT_LIMIT);
+        assert!(!parsed.forecast.chart);
+    }
+
+    #[tokio::test]
+    async fn test_args_units() {
+        let args = vec!["wxdotgov", "--zip", "12345", "--units", "si"];
+        let parsed = Args::try_parse_from(args).unwrap();
+        assert_eq!(parsed.forecast.display.units, crate::units::Units::Si);
+
+        let bad = vec!["wxdotgov", "--zip", "12345", "--units", "imperial"];
+        assert!(Args::try_parse_from(bad).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_args_time_options() {
+        use crate::times::{DisplayZone, TimeFormat};
+
+        let args = vec![
+            "wxdotgov",
+            "--zip",
+            "12345",
+            "--time-format",
+            "24h",
+            "--time-zone",
+            "local",
+        ];
+        let parsed = Args::try_parse_from(args).unwrap();
+        assert_eq!(parsed.forecast.time_format, TimeFormat::TwentyFourHour);
+        assert_eq!(parsed.forecast.time_zone, DisplayZone::Local);
+
+        let defaults = Args::try_parse_from(vec!["wxdotgov", "--zip", "12345"]).unwrap();
+        assert_eq!(defaults.forecast.time_format, TimeFormat::TwelveHour);
+        assert_eq!(defaults.forecast.time_zone, DisplayZone::Location);
+    }
+
+    #[tokio::test]
+    async fn test_args_time_filters() {
+        use crate::filter::DayPart;
+        use crate::times::TimeSpec;
+
+        let args = vec![
+            "wxdotgov",
+            "--zip",
+            "12345",
+            "--from",
+            "tonight",
+            "--until",
+            "+12h",
+            "--night-only",
+        ];
+        let filter = Args::try_parse_from(args).unwrap().forecast.period_filter();
+        assert_eq!(filter.from, Some(TimeSpec::Tonight));
+        assert!(matches!(filter.until, Some(TimeSpec::Ahead(_))));
+        assert_eq!(filter.part, Some(DayPart::Night));
+
+        let both = vec!["wxdotgov", "--zip", "1", "--daytime-only", "--night-only"];
+        assert!(Args::try_parse_from(both).is_err());
+
+        let bad = vec!["wxdotgov", "--zip", "12345", "--from", "whenever"];
+        assert!(Args::try_parse_from(bad).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_args_status_line_modes() {
+        let args = vec!["wxdotgov", "--zip", "12345", "--format", "{temp}°{unit}"];
+        let parsed = Args::try_parse_from(args).unwrap();
+        assert!(parsed.forecast.format.is_some());
+        // A status line is always drawn from the hourly forecast.
+        assert_eq!(
+            parsed.forecast.forecast_type(parsed.forecast_type),
+            ForecastType::Hourly
+        );
+
+        let typo = vec!["wxdotgov", "--zip", "12345", "--format", "{tmep}"];
+        assert!(Args::try_parse_from(typo).is_err());
+
+        let both = vec!["wxdotgov", "--zip", "12345", "--waybar", "--chart"];
+        assert!(Args::try_parse_from(both).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_args_template() {
+        let args = vec!["wxdotgov", "--zip", "12345", "--template", "hourly"];
+        let parsed = Args::try_parse_from(args).unwrap();
+        // The bundled hourly template only makes sense over hourly periods.
+        assert_eq!(
+            parsed.forecast.forecast_type(parsed.forecast_type),
+            ForecastType::Hourly
+        );
+
+        // Any other name is a path, read when the forecast is rendered.
+        let args = vec!["wxdotgov", "--zip", "12345", "--template", "./mine.j2"];
+        let parsed = Args::try_parse_from(args).unwrap();
+        assert_eq!(
+            parsed.forecast.forecast_type(parsed.forecast_type),
+            ForecastType::Detailed
+        );
+
+        let both = vec![
+            "wxdotgov",
+            "--zip",
+            "12345",
+            "--template",
+            "slack",
+            "--waybar",
+        ];
+        assert!(Args::try_parse_from(both).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_args_output_format() {
+        let args = vec!["wxdotgov", "--zip", "12345", "--output", "markdown"];
+        let parsed = Args::try_parse_from(args).unwrap();
+        assert_eq!(parsed.forecast.output, OutputFormat::Markdown);
+
+        let args = vec!["wxdotgov", "--zip", "12345"];
+        assert_eq!(
+            Args::try_parse_from(args).unwrap().forecast.output,
+            OutputFormat::Text
+        );
+
+        // A calendar is built from the named periods whatever the flag says.
+        let args = vec![
+            "wxdotgov",
+            "--zip",
+            "12345",
+            "--output",
+            "ics",
+            "--forecast-type",
+            "hourly",
+        ];
+        let parsed = Args::try_parse_from(args).unwrap();
+        assert_eq!(
+            parsed.forecast.forecast_type(parsed.forecast_type),
+            ForecastType::Detailed
+        );
+        assert!(!parsed.forecast.no_alerts);
+        let args = vec!["wxdotgov", "--zip", "1", "--output", "ics", "--no-alerts"];
+        assert!(Args::try_parse_from(args).unwrap().forecast.no_alerts);
+
+        // A chart has no document form.
+        let both = vec!["wxdotgov", "--zip", "12345", "--output", "html", "--chart"];
+        assert!(Args::try_parse_from(both).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_args_forecast_subcommands() {
+        use crate::Command;
+
+        let args = vec![
+            "wxdotgov", "hourly", "--zip", "12345", "-n", "6", "--pretty",
+        ];
+        let Some(Command::Hourly(forecast)) = Args::try_parse_from(args).unwrap().command else {
+            panic!("expected the hourly subcommand");
+        };
+        assert_eq!(forecast.location.zip.as_deref(), Some("12345"));
+        assert_eq!(forecast.limit, 6);
+        assert!(forecast.display.pretty);
+        assert_eq!(
+            forecast.forecast_type(ForecastType::Hourly),
+            ForecastType::Hourly
+        );
+
+        let args = vec!["wxdotgov", "forecast", "--city", "Seattle", "--chart"];
+        let Some(Command::Forecast(forecast)) = Args::try_parse_from(args).unwrap().command else {
+            panic!("expected the forecast subcommand");
+        };
+        assert_eq!(
+            forecast.forecast_type(ForecastType::Detailed),
+            ForecastType::Hourly
+        );
+
+        // Options follow the subcommand, and each still needs a location.
+        let before = vec!["wxdotgov", "--zip", "12345", "forecast"];
+        assert!(Args::try_parse_from(before).is_err());
+        assert!(Args::try_parse_from(vec!["wxdotgov", "forecast"]).is_err());
+        // --forecast-type belongs to the flat form; the subcommand names it.
+        let both = vec![
+            "wxdotgov",
+            "hourly",
+            "--zip",
+            "1",
+            "--forecast-type",
+            "hourly",
+        ];
+        assert!(Args::try_parse_from(both).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_args_report_subcommands() {
+        use crate::Command;
+
+        for name in [
+            "alerts",
+            "current",
+            "discussion",
+            "info",
+            "fire",
+            "history",
+            "stations",
+        ] {
+            let args = vec!["wxdotgov", name, "--city", "Seattle", "--state", "WA"];
+            let parsed = Args::try_parse_from(args).unwrap();
+            assert!(matches!(
+                parsed.command,
+                Some(
+                    Command::Alerts(_)
+                        | Command::Current(_)
+                        | Command::Discussion(_)
+                        | Command::Info(_)
+                        | Command::Fire(_)
+                        | Command::History(_)
+                        | Command::Stations(_)
+                )
+            ));
+            assert!(Args::try_parse_from(vec!["wxdotgov", name]).is_err());
+            let both = vec!["wxdotgov", name, "--zip", "1", "--city", "x"];
+            assert!(Args::try_parse_from(both).is_err());
+        }
+
+        let args = vec!["wxdotgov", "current", "--zip", "12345", "--units", "si"];
+        let Some(Command::Current(current)) = Args::try_parse_from(args).unwrap().command else {
+            panic!("expected the current subcommand");
+        };
+        assert_eq!(current.lookup.display.units, crate::units::Units::Si);
+
+        let args = vec!["wxdotgov", "fire", "--zip", "12345", "--days", "5"];
+        let Some(Command::Fire(fire)) = Args::try_parse_from(args).unwrap().command else {
+            panic!("expected the fire subcommand");
+        };
+        assert_eq!(fire.days, 5);
+        let args = vec!["wxdotgov", "fire", "--zip", "12345", "--days", "8"];
+        assert!(Args::try_parse_from(args).is_err());
+
+        let args = vec![
+            "wxdotgov",
+            "history",
+            "--zip",
+            "12345",
+            "--hours",
+            "72",
+            "--chart",
+            "--compare",
+        ];
+        let Some(Command::History(history)) = Args::try_parse_from(args).unwrap().command else {
+            panic!("expected the history subcommand");
+        };
+        assert_eq!(history.hours, 72);
+        assert!(history.chart && history.compare);
+        let args = vec!["wxdotgov", "history", "--zip", "12345", "--hours", "0"];
+        assert!(Args::try_parse_from(args).is_err());
+
+        let args = vec!["wxdotgov", "current", "--zip", "12345", "--station", "ksea"];
+        let Some(Command::Current(current)) = Args::try_parse_from(args).unwrap().command else {
+            panic!("expected the current subcommand");
+        };
+        assert_eq!(current.station.as_deref(), Some("KSEA"));
+        let args = vec!["wxdotgov", "history", "--zip", "12345", "--station", "KBFI"];
+        let Some(Command::History(history)) = Args::try_parse_from(args).unwrap().command else {
+            panic!("expected the history subcommand");
+        };
+        assert_eq!(history.station.as_deref(), Some("KBFI"));
+        let args = vec![
+            "wxdotgov",
+            "history",
+            "--zip",
+            "12345",
+            "--station",
+            "K-SEA",
+        ];
+        assert!(Args::try_parse_from(args).is_err());
+
+        let args = vec!["wxdotgov", "stations", "--zip", "12345", "--use", "ksea"];
+        let Some(Command::Stations(stations)) = Args::try_parse_from(args).unwrap().command else {
+            panic!("expected the stations subcommand");
+        };
+        assert_eq!(stations.use_station.as_deref(), Some("KSEA"));
+        assert_eq!(stations.limit, 10);
+        assert!(!stations.forget);
+        let both = vec![
+            "wxdotgov", "stations", "--zip", "12345", "--use", "KSEA", "--forget",
+        ];
+        assert!(Args::try_parse_from(both).is_err());
+
+        // Only the forecast views take the forecast options.
+        let chart = vec!["wxdotgov", "alerts", "--zip", "12345", "--chart"];
+        assert!(Args::try_parse_from(chart).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_args_locations_and_cache_subcommands() {
+        use crate::Command;
+
+        let args = vec!["wxdotgov", "locations", "--city", "Springfield", "-n", "10"];
+        let Some(Command::Locations(locations)) = Args::try_parse_from(args).unwrap().command
+        else {
+            panic!("expected the locations subcommand");
+        };
+        assert_eq!(locations.limit, 10);
+
+        // The cache is not about any one place.
+        assert!(Args::try_parse_from(vec!["wxdotgov", "cache", "--clear"]).is_ok());
+        assert!(Args::try_parse_from(vec!["wxdotgov", "cache", "--zip", "1"]).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_args_zone() {
+        use crate::Command;
+
+        let args = vec!["wxdotgov", "--zone", "waz558"];
+        let parsed = Args::try_parse_from(args).unwrap();
+        assert_eq!(parsed.forecast.location.zone.as_deref(), Some("WAZ558"));
+
+        let args = vec!["wxdotgov", "alerts", "--zone", "WAC033"];
+        let Some(Command::Alerts(alerts)) = Args::try_parse_from(args).unwrap().command else {
+            panic!("expected the alerts subcommand");
+        };
+        assert_eq!(alerts.lookup.location.zone.as_deref(), Some("WAC033"));
+        assert!(!alerts.marine);
+
+        let args = vec!["wxdotgov", "alerts", "--zone", "PZZ135", "--marine"];
+        let Some(Command::Alerts(alerts)) = Args::try_parse_from(args).unwrap().command else {
+            panic!("expected the alerts subcommand");
+        };
+        assert!(alerts.marine);
+
+        // A point may be west of Greenwich, and stands in for a place.
+        let args = vec!["wxdotgov", "--point", "47.7,-122.45"];
+        let parsed = Args::try_parse_from(args).unwrap();
+        assert_eq!(parsed.forecast.location.point.unwrap().lon, "-122.45");
+        let args = vec!["wxdotgov", "--point", "47.7,-122.45", "--zone", "PZZ135"];
+        assert!(Args::try_parse_from(args).is_err());
+
+        // One place at a time, and only IDs shaped like one.
+        assert!(Args::try_parse_from(vec!["wxdotgov", "--zone", "WAZ558", "--zip", "1"]).is_err());
+        assert!(Args::try_parse_from(vec!["wxdotgov", "--zone", "Seattle"]).is_err());
+
+        let args = vec![
+            "wxdotgov", "zones", "search", "--area", "wa", "--type", "fire",
+        ];
+        assert!(Args::try_parse_from(args).is_ok());
+        let args = vec![
+            "wxdotgov", "zones", "search", "--area", "pz", "--type", "coastal",
+        ];
+        assert!(Args::try_parse_from(args).is_ok());
+        assert!(Args::try_parse_from(vec!["wxdotgov", "zones", "search"]).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_location_labels_key_remembered_stations() {
+        use crate::Command;
+
+        let label = |args: Vec<&str>| {
+            let Some(Command::Stations(stations)) = Args::try_parse_from(args).unwrap().command
+            else {
+                panic!("expected the stations subcommand");
+            };
+            stations.lookup.location.label()
+        };
+        assert_eq!(
+            label(vec!["wxdotgov", "stations", "--zip", " 98101 "]),
+            "98101"
+        );
+        assert_eq!(
+            label(vec![
+                "wxdotgov", "stations", "--city", "Seattle", "--state", "wa"
+            ]),
+            "Seattle, WA"
+        );
+        assert_eq!(
+            label(vec!["wxdotgov", "stations", "--city", "Seattle"]),
+            "Seattle"
+        );
+        assert_eq!(
+            label(vec!["wxdotgov", "stations", "--point", "47.6,-122.3"]),
+            "47.6,-122.3"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_args_record_and_replay() {
+        use std::path::Path;
+
+        let args = vec!["wxdotgov", "--zip", "12345", "--record", "tape"];
+        let parsed = Args::try_parse_from(args).unwrap();
+        assert_eq!(parsed.record.as_deref(), Some(Path::new("tape")));
+
+        // Accepted after any subcommand, and seen at the top level.
+        let args = vec!["wxdotgov", "alerts", "--zip", "12345", "--replay", "tape"];
+        let parsed = Args::try_parse_from(args).unwrap();
+        assert_eq!(parsed.replay.as_deref(), Some(Path::new("tape")));
+        let args = vec!["wxdotgov", "serve", "--replay", "tape"];
+        assert!(Args::try_parse_from(args).is_ok());
+
+        let both = vec!["wxdotgov", "--zip", "1", "--record", "a", "--replay", "b"];
+        assert!(Args::try_parse_from(both).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_args_serve_subcommand() {
+        // No location is needed to serve; each request names its own.
+        let args = vec!["wxdotgov", "serve", "--listen", "0.0.0.0:9000"];
+        let parsed = Args::try_parse_from(args).unwrap();
+        assert!(parsed.command.is_some());
+
+        let mixed = vec!["wxdotgov", "--zip", "12345", "serve"];
+        assert!(Args::try_parse_from(mixed).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_args_exporter_subcommand() {
+        let args = vec![
+            "wxdotgov",
+            "exporter",
+            "--location",
+            "98101",
+            "--location",
+            "Portland, OR",
+            "--interval",
+            "5m",
+        ];
+        assert!(Args::try_parse_from(args).is_ok());
+
+        // At least one location, and no faster than once a minute.
+        let none = vec!["wxdotgov", "exporter"];
+        assert!(Args::try_parse_from(none).is_err());
+        let eager = vec![
+            "wxdotgov",
+            "exporter",
+            "--location",
+            "98101",
+            "--interval",
+            "10s",
+        ];
+        assert!(Args::try_parse_from(eager).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_args_notify_subcommand() {
+        let args = vec![
+            "wxdotgov",
+            "notify",
+            "--location",
+            "98101",
+            "--min-severity",
+            "severe",
+            "--event",
+            "Flood Warning",
+            "--webhook",
+            "http://localhost:9000/hook",
+            "--once",
+        ];
+        assert!(Args::try_parse_from(args).is_ok());
+
+        let unknown = vec![
+            "wxdotgov",
+            "notify",
+            "--location",
+            "98101",
+            "--min-severity",
+            "bad",
+        ];
+        assert!(Args::try_parse_from(unknown).is_err());
+        assert!(Args::try_parse_from(vec!["wxdotgov", "notify"]).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_args_tui_subcommand() {
+        let args = vec![
+            "wxdotgov",
+            "tui",
+            "--location",
+            "98101",
+            "--location",
+            "Portland, OR",
+            "--units",
+            "si",
+        ];
+        assert!(Args::try_parse_from(args).is_ok());
+        // Saved locations can stand in for --location.
+        assert!(Args::try_parse_from(vec!["wxdotgov", "tui"]).is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_args_watch() {
+        use std::time::Duration;
+
+        let bare = vec!["wxdotgov", "--zip", "12345", "--watch"];
+        let parsed = Args::try_parse_from(bare).unwrap();
+        assert_eq!(parsed.forecast.watch, Some(Duration::from_secs(600)));
+
+        let args = vec!["wxdotgov", "--watch", "5m", "--zip", "12345"];
+        let parsed = Args::try_parse_from(args).unwrap();
+        assert_eq!(parsed.forecast.watch, Some(Duration::from_secs(300)));
+
+        let defaults = Args::try_parse_from(vec!["wxdotgov", "--zip", "12345"]).unwrap();
+        assert_eq!(defaults.forecast.watch, None);
+
+        let eager = vec!["wxdotgov", "--zip", "12345", "--watch", "30s"];
+        assert!(Args::try_parse_from(eager).is_err());
+        let waybar = vec!["wxdotgov", "--zip", "12345", "--watch", "--waybar"];
+        assert!(Args::try_parse_from(waybar).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_args_stale_forecast_handling() {
+        use crate::weatherdotgov::OnStale;
+        use crate::Command;
+        use std::time::Duration;
+
+        let defaults = Args::try_parse_from(vec!["wxdotgov", "--zip", "12345"]).unwrap();
+        assert_eq!(
+            defaults.forecast.stale_after,
+            Duration::from_secs(12 * 60 * 60)
+        );
+        assert_eq!(defaults.forecast.on_stale, OnStale::Warn);
+
+        let args = vec![
+            "wxdotgov",
+            "hourly",
+            "--zip",
+            "12345",
+            "--stale-after",
+            "6h",
+            "--on-stale",
+            "fallback",
+        ];
+        let Some(Command::Hourly(forecast)) = Args::try_parse_from(args).unwrap().command else {
+            panic!("expected the hourly subcommand");
+        };
+        assert_eq!(forecast.stale_after, Duration::from_secs(6 * 60 * 60));
+        assert_eq!(forecast.on_stale, OnStale::Fallback);
+
+        // --watch keeps its own last good forecast.
+        let watch = vec![
+            "wxdotgov",
+            "--zip",
+            "12345",
+            "--watch",
+            "--on-stale",
+            "retry",
+        ];
+        assert!(Args::try_parse_from(watch).is_err());
     }
 
     #[tokio::test]
     async fn test_args_city_state() {
         let args = vec!["wxdotgov", "--city", "Seattle", "--state", "WA"];
         let parsed = Args::try_parse_from(args).unwrap();
-        assert!(parsed.zip.is_none());
-        assert_eq!(parsed.city.unwrap(), "Seattle");
-        assert_eq!(parsed.state.unwrap(), "WA");
+        assert!(parsed.forecast.location.zip.is_none());
+        assert_eq!(parsed.forecast.location.city.unwrap(), "Seattle");
+        assert_eq!(parsed.forecast.location.state.unwrap(), "WA");
     }
 
     #[tokio::test]
@@ -36,7 +580,7 @@ mod tests {
     async fn test_args_pretty_output() {
         let args = vec!["wxdotgov", "--zip", "12345", "--pretty"];
         let parsed = Args::try_parse_from(args).unwrap();
-        assert!(parsed.pretty);
+        assert!(parsed.forecast.display.pretty);
     }
 
     #[tokio::test]
@@ -63,16 +607,16 @@ mod tests {
     #[tokio::test]
     async fn test_args_limit() {
         let args = vec!["wxdotgov", "--zip", "12345", "--limit", "5"];
-        assert_eq!(Args::try_parse_from(args).unwrap().limit, 5);
+        assert_eq!(Args::try_parse_from(args).unwrap().forecast.limit, 5);
 
         let short = vec!["wxdotgov", "--zip", "12345", "-n", "5"];
-        assert_eq!(Args::try_parse_from(short).unwrap().limit, 5);
+        assert_eq!(Args::try_parse_from(short).unwrap().forecast.limit, 5);
     }
 
     #[tokio::test]
     async fn test_args_limit_zero_is_accepted_as_no_limit() {
         let args = vec!["wxdotgov", "--zip", "12345", "--limit", "0"];
-        assert_eq!(Args::try_parse_from(args).unwrap().limit, 0);
+        assert_eq!(Args::try_parse_from(args).unwrap().forecast.limit, 0);
     }
 
     #[tokio::test]
@@ -0,0 +1,127 @@
+#[cfg(test)]
+mod tests {
+    use crate::archive::{add, Archive};
+    use crate::dirs;
+    use crate::units::Units;
+    use crate::weatherdotgov::Period;
+    use chrono::{DateTime, Utc};
+
+    fn period(name: &str, start: &str, end: &str, temperature: Option<i32>) -> Period {
+        serde_json::from_value(serde_json::json!({
+            "name": name, "startTime": start, "endTime": end, "isDaytime": true,
+            "temperature": temperature, "detailedForecast": ""
+        }))
+        .unwrap()
+    }
+
+    fn now(s: &str) -> DateTime<Utc> {
+        s.parse().unwrap()
+    }
+
+    #[test]
+    fn a_period_keeps_the_first_forecast_recorded_for_it() {
+        let mut archive = Archive::new();
+        let monday = |temperature| {
+            period(
+                "Monday",
+                "2024-07-15T06:00:00-07:00",
+                "2024-07-15T18:00:00-07:00",
+                Some(temperature),
+            )
+        };
+        let tuesday = period(
+            "Tuesday",
+            "2024-07-16T06:00:00-07:00",
+            "2024-07-16T18:00:00-07:00",
+            Some(81),
+        );
+        add(
+            &mut archive,
+            &[monday(75)],
+            Units::Us,
+            now("2024-07-13T12:00:00Z"),
+        );
+        add(
+            &mut archive,
+            &[monday(80), tuesday],
+            Units::Us,
+            now("2024-07-14T12:00:00Z"),
+        );
+
+        let temperatures: Vec<f64> = archive.values().map(|r| r.fahrenheit).collect();
+        assert_eq!(temperatures, [75.0, 81.0]);
+    }
+
+    #[test]
+    fn forecasts_are_kept_in_fahrenheit() {
+        let mut archive = Archive::new();
+        let warm = period(
+            "Monday",
+            "2024-07-15T06:00:00-07:00",
+            "2024-07-15T18:00:00-07:00",
+            Some(25),
+        );
+        add(
+            &mut archive,
+            &[warm],
+            Units::Si,
+            now("2024-07-14T12:00:00Z"),
+        );
+        let recorded = archive.values().next().unwrap();
+        assert_eq!(recorded.fahrenheit, 77.0);
+        assert_eq!(recorded.temperature(Units::Si), 25.0);
+    }
+
+    #[test]
+    fn periods_without_a_temperature_or_long_ended_are_dropped() {
+        let mut archive = Archive::new();
+        let old = period(
+            "Monday",
+            "2024-07-15T06:00:00-07:00",
+            "2024-07-15T18:00:00-07:00",
+            Some(75),
+        );
+        let blank = period(
+            "Tuesday",
+            "2024-07-16T06:00:00-07:00",
+            "2024-07-16T18:00:00-07:00",
+            None,
+        );
+        add(
+            &mut archive,
+            &[old, blank],
+            Units::Us,
+            now("2024-07-15T12:00:00Z"),
+        );
+        assert_eq!(archive.len(), 1);
+
+        // Still on record a week after it ended; gone a day after that.
+        add(&mut archive, &[], Units::Us, now("2024-07-22T12:00:00Z"));
+        assert_eq!(archive.len(), 1);
+        add(&mut archive, &[], Units::Us, now("2024-07-24T12:00:00Z"));
+        assert!(archive.is_empty());
+    }
+
+    #[test]
+    fn the_record_survives_a_save_and_load() {
+        let dir = std::env::temp_dir().join(format!("wxdotgov-archive-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        let path = dir.join("forecasts").join("SEW-124-67.json");
+
+        let mut archive: Archive = dirs::load_state(&path).unwrap();
+        let monday = period(
+            "Monday",
+            "2024-07-15T06:00:00-07:00",
+            "2024-07-15T18:00:00-07:00",
+            Some(75),
+        );
+        add(
+            &mut archive,
+            &[monday],
+            Units::Us,
+            now("2024-07-14T12:00:00Z"),
+        );
+        dirs::save_state(&path, &archive).unwrap();
+        assert_eq!(dirs::load_state::<Archive>(&path).unwrap(), archive);
+    }
+}
@@ -0,0 +1,270 @@
+#[cfg(test)]
+mod tests {
+    use crate::cache::{self, Cache, Usage, Validators};
+    use crate::units::Units;
+    use crate::weatherdotgov::get_detailed_forecast;
+    use mockito::{Matcher, Server};
+    use std::path::PathBuf;
+    use std::sync::Arc;
+    use std::time::{Duration, SystemTime};
+
+    fn temp_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("wxdotgov-{name}-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        dir
+    }
+
+    #[test]
+    fn entries_are_fresh_until_the_callers_max_age() {
+        let cache = Cache::default();
+        cache.insert("k".to_string(), "body".to_string());
+
+        assert_eq!(
+            cache.get("k", Duration::from_secs(60)).as_deref(),
+            Some("body")
+        );
+        // The same entry is stale to a caller that wants it newer.
+        assert_eq!(cache.get("k", Duration::ZERO), None);
+        assert_eq!(cache.get("other", Duration::from_secs(60)), None);
+    }
+
+    #[test]
+    fn keys_include_the_query_in_order() {
+        assert_eq!(cache::key("https://x/a", &[]), "https://x/a");
+        assert_eq!(
+            cache::key("https://x/a", &[("units", "us"), ("limit", "1")]),
+            "https://x/a?units=us&limit=1"
+        );
+        assert_ne!(
+            cache::key("https://x/a", &[("units", "us")]),
+            cache::key("https://x/a", &[("units", "si")])
+        );
+    }
+
+    /// Outside a scope nothing is cached, so no caller is ever served a
+    /// response from some other caller's cache.
+    #[tokio::test]
+    async fn nothing_is_cached_outside_a_scope() {
+        cache::store("outside", "body");
+        assert_eq!(cache::lookup("outside", Duration::from_secs(60)), None);
+
+        let shared = Arc::new(Cache::default());
+        cache::scope(shared.clone(), async {
+            cache::store("inside", "body");
+            assert_eq!(
+                cache::lookup("inside", Duration::from_secs(60)).as_deref(),
+                Some("body")
+            );
+        })
+        .await;
+        assert_eq!(
+            shared.get("inside", Duration::from_secs(60)).as_deref(),
+            Some("body")
+        );
+        assert_eq!(cache::lookup("inside", Duration::from_secs(60)), None);
+    }
+
+    #[tokio::test]
+    async fn a_reload_finds_nothing_fresh_but_keeps_what_it_stores() {
+        let shared = Arc::new(Cache::default());
+        shared.insert("old".to_string(), "body".to_string());
+        cache::reload(shared.clone(), async {
+            assert!(cache::reloading());
+            assert_eq!(cache::lookup("old", Duration::from_secs(60)), None);
+            cache::store("new", "fetched");
+        })
+        .await;
+        cache::scope(shared.clone(), async {
+            assert!(!cache::reloading());
+            assert_eq!(
+                cache::lookup("new", Duration::from_secs(60)).as_deref(),
+                Some("fetched")
+            );
+        })
+        .await;
+    }
+
+    #[test]
+    fn disk_entries_outlive_the_cache_that_wrote_them() {
+        let dir = temp_dir("cache-disk");
+        Cache::on_disk(dir.clone()).insert("k".to_string(), "body".to_string());
+
+        // A later run, with nothing in memory yet.
+        let next = Cache::on_disk(dir.clone());
+        assert_eq!(
+            next.get("k", Duration::from_secs(60)).as_deref(),
+            Some("body")
+        );
+        assert_eq!(next.get("k", Duration::ZERO), None);
+        assert_eq!(next.get("other", Duration::from_secs(60)), None);
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    /// Date `path` back two days, past anything the cache keeps.
+    fn age(path: &std::path::Path) {
+        let two_days_ago = SystemTime::now() - Duration::from_secs(2 * 24 * 60 * 60);
+        let file = std::fs::File::options().write(true).open(path).unwrap();
+        file.set_modified(two_days_ago).unwrap();
+    }
+
+    #[test]
+    fn old_files_are_swept_at_most_hourly() {
+        let dir = temp_dir("cache-sweep");
+        let cache = Cache::on_disk(dir.clone());
+        cache.insert("a".to_string(), "one".to_string());
+        let leftover = dir.join("0123456789abcdef.json.99.0.tmp");
+        std::fs::write(&leftover, "half").unwrap();
+        age(&leftover);
+
+        // Swept within the hour: the leftover stays.
+        cache.insert("b".to_string(), "two".to_string());
+        assert!(leftover.exists());
+
+        // An hour on, the next insert sweeps it, and only it.
+        age(&dir.join("swept"));
+        cache.insert("c".to_string(), "three".to_string());
+        assert!(!leftover.exists());
+        assert_eq!(cache::usage(&dir).unwrap().entries, 3);
+        let names: Vec<String> = std::fs::read_dir(&dir)
+            .unwrap()
+            .map(|file| file.unwrap().file_name().to_string_lossy().into_owned())
+            .collect();
+        assert!(
+            names.iter().all(|name| !name.ends_with(".tmp")),
+            "{names:?}"
+        );
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn usage_and_clear_count_only_cache_entries() {
+        let dir = temp_dir("cache-clear");
+        assert_eq!(
+            cache::usage(&dir).unwrap(),
+            Usage {
+                entries: 0,
+                bytes: 0
+            }
+        );
+
+        let cache = Cache::on_disk(dir.clone());
+        cache.insert("a".to_string(), "one".to_string());
+        cache.insert("b".to_string(), "two".to_string());
+        std::fs::write(dir.join("notes.txt"), "not mine").unwrap();
+
+        let usage = cache::usage(&dir).unwrap();
+        assert_eq!(usage.entries, 2);
+        assert!(usage.bytes > 0);
+
+        assert_eq!(cache::clear(&dir).unwrap(), 2);
+        assert_eq!(cache::usage(&dir).unwrap().entries, 0);
+        assert!(dir.join("notes.txt").exists());
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    const FORECAST: &str = r#"{"properties":{"periods":[{"number":1,"name":"Tonight","startTime":"2024-01-28T18:00:00-08:00","endTime":"2024-01-29T06:00:00-08:00","isDaytime":false,"temperature":51,"temperatureUnit":"F","windSpeed":"5 mph","windDirection":"SSW","shortForecast":"Chance Light Rain","detailedForecast":"A chance of rain."}]}}"#;
+
+    #[tokio::test]
+    async fn validators_are_kept_with_the_body() {
+        let mut server = Server::new_async().await;
+        server
+            .mock("GET", "/gridpoints/SEW/115,68/forecast")
+            .match_query(Matcher::Any)
+            .with_status(200)
+            .with_header("etag", "\"v1\"")
+            .with_header("last-modified", "Sun, 28 Jan 2024 18:00:00 GMT")
+            .with_body(FORECAST)
+            .create();
+        let url = format!("{}/gridpoints/SEW/115,68/forecast", server.url());
+
+        let shared = Arc::new(Cache::default());
+        cache::scope(shared.clone(), get_detailed_forecast(&url, Units::Us))
+            .await
+            .unwrap();
+
+        let (body, validators) = shared
+            .get_stale(&cache::key(&url, &[("units", "us")]))
+            .unwrap();
+        assert_eq!(body, FORECAST);
+        assert_eq!(
+            validators,
+            Validators {
+                etag: Some("\"v1\"".to_string()),
+                last_modified: Some("Sun, 28 Jan 2024 18:00:00 GMT".to_string()),
+            }
+        );
+    }
+
+    /// A stale entry is revalidated rather than refetched, and a 304 serves
+    /// and renews it.
+    #[tokio::test]
+    async fn a_stale_entry_is_revalidated_and_renewed_by_304() {
+        let dir = temp_dir("cache-revalidate");
+        let mut server = Server::new_async().await;
+        let mock = server
+            .mock("GET", "/gridpoints/SEW/115,68/forecast")
+            .match_query(Matcher::Any)
+            .match_header("if-none-match", "\"v1\"")
+            .match_header("if-modified-since", "Sun, 28 Jan 2024 18:00:00 GMT")
+            .with_status(304)
+            .expect(1)
+            .create();
+        let url = format!("{}/gridpoints/SEW/115,68/forecast", server.url());
+        let key = cache::key(&url, &[("units", "us")]);
+
+        // An entry from a run long ago, past any freshness.
+        std::fs::create_dir_all(&dir).unwrap();
+        let entry = serde_json::json!({
+            "key": key,
+            "body": FORECAST,
+            "stored": 0,
+            "validators": {
+                "etag": "\"v1\"",
+                "last_modified": "Sun, 28 Jan 2024 18:00:00 GMT",
+            },
+        });
+        std::fs::write(
+            dir.join(format!("{:016x}.json", cache::stable_hash(&key))),
+            entry.to_string(),
+        )
+        .unwrap();
+
+        let shared = Arc::new(Cache::on_disk(dir.clone()));
+        let forecast = cache::scope(shared.clone(), get_detailed_forecast(&url, Units::Us))
+            .await
+            .unwrap();
+        assert_eq!(forecast.properties.periods[0].name, "Tonight");
+        mock.assert();
+
+        // Fresh again, for this run and the next.
+        assert!(shared.get(&key, Duration::from_secs(60)).is_some());
+        let next = Cache::on_disk(dir.clone());
+        let (_, validators) = next.get_stale(&key).unwrap();
+        assert_eq!(validators.etag.as_deref(), Some("\"v1\""));
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    /// Entries written before validators were kept still read, and are
+    /// refetched without conditions.
+    #[test]
+    fn entries_without_validators_still_read() {
+        let dir = temp_dir("cache-old-entry");
+        std::fs::create_dir_all(&dir).unwrap();
+        let entry = serde_json::json!({"key": "k", "body": "body", "stored": 0});
+        std::fs::write(
+            dir.join(format!("{:016x}.json", cache::stable_hash("k"))),
+            entry.to_string(),
+        )
+        .unwrap();
+
+        let (body, validators) = Cache::on_disk(dir.clone()).get_stale("k").unwrap();
+        assert_eq!(body, "body");
+        assert_eq!(validators, Validators::default());
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,91 @@
+#[cfg(test)]
+mod tests {
+    use crate::completions::{run, saved, write_man, Saved};
+    use crate::stations::Choices;
+    use crate::{Args, Command};
+    use clap::{CommandFactory, Parser};
+
+    fn completions(shell: &str) -> String {
+        let args = Args::try_parse_from(["wxdotgov", "completions", shell]).unwrap();
+        let Some(Command::Completions(completions)) = args.command else {
+            panic!("expected the completions subcommand");
+        };
+        let mut out = Vec::new();
+        run(&completions, &mut Args::command(), &mut out).unwrap();
+        String::from_utf8(out).unwrap()
+    }
+
+    #[test]
+    fn completions_cover_flags_subcommands_and_choices() {
+        let bash = completions("bash");
+        for word in ["--forecast-type", "--units", "serve", "notify", "hourly"] {
+            assert!(bash.contains(word), "bash completion lacks {word}");
+        }
+        for shell in ["zsh", "fish", "powershell", "elvish"] {
+            assert!(completions(shell).contains("forecast-type"), "{shell}");
+        }
+        assert!(Args::try_parse_from(["wxdotgov", "completions", "tcsh"]).is_err());
+    }
+
+    #[test]
+    fn saved_value_options_complete_through_the_binary() {
+        let bash = completions("bash");
+        assert!(bash.contains("--station|--use) kind=station"), "{bash}");
+        assert!(bash.contains("complete -F _wxdotgov_saved"), "{bash}");
+
+        let zsh = completions("zsh");
+        for spec in [
+            ":ZIP:_wxdotgov_saved zip'",
+            ":CITY:_wxdotgov_saved city'",
+            ":LAT,LON:_wxdotgov_saved point'",
+            ":STATION:_wxdotgov_saved station'",
+        ] {
+            assert!(zsh.contains(spec), "zsh completion lacks {spec}");
+        }
+        let defined = zsh.find("_wxdotgov_saved() {").unwrap();
+        let called = zsh.rfind("_wxdotgov \"$@\"").unwrap();
+        assert!(
+            defined < called,
+            "the completer is defined after the script runs"
+        );
+
+        let fish = completions("fish");
+        assert!(
+            fish.contains("-l use -x -a '(wxdotgov completions --saved station 2>/dev/null)'"),
+            "{fish}"
+        );
+        assert!(!completions("elvish").contains("completions --saved"));
+    }
+
+    #[test]
+    fn saved_values_are_split_by_the_option_they_complete() {
+        let mut choices = Choices::new();
+        for (label, station) in [
+            ("98101", "KBFI"),
+            ("Seattle, WA", "KSEA"),
+            ("Tacoma", "KTIW"),
+            ("47.6,-122.3", "KSEA"),
+        ] {
+            choices.insert(label.to_string(), station.to_string());
+        }
+        assert_eq!(saved(&choices, Saved::Zip), ["98101"]);
+        assert_eq!(saved(&choices, Saved::City), ["Seattle", "Tacoma"]);
+        assert_eq!(saved(&choices, Saved::Point), ["47.6,-122.3"]);
+        assert_eq!(saved(&choices, Saved::Station), ["KBFI", "KSEA", "KTIW"]);
+        assert!(Args::try_parse_from(["wxdotgov", "completions"]).is_err());
+        assert!(
+            Args::try_parse_from(["wxdotgov", "completions", "bash", "--saved", "zip"]).is_err()
+        );
+    }
+
+    #[test]
+    fn man_page_is_roff_for_the_real_flags() {
+        let mut out = Vec::new();
+        write_man(Args::command(), &mut out).unwrap();
+        let man = String::from_utf8(out).unwrap();
+        assert!(man.starts_with(".ie"), "{}", &man[..40]);
+        assert!(man.contains(".TH wxdotgov 1"), "{man}");
+        assert!(man.contains("\\-\\-zip"), "{man}");
+        assert!(man.contains("exporter"), "{man}");
+    }
+}
@@ -0,0 +1,135 @@
+#[cfg(test)]
+mod tests {
+    use crate::exporter::{Exporter, Target};
+    use crate::metrics::Exposition;
+    use crate::LocationInput;
+    use mockito::{Matcher, ServerGuard};
+
+    #[test]
+    fn locations_parse_as_zip_city_or_city_and_state() {
+        let target: Target = "98101".parse().unwrap();
+        assert_eq!(target.input, LocationInput::PostalCode("98101".to_string()));
+
+        let target: Target = "Portland, OR".parse().unwrap();
+        assert_eq!(target.label, "Portland, OR");
+        assert_eq!(
+            target.input,
+            LocationInput::CityWithState("Portland".to_string(), "OR".to_string())
+        );
+
+        let target: Target = "Seattle".parse().unwrap();
+        assert_eq!(target.input, LocationInput::City("Seattle".to_string()));
+
+        assert!("981".parse::<Target>().is_err());
+    }
+
+    #[test]
+    fn label_values_are_escaped() {
+        let mut out = Exposition::default();
+        out.family("m", "gauge", "Help text.");
+        out.sample("m", &[("location", "a \"b\" \\ c")], 1.5);
+        assert_eq!(
+            out.finish(),
+            "# HELP m Help text.\n# TYPE m gauge\nm{location=\"a \\\"b\\\" \\\\ c\"} 1.5\n"
+        );
+    }
+
+    /// Geocoding answers for `q` starting with "Seattle"; anything else finds
+    /// no place, which is how the failing-location test gets its failure.
+    fn mock_upstream(upstream: &mut ServerGuard) {
+        let url = upstream.url();
+        upstream
+            .mock("GET", "/search")
+            .match_query(Matcher::Regex("q=Seattle".to_string()))
+            .with_body(r#"[{"lat": "47.6", "lon": "-122.3", "display_name": "Seattle"}]"#)
+            .create();
+        upstream
+            .mock("GET", "/search")
+            .match_query(Matcher::Regex("q=Nowhere".to_string()))
+            .with_body("[]")
+            .create();
+        upstream
+            .mock("GET", "/points/47.6,-122.3")
+            .with_body(format!(
+                r#"{{"properties": {{
+                    "gridId": "SEW", "gridX": 115, "gridY": 68,
+                    "forecast": "{url}/gridpoints/SEW/115,68/forecast",
+                    "forecastHourly": "{url}/gridpoints/SEW/115,68/forecast/hourly"
+                }}}}"#
+            ))
+            .create();
+        upstream
+            .mock("GET", "/gridpoints/SEW/115,68/forecast/hourly")
+            .match_query(Matcher::UrlEncoded("units".to_string(), "si".to_string()))
+            .with_body(
+                r#"{"properties": {"periods": [{
+                    "startTime": "2024-01-28T18:00:00-08:00",
+                    "endTime": "2024-01-28T19:00:00-08:00",
+                    "isDaytime": false,
+                    "temperature": 8,
+                    "temperatureUnit": "C",
+                    "windSpeed": "8 km/h",
+                    "windDirection": "SSW",
+                    "shortForecast": "Light Rain",
+                    "dewpoint": {"unitCode": "wmoUnit:degC", "value": 5.0},
+                    "probabilityOfPrecipitation": {"unitCode": "wmoUnit:percent", "value": 40}
+                }]}}"#,
+            )
+            .create();
+        upstream
+            .mock("GET", "/alerts/active")
+            .match_query(Matcher::Any)
+            .with_body(
+                r#"{"features": [{"properties": {
+                    "event": "Wind Advisory", "headline": null, "severity": "Moderate"
+                }}]}"#,
+            )
+            .create();
+    }
+
+    fn exporter(upstream: &ServerGuard, locations: &[&str]) -> Exporter {
+        let targets = locations.iter().map(|l| l.parse().unwrap()).collect();
+        let mut exporter = Exporter::new(targets);
+        exporter.nominatim_url = Some(upstream.url());
+        exporter.weather_url = Some(upstream.url());
+        exporter
+    }
+
+    #[tokio::test]
+    async fn metrics_report_the_current_hour_in_si() {
+        let mut upstream = mockito::Server::new_async().await;
+        mock_upstream(&mut upstream);
+        let exporter = exporter(&upstream, &["Seattle, WA"]);
+        exporter.refresh().await;
+        let metrics = exporter.metrics();
+
+        for line in [
+            "wxdotgov_temperature_celsius{location=\"Seattle, WA\"} 8\n",
+            "wxdotgov_dewpoint_celsius{location=\"Seattle, WA\"} 5\n",
+            "wxdotgov_precip_probability{location=\"Seattle, WA\"} 0.4\n",
+            "wxdotgov_active_alerts{location=\"Seattle, WA\",severity=\"moderate\"} 1\n",
+            // Zero severities are still exported, so the series never vanish.
+            "wxdotgov_active_alerts{location=\"Seattle, WA\",severity=\"extreme\"} 0\n",
+            "wxdotgov_refresh_errors_total{location=\"Seattle, WA\"} 0\n",
+            "# TYPE wxdotgov_upstream_requests_total counter\n",
+        ] {
+            assert!(metrics.contains(line), "missing {line:?} in:\n{metrics}");
+        }
+        assert!(
+            metrics.contains("wxdotgov_last_refresh_timestamp_seconds{location=\"Seattle, WA\"} ")
+        );
+    }
+
+    #[tokio::test]
+    async fn a_failing_location_is_counted_without_stopping_the_others() {
+        let mut upstream = mockito::Server::new_async().await;
+        mock_upstream(&mut upstream);
+        let exporter = exporter(&upstream, &["Nowhere", "Seattle"]);
+        exporter.refresh().await;
+        let metrics = exporter.metrics();
+
+        assert!(metrics.contains("wxdotgov_refresh_errors_total{location=\"Nowhere\"} 1\n"));
+        assert!(!metrics.contains("wxdotgov_temperature_celsius{location=\"Nowhere\"}"));
+        assert!(metrics.contains("wxdotgov_temperature_celsius{location=\"Seattle\"} 8\n"));
+    }
+}
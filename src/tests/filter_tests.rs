@@ -0,0 +1,121 @@
+#[cfg(test)]
+mod tests {
+    use crate::filter::{DayPart, PeriodFilter};
+    use crate::times::{DisplayZone, TimeDisplay, TimeFormat, TimeSpec};
+    use crate::weatherdotgov::HourlyPeriod;
+    use chrono::{DateTime, FixedOffset, TimeDelta, Utc};
+
+    fn at(rfc3339: &str) -> DateTime<FixedOffset> {
+        DateTime::parse_from_rfc3339(rfc3339).unwrap()
+    }
+
+    /// 48 hourly periods from midnight Sunday, Seattle time.
+    fn two_days() -> Vec<HourlyPeriod> {
+        let start = at("2024-01-28T00:00:00-08:00");
+        (0..48)
+            .map(|i| HourlyPeriod {
+                start_time: start + TimeDelta::hours(i),
+                end_time: start + TimeDelta::hours(i + 1),
+                is_daytime: (6..18).contains(&(i % 24)),
+                temperature: 50,
+                temperature_unit: "F".to_string(),
+                wind_speed: "5 mph".to_string(),
+                wind_direction: "SSW".to_string(),
+                short_forecast: "Cloudy".to_string(),
+                dewpoint: None,
+                probability_of_precipitation: None,
+                icon: None,
+            })
+            .collect()
+    }
+
+    /// "Now" is 9:30 AM Sunday at the location.
+    fn times() -> TimeDisplay {
+        TimeDisplay {
+            format: TimeFormat::Iso,
+            zone: DisplayZone::Location,
+            now: at("2024-01-28T09:30:00-08:00").with_timezone(&Utc),
+        }
+    }
+
+    fn starts(periods: &[HourlyPeriod]) -> Vec<String> {
+        periods.iter().map(|p| p.start_time.to_rfc3339()).collect()
+    }
+
+    #[test]
+    fn the_default_filter_keeps_everything() {
+        let mut periods = two_days();
+        assert_eq!(
+            PeriodFilter::default()
+                .apply(&mut periods, &times())
+                .unwrap(),
+            0
+        );
+        assert_eq!(periods.len(), 48);
+    }
+
+    #[test]
+    fn from_now_keeps_the_period_in_progress() {
+        let mut periods = two_days();
+        let filter = PeriodFilter {
+            from: Some(TimeSpec::Now),
+            ..PeriodFilter::default()
+        };
+        assert_eq!(filter.apply(&mut periods, &times()).unwrap(), 9);
+        assert_eq!(starts(&periods)[0], "2024-01-28T09:00:00-08:00");
+    }
+
+    #[test]
+    fn relative_and_named_bounds_resolve_in_the_location_offset() {
+        let mut periods = two_days();
+        let filter = PeriodFilter {
+            from: Some(TimeSpec::Tonight),
+            until: Some(TimeSpec::Tomorrow),
+            ..PeriodFilter::default()
+        };
+        filter.apply(&mut periods, &times()).unwrap();
+        assert_eq!(periods.len(), 6);
+        assert_eq!(starts(&periods)[0], "2024-01-28T18:00:00-08:00");
+        assert_eq!(starts(&periods)[5], "2024-01-28T23:00:00-08:00");
+
+        let mut periods = two_days();
+        let filter = PeriodFilter {
+            until: Some("+6h".parse().unwrap()),
+            ..PeriodFilter::default()
+        };
+        filter.apply(&mut periods, &times()).unwrap();
+        // 00:00 through the 15:00 period, which starts before 15:30.
+        assert_eq!(periods.len(), 16);
+    }
+
+    #[test]
+    fn day_part_uses_the_api_daytime_flag() {
+        let mut day = two_days();
+        let filter = PeriodFilter {
+            part: Some(DayPart::Day),
+            ..PeriodFilter::default()
+        };
+        assert_eq!(filter.apply(&mut day, &times()).unwrap(), 24);
+        assert!(day.iter().all(|p| p.is_daytime));
+
+        let mut night = two_days();
+        let filter = PeriodFilter {
+            part: Some(DayPart::Night),
+            ..PeriodFilter::default()
+        };
+        filter.apply(&mut night, &times()).unwrap();
+        assert!(night.iter().all(|p| !p.is_daytime));
+        assert_eq!(night.len(), 24);
+    }
+
+    #[test]
+    fn a_window_that_misses_every_period_leaves_none() {
+        let mut periods = two_days();
+        let filter = PeriodFilter {
+            from: Some("2025-01-01".parse().unwrap()),
+            ..PeriodFilter::default()
+        };
+        assert_eq!(filter.apply(&mut periods, &times()).unwrap(), 48);
+        assert!(periods.is_empty());
+    }
+}
@@ -0,0 +1,257 @@
+#[cfg(test)]
+mod tests {
+    use crate::ics::render_ics;
+    use crate::render::{RenderOptions, Style};
+    use crate::times::{DisplayZone, TimeDisplay, TimeFormat};
+    use crate::units::Units;
+    use crate::weatherdotgov::{Alert, Period, PointsProperties, Severity};
+    use chrono::{DateTime, FixedOffset, TimeDelta, Utc};
+
+    fn at(s: &str) -> DateTime<FixedOffset> {
+        DateTime::parse_from_rfc3339(s).unwrap()
+    }
+
+    /// Periods starting at 6 PM Seattle time, 02:00 UTC the next day.
+    fn periods(count: usize) -> Vec<Period> {
+        let start = at("2024-01-28T18:00:00-08:00");
+        (0..count)
+            .map(|i| Period {
+                name: format!("Period{i}"),
+                start_time: start + TimeDelta::hours(12 * i as i64),
+                end_time: start + TimeDelta::hours(12 * (i as i64 + 1)),
+                is_daytime: i % 2 == 1,
+                temperature: None,
+                short_forecast: None,
+                detailed_forecast: format!("Forecast text {i}"),
+                icon: None,
+            })
+            .collect()
+    }
+
+    fn point() -> PointsProperties {
+        PointsProperties {
+            grid_id: "SEW".to_string(),
+            grid_x: 115,
+            grid_y: 68,
+            forecast: "https://api.weather.gov/gridpoints/SEW/115,68/forecast".to_string(),
+            forecast_hourly: None,
+            ..Default::default()
+        }
+    }
+
+    fn opts(now: &str, limit: Option<usize>) -> RenderOptions {
+        RenderOptions {
+            style: Style::Plain,
+            limit,
+            units: Units::Us,
+            times: TimeDisplay {
+                format: TimeFormat::TwelveHour,
+                zone: DisplayZone::Location,
+                now: at(now).with_timezone(&Utc),
+            },
+        }
+    }
+
+    fn advisory() -> Alert {
+        Alert {
+            id: "urn:oid:2.49.0.1.840.0.abc".to_string(),
+            event: "Wind Advisory".to_string(),
+            headline: Some("Wind Advisory until 6 AM Tuesday".to_string()),
+            severity: Severity::Moderate,
+            onset: Some(at("2024-01-28T18:00:00-08:00")),
+            ends: Some(at("2024-01-30T06:00:00-08:00")),
+            message_type: None,
+            references: Vec::new(),
+        }
+    }
+
+    /// Content lines with folding undone, for asserting on whole properties.
+    fn unfolded(out: &str) -> Vec<String> {
+        out.replace("\r\n ", "")
+            .split("\r\n")
+            .filter(|line| !line.is_empty())
+            .map(str::to_string)
+            .collect()
+    }
+
+    #[test]
+    fn each_period_is_an_event_in_utc() {
+        let out = render_ics(
+            &periods(2),
+            &[],
+            &point(),
+            "Seattle",
+            &opts("2024-01-28T17:00:00-08:00", None),
+        );
+        let lines = unfolded(&out);
+
+        assert_eq!(lines.first().map(String::as_str), Some("BEGIN:VCALENDAR"));
+        assert_eq!(lines.last().map(String::as_str), Some("END:VCALENDAR"));
+        assert_eq!(out.matches("BEGIN:VEVENT").count(), 2);
+        for line in [
+            "UID:20240129T020000Z-SEW-115-68@wxdotgov",
+            "DTSTART:20240129T020000Z",
+            "DTEND:20240129T140000Z",
+            "SUMMARY:Period0",
+            "DESCRIPTION:Forecast text 0",
+            "DTSTAMP:20240129T010000Z",
+        ] {
+            assert!(lines.iter().any(|l| l == line), "missing {line} in {out}");
+        }
+    }
+
+    /// The point of the UID scheme: the same period fetched on another run,
+    /// with a reworded forecast, must be recognisably the same event.
+    #[test]
+    fn uids_survive_a_forecast_update() {
+        let uids = |out: &str| -> Vec<String> {
+            unfolded(out)
+                .into_iter()
+                .filter(|l| l.starts_with("UID:"))
+                .collect()
+        };
+        let first = render_ics(
+            &periods(3),
+            &[],
+            &point(),
+            "Seattle",
+            &opts("2024-01-28T17:00:00-08:00", None),
+        );
+        let mut updated = periods(3);
+        updated[0].detailed_forecast = "Rain, heavy at times.".to_string();
+        let second = render_ics(
+            &updated,
+            &[],
+            &point(),
+            "Seattle",
+            &opts("2024-01-28T20:00:00-08:00", None),
+        );
+
+        assert_eq!(uids(&first), uids(&second));
+        assert_eq!(uids(&first).len(), 3);
+    }
+
+    #[test]
+    fn text_is_escaped_and_long_lines_are_folded() {
+        let mut periods = periods(1);
+        periods[0].detailed_forecast = format!(
+            "Rain; heavy at times, then showers.{}",
+            " Breezy.".repeat(20)
+        );
+        let out = render_ics(
+            &periods,
+            &[],
+            &point(),
+            "Seattle",
+            &opts("2024-01-28T17:00:00-08:00", None),
+        );
+
+        for line in out.split("\r\n") {
+            assert!(line.len() <= 75, "unfolded line: {line}");
+        }
+        let description = unfolded(&out)
+            .into_iter()
+            .find(|l| l.starts_with("DESCRIPTION:"))
+            .unwrap();
+        assert!(
+            description.starts_with(r"DESCRIPTION:Rain\; heavy at times\, then showers."),
+            "{description}"
+        );
+        assert!(description.ends_with(" Breezy."), "{description}");
+    }
+
+    #[test]
+    fn alerts_are_all_day_events_through_their_last_day() {
+        let out = render_ics(
+            &periods(1),
+            &[advisory()],
+            &point(),
+            "Seattle",
+            &opts("2024-01-28T17:00:00-08:00", None),
+        );
+        let lines = unfolded(&out);
+
+        assert_eq!(out.matches("BEGIN:VEVENT").count(), 2);
+        for line in [
+            "UID:urn:oid:2.49.0.1.840.0.abc@wxdotgov",
+            "DTSTART;VALUE=DATE:20240128",
+            // Exclusive: the alert ends on the 30th, so the event ends the 31st.
+            "DTEND;VALUE=DATE:20240131",
+            "SUMMARY:⚠ Wind Advisory",
+            "DESCRIPTION:Wind Advisory until 6 AM Tuesday",
+        ] {
+            assert!(lines.iter().any(|l| l == line), "missing {line} in {out}");
+        }
+    }
+
+    #[test]
+    fn an_updated_alert_keeps_the_original_alerts_uid() {
+        let update: Alert = serde_json::from_value(serde_json::json!({
+            "id": "urn:oid:2.49.0.1.840.0.def",
+            "event": "Wind Advisory",
+            "severity": "Moderate",
+            "messageType": "Update",
+            "references": [
+                {"@id": "https://api.weather.gov/alerts/urn:oid:2.49.0.1.840.0.bcd",
+                 "identifier": "urn:oid:2.49.0.1.840.0.bcd",
+                 "sender": "w-nws.webmaster@noaa.gov",
+                 "sent": "2024-01-28T12:00:00-08:00"},
+                {"@id": "https://api.weather.gov/alerts/urn:oid:2.49.0.1.840.0.abc",
+                 "identifier": "urn:oid:2.49.0.1.840.0.abc",
+                 "sender": "w-nws.webmaster@noaa.gov",
+                 "sent": "2024-01-28T09:00:00-08:00"}
+            ]
+        }))
+        .unwrap();
+        assert_eq!(update.original_id(), advisory().original_id());
+
+        let out = render_ics(
+            &periods(1),
+            &[update],
+            &point(),
+            "Seattle",
+            &opts("2024-01-28T17:00:00-08:00", None),
+        );
+        assert!(
+            unfolded(&out).contains(&"UID:urn:oid:2.49.0.1.840.0.abc@wxdotgov".to_string()),
+            "{out}"
+        );
+    }
+
+    #[test]
+    fn an_alert_without_times_lands_on_today() {
+        let alert = Alert {
+            id: String::new(),
+            onset: None,
+            ends: None,
+            ..advisory()
+        };
+        let out = render_ics(
+            &periods(1),
+            &[alert],
+            &point(),
+            "Seattle",
+            &opts("2024-01-28T17:00:00-08:00", None),
+        );
+        let lines = unfolded(&out);
+        for line in [
+            "UID:Wind-Advisory-20240128-SEW-115-68@wxdotgov",
+            "DTSTART;VALUE=DATE:20240128",
+            "DTEND;VALUE=DATE:20240129",
+        ] {
+            assert!(lines.iter().any(|l| l == line), "missing {line} in {out}");
+        }
+    }
+
+    #[test]
+    fn limit_caps_the_period_events() {
+        let out = render_ics(
+            &periods(5),
+            &[],
+            &point(),
+            "Seattle",
+            &opts("2024-01-28T17:00:00-08:00", Some(2)),
+        );
+        assert_eq!(out.matches("BEGIN:VEVENT").count(), 2);
+    }
+}
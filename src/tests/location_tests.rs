@@ -1,6 +1,7 @@
 #[cfg(test)]
 mod tests {
-    use crate::{build_location_input, parse_zip, LocationInput};
+    use crate::weatherdotgov::{parse_zone, Zone, ZoneType};
+    use crate::{build_location_input, parse_point, parse_zip, Coordinates, LocationInput};
 
     #[test]
     fn parses_five_digit_zip() {
@@ -84,4 +85,45 @@ mod tests {
         let err = build_location_input(Some("nope".to_string()), None, None).unwrap_err();
         assert!(format!("{err}").contains("not a valid US ZIP code"));
     }
+
+    #[test]
+    fn zone_ids_are_normalised_and_checked() {
+        assert_eq!(parse_zone(" waz558 ").unwrap(), "WAZ558");
+        assert_eq!(parse_zone("WAC033").unwrap(), "WAC033");
+        assert_eq!(parse_zone("PZZ135").unwrap(), "PZZ135");
+        for bad in ["WA558", "WAZ55", "WAX558", "W1Z558", "Seattle"] {
+            let err = parse_zone(bad).expect_err(bad);
+            assert!(format!("{err}").contains("zones search"), "{err}");
+        }
+    }
+
+    #[test]
+    fn zone_types_come_from_the_api() {
+        let zone = |kind: &str| Zone {
+            id: "PZZ135".to_string(),
+            name: "Puget Sound and Hood Canal".to_string(),
+            kind: kind.to_string(),
+        };
+        assert_eq!(zone("public").zone_type(), Some(ZoneType::Forecast));
+        assert_eq!(zone("coastal").zone_type(), Some(ZoneType::Coastal));
+        assert_eq!(zone("offshore").zone_type(), Some(ZoneType::Offshore));
+        assert_eq!(zone("fire").zone_type(), Some(ZoneType::Fire));
+        assert_eq!(zone("observations").zone_type(), None);
+        assert!(ZoneType::Coastal.is_marine() && ZoneType::Coastal.has_forecast());
+        assert!(!ZoneType::County.is_marine() && !ZoneType::County.has_forecast());
+    }
+
+    #[test]
+    fn points_are_checked_and_kept_as_given() {
+        assert_eq!(
+            parse_point("47.7, -122.45").unwrap(),
+            Coordinates {
+                lat: "47.7".to_string(),
+                lon: "-122.45".to_string(),
+            }
+        );
+        for bad in ["47.7", "north,west", "91,0", "0,181", ""] {
+            assert!(parse_point(bad).is_err(), "{bad}");
+        }
+    }
 }
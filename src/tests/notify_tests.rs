@@ -0,0 +1,234 @@
+#[cfg(test)]
+mod tests {
+    use crate::notify::{changes, command_env, AlertFilter, Notification, Notifier, Sink, Status};
+    use crate::weatherdotgov::{Alert, AlertReference, Severity};
+    use mockito::{Matcher, ServerGuard};
+    use std::path::PathBuf;
+
+    fn alert(id: &str, event: &str, severity: Severity) -> Alert {
+        Alert {
+            id: id.to_string(),
+            event: event.to_string(),
+            headline: Some(format!("{event} until 6 PM")),
+            severity,
+            onset: None,
+            ends: None,
+            message_type: Some("Alert".to_string()),
+            references: Vec::new(),
+        }
+    }
+
+    /// A state file path no other test uses, with nothing in it yet.
+    fn state_file(name: &str) -> PathBuf {
+        let dir =
+            std::env::temp_dir().join(format!("wxdotgov-notify-{}-{name}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        dir.join("state.json")
+    }
+
+    #[test]
+    fn new_alerts_and_ended_alerts_are_changes() {
+        let wind = alert("urn:a", "Wind Advisory", Severity::Moderate);
+        let flood = alert("urn:b", "Flood Watch", Severity::Severe);
+        let mut extended = alert("urn:c", "Wind Advisory", Severity::Moderate);
+        extended.message_type = Some("Update".to_string());
+
+        let seen = [wind.clone(), flood];
+        let active = [wind, extended];
+        let statuses: Vec<(Status, &str)> = changes(&seen, &active)
+            .into_iter()
+            .map(|(status, alert)| (status, alert.id.as_str()))
+            .collect();
+        assert_eq!(
+            statuses,
+            vec![(Status::Update, "urn:c"), (Status::Ended, "urn:b")]
+        );
+        assert!(changes(&seen, &seen).is_empty());
+    }
+
+    /// An update replaces the alert it references on the active list. That
+    /// is one update, not an update and the original ending.
+    #[test]
+    fn an_update_does_not_end_the_alert_it_replaces() {
+        let wind = alert("urn:a", "Wind Advisory", Severity::Moderate);
+        let mut extended = alert("urn:c", "Wind Advisory", Severity::Moderate);
+        extended.message_type = Some("Update".to_string());
+        extended.references = vec![AlertReference {
+            identifier: "urn:a".to_string(),
+            sent: None,
+        }];
+
+        let seen = [wind];
+        let active = [extended];
+        let statuses: Vec<(Status, &str)> = changes(&seen, &active)
+            .into_iter()
+            .map(|(status, alert)| (status, alert.id.as_str()))
+            .collect();
+        assert_eq!(statuses, vec![(Status::Update, "urn:c")]);
+
+        // When the update itself drops off, the alert has ended.
+        let statuses: Vec<(Status, &str)> = changes(&active, &[])
+            .into_iter()
+            .map(|(status, alert)| (status, alert.id.as_str()))
+            .collect();
+        assert_eq!(statuses, vec![(Status::Ended, "urn:c")]);
+    }
+
+    #[test]
+    fn filter_by_severity_and_event() {
+        let minor = alert("urn:a", "Special Weather Statement", Severity::Minor);
+        let flood = alert("urn:b", "Flood Warning", Severity::Severe);
+
+        let by_severity = AlertFilter {
+            min_severity: Some(Severity::Moderate),
+            events: Vec::new(),
+        };
+        assert!(!by_severity.wants(&minor));
+        assert!(by_severity.wants(&flood));
+
+        let by_event = AlertFilter {
+            min_severity: None,
+            events: vec!["flood warning".to_string()],
+        };
+        assert!(!by_event.wants(&minor));
+        assert!(by_event.wants(&flood));
+
+        assert!(AlertFilter::default().wants(&minor));
+    }
+
+    #[test]
+    fn commands_see_the_alert_in_the_environment() {
+        let alert = alert("urn:a", "Wind Advisory", Severity::Moderate);
+        let notification = Notification {
+            location: "98101",
+            status: Status::New,
+            alert: &alert,
+        };
+        let env = command_env(&notification);
+        for (name, value) in [
+            ("WXDOTGOV_LOCATION", "98101"),
+            ("WXDOTGOV_STATUS", "new"),
+            ("WXDOTGOV_ALERT_ID", "urn:a"),
+            ("WXDOTGOV_ALERT_EVENT", "Wind Advisory"),
+            ("WXDOTGOV_ALERT_HEADLINE", "Wind Advisory until 6 PM"),
+            ("WXDOTGOV_ALERT_SEVERITY", "moderate"),
+            ("WXDOTGOV_ALERT_ONSET", ""),
+        ] {
+            assert!(
+                env.iter().any(|(n, v)| *n == name && v == value),
+                "{name}={value} missing from {env:?}"
+            );
+        }
+        assert_eq!(
+            notification.summary(),
+            "98101: new Wind Advisory (moderate): Wind Advisory until 6 PM"
+        );
+    }
+
+    fn mock_upstream(upstream: &mut ServerGuard, alerts: &str) {
+        upstream
+            .mock("GET", "/search")
+            .match_query(Matcher::Any)
+            .with_body(r#"[{"lat": "47.6", "lon": "-122.3", "display_name": "Seattle"}]"#)
+            .create();
+        upstream
+            .mock("GET", "/alerts/active")
+            .match_query(Matcher::UrlEncoded(
+                "point".to_string(),
+                "47.6,-122.3".to_string(),
+            ))
+            .with_body(format!(r#"{{"features": [{alerts}]}}"#))
+            .create();
+    }
+
+    const WIND: &str = r#"{"properties": {
+        "id": "urn:oid:wind", "event": "Wind Advisory", "headline": "Wind Advisory until 6 PM",
+        "severity": "Moderate", "messageType": "Alert"
+    }}"#;
+
+    fn notifier(upstream: &ServerGuard, hook: &ServerGuard, state: PathBuf) -> Notifier {
+        let mut notifier = Notifier::new(
+            vec!["98101".parse().unwrap()],
+            AlertFilter::default(),
+            vec![Sink::Webhook(format!("{}/hook", hook.url()))],
+            state,
+        );
+        notifier.nominatim_url = Some(upstream.url());
+        notifier.weather_url = Some(upstream.url());
+        notifier
+    }
+
+    /// The point of the state file: a restarted daemon does not repeat what
+    /// it already delivered.
+    #[tokio::test]
+    async fn each_alert_is_delivered_once_across_restarts() {
+        let mut upstream = mockito::Server::new_async().await;
+        mock_upstream(&mut upstream, WIND);
+        let mut hook = mockito::Server::new_async().await;
+        let delivered = hook
+            .mock("POST", "/hook")
+            .match_body(Matcher::PartialJsonString(
+                r#"{"location": "98101", "status": "new",
+                    "alert": {"id": "urn:oid:wind", "event": "Wind Advisory"}}"#
+                    .to_string(),
+            ))
+            .expect(1)
+            .create_async()
+            .await;
+        let state = state_file("once");
+
+        let first = notifier(&upstream, &hook, state.clone());
+        assert_eq!(first.poll().await.unwrap(), 1);
+        assert_eq!(first.poll().await.unwrap(), 0);
+        let restarted = notifier(&upstream, &hook, state.clone());
+        assert_eq!(restarted.poll().await.unwrap(), 0);
+
+        delivered.assert_async().await;
+        let saved = std::fs::read_to_string(&state).unwrap();
+        assert!(saved.contains("urn:oid:wind"), "{saved}");
+    }
+
+    #[tokio::test]
+    async fn an_alert_that_drops_off_is_delivered_as_ended() {
+        let mut upstream = mockito::Server::new_async().await;
+        mock_upstream(&mut upstream, "");
+        let mut hook = mockito::Server::new_async().await;
+        let ended = hook
+            .mock("POST", "/hook")
+            .match_body(Matcher::PartialJsonString(
+                r#"{"status": "ended", "alert": {"id": "urn:oid:wind"}}"#.to_string(),
+            ))
+            .expect(1)
+            .create_async()
+            .await;
+        let state = state_file("ended");
+        std::fs::create_dir_all(state.parent().unwrap()).unwrap();
+        std::fs::write(
+            &state,
+            r#"{"98101": [{"id": "urn:oid:wind", "event": "Wind Advisory",
+                           "headline": null, "severity": "Moderate"}]}"#,
+        )
+        .unwrap();
+
+        let notifier = notifier(&upstream, &hook, state);
+        assert_eq!(notifier.poll().await.unwrap(), 1);
+        ended.assert_async().await;
+    }
+
+    #[tokio::test]
+    async fn a_failing_sink_does_not_stop_delivery() {
+        let mut upstream = mockito::Server::new_async().await;
+        mock_upstream(&mut upstream, WIND);
+        let mut hook = mockito::Server::new_async().await;
+        hook.mock("POST", "/hook")
+            .with_status(500)
+            .create_async()
+            .await;
+        let state = state_file("failing");
+
+        let notifier = notifier(&upstream, &hook, state);
+        assert_eq!(notifier.poll().await.unwrap(), 1);
+        // Recorded as delivered, so a recovering webhook is not flooded.
+        assert_eq!(notifier.poll().await.unwrap(), 0);
+    }
+}
@@ -1,7 +1,20 @@
 #[cfg(test)]
 mod tests {
-    use crate::render::{render_detailed, render_hourly, Style, DEFAULT_LIMIT};
-    use crate::weatherdotgov::{HourlyPeriod, Period};
+    use crate::archive::Recorded;
+    use crate::render::{
+        render_alerts, render_chart, render_detailed, render_detailed_html,
+        render_detailed_markdown, render_fire, render_forecast_check, render_hourly,
+        render_hourly_html, render_hourly_markdown, render_info, render_latest_observation,
+        render_observation_chart, render_observations, render_product, render_stations,
+        RenderOptions, Style, DEFAULT_LIMIT,
+    };
+    use crate::times::{DisplayZone, TimeDisplay, TimeFormat};
+    use crate::units::{QuantitativeValue, Units};
+    use crate::weatherdotgov::{
+        Alert, FireWeather, HourlyPeriod, Observation, Office, OfficeAddress, Period,
+        PointsProperties, Product, Severity, Station,
+    };
+    use chrono::{DateTime, FixedOffset, TimeDelta, Utc};
 
     /// Count the separator rules, which is how many periods actually printed.
     /// Matching on a dash substring would over-count inside the rule itself.
@@ -11,11 +24,22 @@ mod tests {
             .count()
     }
 
+    /// Midnight at the start of the fixture forecast, in Seattle's offset.
+    fn start() -> DateTime<FixedOffset> {
+        DateTime::parse_from_rfc3339("2024-01-28T00:00:00-08:00").unwrap()
+    }
+
     fn detailed_periods(count: usize) -> Vec<Period> {
         (0..count)
             .map(|i| Period {
                 name: format!("Period{i}"),
+                start_time: start() + TimeDelta::hours(12 * i as i64),
+                end_time: start() + TimeDelta::hours(12 * (i as i64 + 1)),
+                is_daytime: i % 2 == 1,
+                temperature: None,
+                short_forecast: None,
                 detailed_forecast: format!("Forecast text {i}"),
+                icon: None,
             })
             .collect()
     }
@@ -23,16 +47,39 @@ mod tests {
     fn hourly_periods(count: usize) -> Vec<HourlyPeriod> {
         (0..count)
             .map(|i| HourlyPeriod {
-                start_time: format!("2024-01-28T{i:02}:00:00-08:00"),
+                start_time: start() + TimeDelta::hours(i as i64),
+                end_time: start() + TimeDelta::hours(i as i64 + 1),
+                is_daytime: (6..18).contains(&(i % 24)),
                 temperature: 50 + i as i32,
                 temperature_unit: "F".to_string(),
                 wind_speed: format!("{i} mph"),
                 wind_direction: "SSW".to_string(),
                 short_forecast: format!("Short forecast {i}"),
+                dewpoint: None,
+                probability_of_precipitation: None,
+                icon: None,
             })
             .collect()
     }
 
+    /// Times exactly as the API sends them, so assertions can name them.
+    fn iso() -> TimeDisplay {
+        TimeDisplay {
+            format: TimeFormat::Iso,
+            zone: DisplayZone::Location,
+            now: Utc::now(),
+        }
+    }
+
+    fn opts(style: Style, limit: Option<usize>) -> RenderOptions {
+        RenderOptions {
+            style,
+            limit,
+            units: Units::Us,
+            times: iso(),
+        }
+    }
+
     /// The parity property #40 exists to protect: every field value printed in
     /// one style is printed in the other. Adding a field to only one branch
     /// fails here. Deliberately says nothing about ANSI escapes, so it does not
@@ -40,8 +87,8 @@ mod tests {
     #[test]
     fn detailed_prints_the_same_fields_in_both_styles() {
         let periods = detailed_periods(3);
-        let plain = render_detailed(&periods, Style::Plain, None);
-        let pretty = render_detailed(&periods, Style::Pretty, None);
+        let plain = render_detailed(&periods, 0, &opts(Style::Plain, None)).unwrap();
+        let pretty = render_detailed(&periods, 0, &opts(Style::Pretty, None)).unwrap();
 
         for period in &periods {
             for field in [&period.name, &period.detailed_forecast] {
@@ -54,12 +101,12 @@ mod tests {
     #[test]
     fn hourly_prints_the_same_fields_in_both_styles() {
         let periods = hourly_periods(3);
-        let plain = render_hourly(&periods, Style::Plain, None);
-        let pretty = render_hourly(&periods, Style::Pretty, None);
+        let plain = render_hourly(&periods, 0, &opts(Style::Plain, None)).unwrap();
+        let pretty = render_hourly(&periods, 0, &opts(Style::Pretty, None)).unwrap();
 
         for period in &periods {
             let fields = [
-                period.start_time.clone(),
+                period.start_time.to_rfc3339(),
                 period.temperature.to_string(),
                 period.temperature_unit.clone(),
                 period.wind_speed.clone(),
@@ -76,11 +123,11 @@ mod tests {
     #[test]
     fn both_styles_carry_the_header_and_one_separator_per_period() {
         for style in [Style::Plain, Style::Pretty] {
-            let detailed = render_detailed(&detailed_periods(3), style, None);
+            let detailed = render_detailed(&detailed_periods(3), 0, &opts(style, None)).unwrap();
             assert!(detailed.contains("Weather Forecast:"));
             assert_eq!(separator_count(&detailed), 3, "{style:?}");
 
-            let hourly = render_hourly(&hourly_periods(2), style, None);
+            let hourly = render_hourly(&hourly_periods(2), 0, &opts(style, None)).unwrap();
             assert!(hourly.contains("Hourly Weather Forecast:"));
             assert_eq!(separator_count(&hourly), 2, "{style:?}");
         }
@@ -89,14 +136,14 @@ mod tests {
     #[test]
     fn no_limit_prints_every_period() {
         let periods = hourly_periods(50);
-        let out = render_hourly(&periods, Style::Plain, None);
+        let out = render_hourly(&periods, 0, &opts(Style::Plain, None)).unwrap();
         assert_eq!(separator_count(&out), 50);
         assert!(!out.contains("not shown"));
     }
 
     #[test]
     fn limit_truncates_and_says_how_many_were_dropped() {
-        let out = render_hourly(&hourly_periods(50), Style::Plain, Some(2));
+        let out = render_hourly(&hourly_periods(50), 0, &opts(Style::Plain, Some(2))).unwrap();
 
         assert!(out.contains("2024-01-28T00:00:00-08:00"));
         assert!(out.contains("2024-01-28T01:00:00-08:00"));
@@ -107,7 +154,7 @@ mod tests {
 
     #[test]
     fn limit_applies_to_detailed_too() {
-        let out = render_detailed(&detailed_periods(5), Style::Plain, Some(1));
+        let out = render_detailed(&detailed_periods(5), 0, &opts(Style::Plain, Some(1))).unwrap();
         assert!(out.contains("Period0"));
         assert!(!out.contains("Period1"));
         assert!(out.contains("4 more period(s) not shown"));
@@ -117,14 +164,19 @@ mod tests {
     /// forecast while bounding the week-plus hourly one.
     #[test]
     fn default_limit_does_not_truncate_a_detailed_forecast() {
-        let out = render_detailed(&detailed_periods(14), Style::Plain, Some(DEFAULT_LIMIT));
+        let out = render_detailed(
+            &detailed_periods(14),
+            0,
+            &opts(Style::Plain, Some(DEFAULT_LIMIT)),
+        )
+        .unwrap();
         assert_eq!(separator_count(&out), 14);
         assert!(!out.contains("not shown"));
     }
 
     #[test]
     fn limit_larger_than_the_period_list_is_not_an_error() {
-        let out = render_detailed(&detailed_periods(2), Style::Plain, Some(99));
+        let out = render_detailed(&detailed_periods(2), 0, &opts(Style::Plain, Some(99))).unwrap();
         assert_eq!(separator_count(&out), 2);
         assert!(!out.contains("not shown"));
     }
@@ -132,10 +184,686 @@ mod tests {
     #[test]
     fn empty_period_lists_render_just_the_header() {
         for style in [Style::Plain, Style::Pretty] {
-            let out = render_detailed(&[], style, Some(DEFAULT_LIMIT));
+            let out = render_detailed::<Period>(&[], 0, &opts(style, Some(DEFAULT_LIMIT))).unwrap();
             assert!(out.contains("Weather Forecast:"));
             assert_eq!(separator_count(&out), 0);
             assert!(!out.contains("not shown"));
         }
     }
+
+    #[test]
+    fn dewpoint_is_shown_in_the_requested_units_in_both_styles() {
+        // The API sends dewpoint in Celsius even for a `units=us` forecast, so
+        // printing it unconverted would put °C beside a °F temperature.
+        let mut periods = hourly_periods(1);
+        periods[0].dewpoint = Some(QuantitativeValue {
+            value: Some(10.0),
+            unit_code: "wmoUnit:degC".to_string(),
+        });
+        periods[0].probability_of_precipitation = Some(QuantitativeValue {
+            value: Some(40.0),
+            unit_code: "wmoUnit:percent".to_string(),
+        });
+
+        for style in [Style::Plain, Style::Pretty] {
+            let us = render_hourly(&periods, 0, &opts(style, None)).unwrap();
+            assert!(us.contains("Dewpoint: 50°F"), "{style:?}: {us}");
+            assert!(us.contains("Precip: 40%"), "{style:?}: {us}");
+
+            let si_opts = RenderOptions {
+                units: Units::Si,
+                ..opts(style, None)
+            };
+            let si = render_hourly(&periods, 0, &si_opts).unwrap();
+            assert!(si.contains("Dewpoint: 10°C"), "{style:?}: {si}");
+        }
+    }
+
+    #[test]
+    fn missing_optional_values_are_omitted_not_blank() {
+        let out = render_hourly(&hourly_periods(1), 0, &opts(Style::Plain, None)).unwrap();
+        assert!(!out.contains("Dewpoint"));
+        assert!(!out.contains("Precip"));
+    }
+
+    #[test]
+    fn hourly_opens_each_day_with_a_heading_in_both_styles() {
+        // 30 hourly periods from midnight span two calendar days.
+        let periods = hourly_periods(30);
+        for style in [Style::Plain, Style::Pretty] {
+            let out = render_hourly(&periods, 0, &opts(style, None)).unwrap();
+            assert_eq!(out.matches("Sunday, January 28").count(), 1, "{style:?}");
+            assert_eq!(out.matches("Monday, January 29").count(), 1, "{style:?}");
+        }
+    }
+
+    #[test]
+    fn hourly_uses_the_requested_time_format() {
+        let twelve_hour = RenderOptions {
+            times: TimeDisplay {
+                format: TimeFormat::TwelveHour,
+                ..iso()
+            },
+            ..opts(Style::Plain, None)
+        };
+        let out = render_hourly(&hourly_periods(19), 0, &twelve_hour).unwrap();
+        assert!(out.contains("Sun 6 PM: "), "{out}");
+        assert!(!out.contains("2024-01-28T"), "{out}");
+    }
+
+    #[test]
+    fn filtered_and_limited_periods_are_reported_separately() {
+        let out = render_hourly(&hourly_periods(10), 4, &opts(Style::Plain, Some(3))).unwrap();
+        assert!(
+            out.contains("4 period(s) outside the requested time window not shown"),
+            "{out}"
+        );
+        assert!(out.contains("7 more period(s) not shown"), "{out}");
+    }
+
+    #[test]
+    fn filtering_alone_does_not_suggest_raising_the_limit() {
+        for style in [Style::Plain, Style::Pretty] {
+            let out = render_detailed(&detailed_periods(2), 5, &opts(style, None)).unwrap();
+            assert!(out.contains("5 period(s) outside"), "{style:?}: {out}");
+            assert!(!out.contains("--limit 0"), "{style:?}: {out}");
+        }
+    }
+
+    /// Hourly periods with a chance of precipitation that rises through the day.
+    fn rainy_periods(count: usize) -> Vec<HourlyPeriod> {
+        let mut periods = hourly_periods(count);
+        for (i, period) in periods.iter_mut().enumerate() {
+            period.probability_of_precipitation = Some(QuantitativeValue {
+                value: Some((i * 10 % 100) as f64),
+                unit_code: "wmoUnit:percent".to_string(),
+            });
+        }
+        periods
+    }
+
+    /// The chart rows: lines carrying the `|` axis, excluding the day labels.
+    fn chart_rows(out: &str) -> Vec<&str> {
+        out.lines()
+            .filter(|l| l.len() > 6 && l.chars().nth(5) == Some('|'))
+            .collect()
+    }
+
+    #[test]
+    fn chart_labels_the_same_extremes_and_days_in_both_styles() {
+        let periods = rainy_periods(30);
+        let plain = render_chart(&periods, 0, &opts(Style::Plain, None));
+        let pretty = render_chart(&periods, 0, &opts(Style::Pretty, None));
+
+        for out in [&plain, &pretty] {
+            assert!(out.contains("high 79°"), "{out}");
+            assert!(out.contains("2024-01-29T05:00:00-08:00"), "{out}");
+            assert!(out.contains("low 50°"), "{out}");
+            assert!(out.contains("peak 90%"), "{out}");
+            assert!(out.contains("Sun") && out.contains("Mon"), "{out}");
+        }
+    }
+
+    #[test]
+    fn plain_chart_is_ascii_only() {
+        // The summary lines share the degree sign with the rest of the plain
+        // output; the bars and axes are what must survive an ASCII terminal.
+        let out = render_chart(&rainy_periods(48), 0, &opts(Style::Plain, None));
+        let drawing: Vec<&str> = out.lines().filter(|l| l.starts_with(' ')).collect();
+        assert!(!drawing.is_empty());
+        assert!(drawing.iter().all(|l| l.is_ascii()), "{out}");
+        assert!(out.contains('#'));
+    }
+
+    #[test]
+    fn chart_has_one_column_per_hour_until_it_would_be_too_wide() {
+        let day = render_chart(&hourly_periods(24), 0, &opts(Style::Plain, None));
+        let rows = chart_rows(&day);
+        assert_eq!(rows.len(), 8, "no precipitation chart without data: {day}");
+        assert!(rows.iter().all(|r| r.len() == 6 + 24), "{day}");
+
+        // A week is 168 hours, which merges into pairs.
+        let week = render_chart(&hourly_periods(168), 0, &opts(Style::Plain, None));
+        assert!(
+            chart_rows(&week).iter().all(|r| r.len() == 6 + 84),
+            "{week}"
+        );
+    }
+
+    #[test]
+    fn chart_respects_the_limit() {
+        let out = render_chart(&rainy_periods(48), 0, &opts(Style::Plain, Some(12)));
+        assert!(chart_rows(&out).iter().all(|r| r.len() == 6 + 12));
+        assert!(out.contains("36 more period(s) not shown"), "{out}");
+    }
+
+    #[test]
+    fn chart_of_nothing_is_just_the_header() {
+        for style in [Style::Plain, Style::Pretty] {
+            let out = render_chart(&[], 3, &opts(style, None));
+            assert!(out.contains("Hourly Forecast Chart:"));
+            assert!(chart_rows(&out).is_empty());
+            assert!(out.contains("3 period(s) outside"));
+        }
+    }
+
+    /// The Markdown and HTML reports are held to the same parity as the two
+    /// terminal styles: a field the plain layout prints must reach the
+    /// documents too, or a pasted report silently says less than the terminal.
+    #[test]
+    fn documents_print_the_same_detailed_fields_as_plain() {
+        let periods = detailed_periods(3);
+        let markdown = render_detailed_markdown(&periods, 0, &opts(Style::Plain, None));
+        let html = render_detailed_html(&periods, 0, &opts(Style::Plain, None));
+
+        for period in &periods {
+            for field in [&period.name, &period.detailed_forecast] {
+                assert!(
+                    markdown.contains(field.as_str()),
+                    "markdown missing {field}"
+                );
+                assert!(html.contains(field.as_str()), "html missing {field}");
+            }
+        }
+    }
+
+    #[test]
+    fn documents_print_the_same_hourly_fields_as_plain() {
+        let mut periods = hourly_periods(30);
+        periods[2].dewpoint = Some(QuantitativeValue {
+            value: Some(10.0),
+            unit_code: "wmoUnit:degC".to_string(),
+        });
+        periods[2].probability_of_precipitation = Some(QuantitativeValue {
+            value: Some(40.0),
+            unit_code: "wmoUnit:percent".to_string(),
+        });
+        let markdown = render_hourly_markdown(&periods, 0, &opts(Style::Plain, None));
+        let html = render_hourly_html(&periods, 0, &opts(Style::Plain, None));
+
+        for period in &periods {
+            let fields = [
+                period.start_time.to_rfc3339(),
+                period.temperature.to_string(),
+                period.temperature_unit.clone(),
+                period.wind_speed.clone(),
+                period.wind_direction.clone(),
+                period.short_forecast.clone(),
+            ];
+            for field in fields {
+                assert!(markdown.contains(&field), "markdown missing {field}");
+                assert!(html.contains(&field), "html missing {field}");
+            }
+        }
+        for field in ["50°F", "40%", "Sunday, January 28", "Monday, January 29"] {
+            assert!(markdown.contains(field), "markdown missing {field}");
+            assert!(html.contains(field), "html missing {field}");
+        }
+    }
+
+    #[test]
+    fn markdown_hourly_is_a_table_per_day() {
+        let out = render_hourly_markdown(&hourly_periods(30), 0, &opts(Style::Plain, None));
+        assert_eq!(out.matches("| Time | Temp |").count(), 2, "{out}");
+        assert!(out.contains("\n## Sunday, January 28\n"), "{out}");
+        assert!(
+            out.contains(
+                "| 2024-01-28T00:00:00-08:00 | 50°F | Short forecast 0 | 0 mph SSW |  |  |\n"
+            ),
+            "{out}"
+        );
+    }
+
+    #[test]
+    fn markdown_escapes_pipes_inside_cells() {
+        let mut periods = hourly_periods(1);
+        periods[0].short_forecast = "Rain | Snow".to_string();
+        let out = render_hourly_markdown(&periods, 0, &opts(Style::Plain, None));
+        assert!(out.contains("| Rain \\| Snow |"), "{out}");
+    }
+
+    #[test]
+    fn html_is_a_complete_page_that_references_the_icons() {
+        let mut periods = detailed_periods(2);
+        periods[0].icon =
+            Some("https://api.weather.gov/icons/land/night/rain,20?size=medium".to_string());
+        let out = render_detailed_html(&periods, 0, &opts(Style::Plain, None));
+
+        assert!(out.starts_with("<!DOCTYPE html>\n"), "{out}");
+        assert!(out.ends_with("</html>\n"), "{out}");
+        assert!(out.contains("<style>"), "{out}");
+        assert!(
+            out.contains(r#"<img src="https://api.weather.gov/icons/land/night/rain,20?size=medium" alt="Period0""#),
+            "{out}"
+        );
+        // A period without an icon gets no broken image.
+        assert_eq!(out.matches("<img").count(), 1, "{out}");
+    }
+
+    #[test]
+    fn html_escapes_forecast_text() {
+        let mut periods = detailed_periods(1);
+        periods[0].detailed_forecast = "Gusts <40 mph & rain".to_string();
+        let out = render_detailed_html(&periods, 0, &opts(Style::Plain, None));
+        assert!(out.contains("Gusts &lt;40 mph &amp; rain"), "{out}");
+    }
+
+    #[test]
+    fn documents_report_filtered_and_limited_periods() {
+        let markdown = render_hourly_markdown(&hourly_periods(10), 4, &opts(Style::Plain, Some(3)));
+        let html = render_hourly_html(&hourly_periods(10), 4, &opts(Style::Plain, Some(3)));
+        for out in [markdown, html] {
+            assert!(
+                out.contains("4 period(s) outside the requested time window"),
+                "{out}"
+            );
+            assert!(out.contains("7 more period(s) not shown"), "{out}");
+        }
+    }
+
+    #[test]
+    fn alerts_print_event_severity_headline_and_end() {
+        let alert = Alert {
+            id: "urn:1".to_string(),
+            event: "Wind Advisory".to_string(),
+            headline: Some("Wind Advisory until 6 PM".to_string()),
+            severity: Severity::Moderate,
+            onset: None,
+            ends: Some(start() + TimeDelta::hours(18)),
+            message_type: None,
+            references: Vec::new(),
+        };
+        for style in [Style::Plain, Style::Pretty] {
+            let out = render_alerts(std::slice::from_ref(&alert), &opts(style, None));
+            assert!(out.contains("Wind Advisory (moderate)"), "{out}");
+            assert!(out.contains("Wind Advisory until 6 PM"), "{out}");
+            assert!(out.contains("Until 2024-01-28T18:00:00-08:00"), "{out}");
+            assert_eq!(separator_count(&out), 1, "{out}");
+        }
+
+        let none = render_alerts(&[], &opts(Style::Plain, None));
+        assert!(none.contains("No active alerts."), "{none}");
+    }
+
+    #[test]
+    fn products_print_as_issued_under_their_name() {
+        let product = Product {
+            issuing_office: "KSEW".to_string(),
+            issuance_time: start(),
+            product_name: "Area Forecast Discussion".to_string(),
+            product_text: ".SYNOPSIS...\nShowers taper off tonight.\n\n".to_string(),
+        };
+        let out = render_product(&product, &opts(Style::Plain, None));
+        assert!(out.contains("Area Forecast Discussion:"), "{out}");
+        assert!(out.contains("by KSEW"), "{out}");
+        assert!(
+            out.ends_with(".SYNOPSIS...\nShowers taper off tonight.\n"),
+            "{out}"
+        );
+    }
+
+    #[test]
+    fn info_prints_the_office_and_every_zone_given() {
+        let point = PointsProperties {
+            grid_id: "SEW".to_string(),
+            grid_x: 124,
+            grid_y: 67,
+            cwa: Some("SEW".to_string()),
+            forecast_zone: Some("https://api.weather.gov/zones/forecast/WAZ558".to_string()),
+            county: Some("https://api.weather.gov/zones/county/WAC033".to_string()),
+            fire_weather_zone: None,
+            radar_station: Some("KATX".to_string()),
+            time_zone: Some("America/Los_Angeles".to_string()),
+            ..Default::default()
+        };
+        let office = Office {
+            id: "SEW".to_string(),
+            name: "Seattle, WA".to_string(),
+            address: Some(OfficeAddress {
+                street_address: "7600 Sand Point Way NE".to_string(),
+                address_locality: "Seattle".to_string(),
+                address_region: "WA".to_string(),
+                postal_code: "98115-6349".to_string(),
+            }),
+            telephone: Some("(206) 526-6087".to_string()),
+            email: None,
+        };
+
+        for style in [Style::Plain, Style::Pretty] {
+            let out = render_info(&point, &office, &opts(style, None));
+            assert!(out.contains("Seattle, WA (SEW)"), "{out}");
+            assert!(
+                out.contains("7600 Sand Point Way NE, Seattle, WA 98115-6349"),
+                "{out}"
+            );
+            assert!(out.contains("(206) 526-6087"), "{out}");
+            assert!(out.contains("SEW 124,67"), "{out}");
+            assert!(out.contains("WAZ558"), "{out}");
+            assert!(out.contains("WAC033"), "{out}");
+            assert!(out.contains("KATX"), "{out}");
+            assert!(out.contains("America/Los_Angeles"), "{out}");
+            // Left out by the API, so left out here.
+            assert!(!out.contains("Fire weather zone"), "{out}");
+        }
+    }
+
+    #[test]
+    fn fire_summarizes_each_day_at_its_worst_in_the_location_zone() {
+        let fire: FireWeather = serde_json::from_str(
+            r#"{
+                "relativeHumidity": {"uom": "wmoUnit:percent", "values": [
+                    {"validTime": "2024-07-15T15:00:00+00:00/PT6H", "value": 40},
+                    {"validTime": "2024-07-15T21:00:00+00:00/PT3H", "value": 18},
+                    {"validTime": "2024-07-16T17:00:00+00:00/PT3H", "value": 22}
+                ]},
+                "windGust": {"uom": "wmoUnit:km_h-1", "values": [
+                    {"validTime": "2024-07-15T22:00:00+00:00/PT1H", "value": 40.2}
+                ]},
+                "mixingHeight": {"uom": "wmoUnit:m", "values": [
+                    {"validTime": "2024-07-15T21:00:00+00:00/PT3H", "value": 1500}
+                ]},
+                "hainesIndex": {"values": [
+                    {"validTime": "2024-07-15T18:00:00+00:00/P1D", "value": 6}
+                ]}
+            }"#,
+        )
+        .unwrap();
+        let warning: Alert = serde_json::from_str(
+            r#"{"event": "Red Flag Warning", "severity": "Severe",
+                "headline": "Red Flag Warning until 9 PM PDT"}"#,
+        )
+        .unwrap();
+        let offset = FixedOffset::west_opt(7 * 3600).unwrap();
+        let opts = RenderOptions {
+            style: Style::Plain,
+            limit: None,
+            units: Units::Us,
+            times: TimeDisplay {
+                format: TimeFormat::TwelveHour,
+                zone: DisplayZone::Location,
+                now: "2024-07-15T17:00:00Z".parse().unwrap(),
+            },
+        };
+
+        let out = render_fire(&fire, &[warning], Some("WAZ654"), offset, 3, &opts);
+        assert!(out.contains("Fire weather zone: WAZ654"), "{out}");
+        assert!(out.contains("⚠ Red Flag Warning (severe)"), "{out}");
+        assert!(out.contains("== Monday, July 15 =="), "{out}");
+        assert!(out.contains("Min humidity:     18% (Mon 2 PM)"), "{out}");
+        assert!(out.contains("Max wind gust:    25 mph (Mon 3 PM)"), "{out}");
+        assert!(
+            out.contains("Mixing height:    4921 ft (Mon 2 PM)"),
+            "{out}"
+        );
+        assert!(
+            out.contains("Haines index:     6 (high) (Mon 11 AM)"),
+            "{out}"
+        );
+        // The Haines value runs into Tuesday, starting there at midnight.
+        assert!(out.contains("== Tuesday, July 16 =="), "{out}");
+        assert!(out.contains("Min humidity:     22% (Tue 10 AM)"), "{out}");
+        assert!(
+            out.contains("Haines index:     6 (high) (Tue 12 AM)"),
+            "{out}"
+        );
+        // The grid ends there, and so does the summary.
+        assert!(!out.contains("Wednesday"), "{out}");
+        assert!(!out.contains("Transport wind"), "{out}");
+
+        let out = render_fire(&fire, &[], None, offset, 1, &opts);
+        assert!(
+            out.contains("No Red Flag Warnings or Fire Weather Watches."),
+            "{out}"
+        );
+        assert!(!out.contains("Tuesday"), "{out}");
+
+        // The days and times follow `offset` alone, whatever zone this
+        // machine is in, rather than being shifted once more into it.
+        let local = RenderOptions {
+            times: TimeDisplay {
+                zone: DisplayZone::Local,
+                ..opts.times
+            },
+            ..opts
+        };
+        let out = render_fire(&fire, &[], None, offset, 3, &local);
+        assert!(out.contains("== Monday, July 15 =="), "{out}");
+        assert!(out.contains("Min humidity:     18% (Mon 2 PM)"), "{out}");
+        assert!(out.contains("== Tuesday, July 16 =="), "{out}");
+        assert!(
+            out.contains("Haines index:     6 (high) (Tue 12 AM)"),
+            "{out}"
+        );
+    }
+
+    fn observations() -> Vec<Observation> {
+        let reading = |at: &str, celsius: f64| {
+            serde_json::json!({
+                "timestamp": at,
+                "textDescription": "",
+                "temperature": {"unitCode": "wmoUnit:degC", "value": celsius},
+            })
+        };
+        let mut observations: Vec<Observation> = [
+            reading("2024-07-14T18:00:00-07:00", 15.0),
+            reading("2024-07-15T04:00:00-07:00", 12.0),
+            reading("2024-07-15T16:00:00-07:00", 25.0),
+        ]
+        .into_iter()
+        .map(|json| serde_json::from_value(json).unwrap())
+        .collect();
+        observations.insert(
+            2,
+            serde_json::from_str(
+                r#"{
+                    "timestamp": "2024-07-15T14:00:00-07:00",
+                    "textDescription": "Mostly Cloudy",
+                    "temperature": {"unitCode": "wmoUnit:degC", "value": 22},
+                    "windSpeed": {"unitCode": "wmoUnit:km_h-1", "value": null},
+                    "relativeHumidity": {"unitCode": "wmoUnit:percent", "value": 55.3}
+                }"#,
+            )
+            .unwrap(),
+        );
+        observations
+    }
+
+    fn history_opts(style: Style) -> RenderOptions {
+        RenderOptions {
+            style,
+            limit: None,
+            units: Units::Us,
+            times: TimeDisplay {
+                format: TimeFormat::TwelveHour,
+                zone: DisplayZone::Location,
+                now: "2024-07-16T02:00:00Z".parse().unwrap(),
+            },
+        }
+    }
+
+    fn station() -> Station {
+        Station {
+            station_identifier: "KSEA".to_string(),
+            name: "Seattle-Tacoma International Airport".to_string(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn observations_list_each_reading_reported() {
+        for style in [Style::Plain, Style::Pretty] {
+            let out = render_observations(&station(), &observations(), &history_opts(style));
+            assert!(
+                out.contains("Observed at Seattle-Tacoma International Airport (KSEA):"),
+                "{out}"
+            );
+            assert!(out.contains("Monday, July 15"), "{out}");
+            assert!(
+                out.contains("72°F | Mostly Cloudy | Humidity: 55%"),
+                "{out}"
+            );
+            // No wind reading, and no blank description, to print.
+            assert!(!out.contains("Wind"), "{out}");
+            assert!(out.contains("Mon 4 AM"), "{out}");
+        }
+        let plain = render_observations(&station(), &observations(), &history_opts(Style::Plain));
+        assert!(plain.contains("Mon 4 AM: 54°F\n"), "{plain}");
+
+        let none = render_observations(&station(), &[], &history_opts(Style::Plain));
+        assert!(none.contains("No observations in that time."), "{none}");
+    }
+
+    #[test]
+    fn observation_chart_marks_the_extremes() {
+        let out =
+            render_observation_chart(&station(), &observations(), &history_opts(Style::Plain));
+        assert!(
+            out.contains("Temperature (°F): high 77° Mon 4 PM, low 54° Mon 4 AM"),
+            "{out}"
+        );
+        // One column per observation, the day axis under them.
+        assert!(out.contains("     +----\n"), "{out}");
+    }
+
+    #[test]
+    fn forecast_check_compares_ended_periods_only() {
+        let recorded = |name: &str, start: &str, end: &str, day: bool, fahrenheit: f64| Recorded {
+            name: name.to_string(),
+            start_time: start.parse().unwrap(),
+            end_time: end.parse().unwrap(),
+            is_daytime: day,
+            fahrenheit,
+        };
+        let recorded = [
+            recorded(
+                "Tonight",
+                "2024-07-14T18:00:00-07:00",
+                "2024-07-15T06:00:00-07:00",
+                false,
+                55.0,
+            ),
+            recorded(
+                "Monday",
+                "2024-07-15T06:00:00-07:00",
+                "2024-07-15T18:00:00-07:00",
+                true,
+                75.0,
+            ),
+            recorded(
+                "Monday Night",
+                "2024-07-15T18:00:00-07:00",
+                "2024-07-16T06:00:00-07:00",
+                false,
+                58.0,
+            ),
+        ];
+
+        let out = render_forecast_check(&recorded, &observations(), &history_opts(Style::Plain));
+        assert!(
+            out.contains("Tonight (Sun 6 PM): forecast low 55°F, observed 54°F (+1°)"),
+            "{out}"
+        );
+        assert!(
+            out.contains("Monday (Mon 6 AM): forecast high 75°F, observed 77°F (-2°)"),
+            "{out}"
+        );
+        assert!(!out.contains("Monday Night"), "{out}");
+        assert!(
+            out.contains("Mean absolute error: 1.7°F over 2 periods"),
+            "{out}"
+        );
+
+        let opts = RenderOptions {
+            units: Units::Si,
+            ..history_opts(Style::Plain)
+        };
+        let out = render_forecast_check(&recorded, &observations(), &opts);
+        assert!(
+            out.contains("Monday (Mon 6 AM): forecast high 24°C, observed 25°C (-1°)"),
+            "{out}"
+        );
+
+        let out = render_forecast_check(&[], &observations(), &history_opts(Style::Plain));
+        assert!(
+            out.contains("No recorded forecast covers the observed hours"),
+            "{out}"
+        );
+    }
+
+    fn nearby() -> Vec<Station> {
+        let at = |id: &str, name: &str, position| Station {
+            station_identifier: id.to_string(),
+            name: name.to_string(),
+            position,
+        };
+        vec![
+            at("KBFI", "Seattle, Boeing Field", Some((47.53, -122.30))),
+            at(
+                "KSEA",
+                "Seattle-Tacoma International Airport",
+                Some((47.44467, -122.31442)),
+            ),
+            at("KXYZ", "Somewhere Unplaced", None),
+            at("KNEA", "Northeast of Downtown", Some((47.68, -122.25))),
+        ]
+    }
+
+    const DOWNTOWN: (f64, f64) = (47.6062, -122.3321);
+
+    #[test]
+    fn stations_are_listed_with_distance_and_bearing() {
+        let out = render_stations(
+            &nearby(),
+            DOWNTOWN,
+            Some("KSEA"),
+            &history_opts(Style::Plain),
+        );
+        assert!(out.contains("Observation Stations:"), "{out}");
+        assert!(
+            out.contains("  KBFI     5.5 mi SSE Seattle, Boeing Field\n"),
+            "{out}"
+        );
+        assert!(
+            out.contains("* KSEA    11.2 mi S   Seattle-Tacoma International Airport\n"),
+            "{out}"
+        );
+        assert!(out.contains("  KNEA     6.4 mi NE  Northeast"), "{out}");
+        // A station without coordinates is still listed.
+        assert!(
+            out.contains("  KXYZ          ?     Somewhere Unplaced"),
+            "{out}"
+        );
+        assert!(!out.contains("not listed"), "{out}");
+
+        let opts = RenderOptions {
+            units: Units::Si,
+            limit: Some(1),
+            ..history_opts(Style::Plain)
+        };
+        let out = render_stations(&nearby(), DOWNTOWN, Some("KPAE"), &opts);
+        assert!(out.contains("  KBFI     8.8 km SSE"), "{out}");
+        assert!(!out.contains("KSEA"), "{out}");
+        assert!(out.contains("... 3 more station(s) not shown"), "{out}");
+        assert!(
+            out.contains("Using KPAE, which is not listed here."),
+            "{out}"
+        );
+    }
+
+    #[test]
+    fn latest_observation_names_its_station_and_time() {
+        let mut observation = observations().remove(2);
+        observation.timestamp = observation
+            .timestamp
+            .with_timezone(&FixedOffset::west_opt(7 * 3600).unwrap());
+        let out = render_latest_observation(&station(), &observation, &history_opts(Style::Plain));
+        assert!(
+            out.contains("Observed at Seattle-Tacoma International Airport (KSEA), Mon 2 PM:"),
+            "{out}"
+        );
+        assert!(
+            out.contains("72°F | Mostly Cloudy | Humidity: 55%"),
+            "{out}"
+        );
+    }
 }
@@ -0,0 +1,273 @@
+#[cfg(test)]
+mod tests {
+    use crate::serve::{router, ServeState};
+    use mockito::{Matcher, Mock, ServerGuard};
+
+    /// Start the server on an ephemeral port, with both upstreams pointed at
+    /// `upstream`, and return its base URL.
+    async fn start(upstream: &ServerGuard) -> String {
+        let state = ServeState {
+            nominatim_url: Some(upstream.url()),
+            weather_url: Some(upstream.url()),
+            ..ServeState::default()
+        };
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move { axum::serve(listener, router(state)).await });
+        format!("http://{addr}")
+    }
+
+    fn mock_geocoding(upstream: &mut ServerGuard) -> Mock {
+        upstream
+            .mock("GET", "/search")
+            .match_query(Matcher::Any)
+            .with_body(r#"[{"lat": "47.6", "lon": "-122.3", "display_name": "Seattle, WA"}]"#)
+            .create()
+    }
+
+    fn mock_point(upstream: &mut ServerGuard) -> Mock {
+        let url = upstream.url();
+        upstream
+            .mock("GET", "/points/47.6,-122.3")
+            .with_body(format!(
+                r#"{{"properties": {{
+                    "gridId": "SEW", "gridX": 115, "gridY": 68,
+                    "forecast": "{url}/gridpoints/SEW/115,68/forecast",
+                    "forecastHourly": "{url}/gridpoints/SEW/115,68/forecast/hourly"
+                }}}}"#
+            ))
+            .create()
+    }
+
+    fn mock_forecast(upstream: &mut ServerGuard) -> Mock {
+        upstream
+            .mock("GET", "/gridpoints/SEW/115,68/forecast")
+            .match_query(Matcher::Any)
+            .with_body(
+                r#"{"properties": {"periods": [{
+                    "name": "Tonight",
+                    "startTime": "2024-01-28T18:00:00-08:00",
+                    "endTime": "2024-01-29T06:00:00-08:00",
+                    "isDaytime": false,
+                    "detailedForecast": "Rain likely."
+                }]}}"#,
+            )
+            .create()
+    }
+
+    fn mock_hourly(upstream: &mut ServerGuard) -> Mock {
+        upstream
+            .mock("GET", "/gridpoints/SEW/115,68/forecast/hourly")
+            .match_query(Matcher::Any)
+            .with_body(
+                r#"{"properties": {"periods": [{
+                    "startTime": "2024-01-28T18:00:00-08:00",
+                    "endTime": "2024-01-28T19:00:00-08:00",
+                    "isDaytime": false,
+                    "temperature": 51,
+                    "temperatureUnit": "F",
+                    "windSpeed": "5 mph",
+                    "windDirection": "SSW",
+                    "shortForecast": "Light Rain"
+                }]}}"#,
+            )
+            .create()
+    }
+
+    fn mock_alerts(upstream: &mut ServerGuard) -> Mock {
+        upstream
+            .mock("GET", "/alerts/active")
+            .match_query(Matcher::UrlEncoded(
+                "point".to_string(),
+                "47.6,-122.3".to_string(),
+            ))
+            .with_body(
+                r#"{"features": [{"properties": {
+                    "event": "Wind Advisory", "headline": null, "severity": "Moderate"
+                }}]}"#,
+            )
+            .create()
+    }
+
+    #[tokio::test]
+    async fn healthz_answers_without_touching_upstream() {
+        let upstream = mockito::Server::new_async().await;
+        let base = start(&upstream).await;
+        let response = reqwest::get(format!("{base}/healthz")).await.unwrap();
+        assert_eq!(response.status(), 200);
+        assert_eq!(response.text().await.unwrap(), "ok\n");
+    }
+
+    #[tokio::test]
+    async fn hourly_json_carries_the_template_context() {
+        let mut upstream = mockito::Server::new_async().await;
+        let mocks = [
+            mock_geocoding(&mut upstream),
+            mock_point(&mut upstream),
+            mock_hourly(&mut upstream),
+            mock_alerts(&mut upstream),
+        ];
+        let base = start(&upstream).await;
+
+        let response = reqwest::get(format!("{base}/forecast?zip=98101&type=hourly&format=json"))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), 200);
+        let json: serde_json::Value = response.json().await.unwrap();
+        assert_eq!(json["location"]["name"], "Seattle, WA");
+        assert_eq!(json["point"]["gridId"], "SEW");
+        assert_eq!(json["forecast_type"], "hourly");
+        assert_eq!(json["periods"][0]["shortForecast"], "Light Rain");
+        assert_eq!(json["alerts"][0]["event"], "Wind Advisory");
+        for mock in mocks {
+            mock.assert();
+        }
+    }
+
+    /// The reason the server exists rather than dashboards running the CLI:
+    /// repeated requests for a place are answered from one cache.
+    #[tokio::test]
+    async fn repeated_requests_share_the_cache() {
+        let mut upstream = mockito::Server::new_async().await;
+        let mocks = [
+            mock_geocoding(&mut upstream).expect(1),
+            mock_point(&mut upstream).expect(1),
+            mock_forecast(&mut upstream).expect(1),
+        ];
+        let base = start(&upstream).await;
+
+        for _ in 0..3 {
+            let response = reqwest::get(format!("{base}/forecast?zip=98101&format=text"))
+                .await
+                .unwrap();
+            assert_eq!(response.status(), 200);
+            assert!(response
+                .text()
+                .await
+                .unwrap()
+                .contains("Tonight: Rain likely."));
+        }
+        for mock in mocks {
+            mock.assert();
+        }
+    }
+
+    #[tokio::test]
+    async fn coordinates_skip_geocoding() {
+        let mut upstream = mockito::Server::new_async().await;
+        let geocoding = mock_geocoding(&mut upstream).expect(0);
+        let _point = mock_point(&mut upstream);
+        let _forecast = mock_forecast(&mut upstream);
+        let base = start(&upstream).await;
+
+        let response = reqwest::get(format!("{base}/forecast?lat=47.6&lon=-122.3&format=html"))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), 200);
+        assert_eq!(
+            response.headers()["content-type"],
+            "text/html; charset=utf-8"
+        );
+        assert!(response.text().await.unwrap().contains("<h2>Tonight</h2>"));
+        geocoding.assert();
+    }
+
+    #[tokio::test]
+    async fn calendars_leave_alerts_out_on_request() {
+        let mut upstream = mockito::Server::new_async().await;
+        let _point = mock_point(&mut upstream);
+        let _forecast = mock_forecast(&mut upstream);
+        let alerts = mock_alerts(&mut upstream).expect(1);
+        let base = start(&upstream).await;
+
+        let calendar = |query: &'static str| {
+            let base = base.clone();
+            async move {
+                let response = reqwest::get(format!(
+                    "{base}/forecast?lat=47.6&lon=-122.3&format=ics{query}"
+                ))
+                .await
+                .unwrap();
+                assert_eq!(response.status(), 200, "{query}");
+                response.text().await.unwrap()
+            }
+        };
+        assert!(calendar("").await.contains("Wind Advisory"));
+        assert!(!calendar("&alerts=false").await.contains("Wind Advisory"));
+        alerts.assert();
+
+        let response = reqwest::get(format!(
+            "{base}/forecast?lat=47.6&lon=-122.3&format=ics&alerts=no"
+        ))
+        .await
+        .unwrap();
+        assert_eq!(response.status(), 400);
+    }
+
+    #[tokio::test]
+    async fn alerts_are_listed_as_json() {
+        let mut upstream = mockito::Server::new_async().await;
+        let mock = mock_alerts(&mut upstream);
+        let base = start(&upstream).await;
+
+        let response = reqwest::get(format!("{base}/alerts?lat=47.6&lon=-122.3"))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), 200);
+        let json: serde_json::Value = response.json().await.unwrap();
+        assert_eq!(json[0]["severity"], "Moderate");
+        mock.assert();
+    }
+
+    #[tokio::test]
+    async fn bad_queries_are_400s_with_a_message() {
+        let upstream = mockito::Server::new_async().await;
+        let base = start(&upstream).await;
+
+        for (query, expected) in [
+            ("", "Give a place"),
+            ("zip=981", "not a valid US ZIP code"),
+            ("zip=98101&type=daily", "Unknown type 'daily'"),
+            ("zip=98101&format=pdf", "Unknown format 'pdf'"),
+            ("zip=98101&type=hourly&format=ics", "detailed forecast"),
+            ("lat=abc&lon=-122.3", "decimal degrees"),
+            ("lat=91&lon=-122.3", "decimal degrees"),
+            ("lat=47.6%2Fstations&lon=-122.3", "decimal degrees"),
+        ] {
+            let response = reqwest::get(format!("{base}/forecast?{query}"))
+                .await
+                .unwrap();
+            assert_eq!(response.status(), 400, "{query}");
+            let json: serde_json::Value = response.json().await.unwrap();
+            let error = json["error"].as_str().unwrap();
+            assert!(error.contains(expected), "{query}: {error}");
+        }
+
+        // Nothing unchecked reaches the upstream URL, for alerts either.
+        let response = reqwest::get(format!("{base}/alerts?lat=47.6%3Fx%3D1&lon=-122.3"))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), 400);
+    }
+
+    #[tokio::test]
+    async fn upstream_failures_are_502s() {
+        let mut upstream = mockito::Server::new_async().await;
+        let _point = upstream
+            .mock("GET", "/points/47.6,-122.3")
+            .with_status(500)
+            .with_body("upstream exploded")
+            .create();
+        let base = start(&upstream).await;
+
+        let response = reqwest::get(format!("{base}/forecast?lat=47.6&lon=-122.3"))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), 502);
+        let json: serde_json::Value = response.json().await.unwrap();
+        assert!(json["error"]
+            .as_str()
+            .unwrap()
+            .contains("upstream exploded"));
+    }
+}
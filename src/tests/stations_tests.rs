@@ -0,0 +1,74 @@
+#[cfg(test)]
+mod tests {
+    use crate::dirs;
+    use crate::stations::{bearing, compass, distance_km, parse_station, Choices};
+    use std::path::PathBuf;
+
+    const SEATTLE: (f64, f64) = (47.6062, -122.3321);
+    const PORTLAND: (f64, f64) = (45.5152, -122.6784);
+
+    /// A state file path no other test uses, with nothing in it yet.
+    fn state_file(name: &str) -> PathBuf {
+        let dir =
+            std::env::temp_dir().join(format!("wxdotgov-stations-{}-{name}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        dir.join("stations.json")
+    }
+
+    #[test]
+    fn distance_is_great_circle() {
+        assert_eq!(distance_km(SEATTLE, SEATTLE), 0.0);
+        let km = distance_km(SEATTLE, PORTLAND);
+        assert!((km - 233.5).abs() < 1.0, "{km}");
+        assert!((distance_km(PORTLAND, SEATTLE) - km).abs() < 1e-9);
+    }
+
+    #[test]
+    fn bearing_is_clockwise_from_north() {
+        let south = bearing(SEATTLE, PORTLAND);
+        assert!((south - 186.0).abs() < 1.0, "{south}");
+        let north = bearing(PORTLAND, SEATTLE);
+        assert!(north > 0.0 && north < 10.0, "{north}");
+        assert!((bearing((0.0, 0.0), (0.0, 1.0)) - 90.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn compass_rounds_to_the_nearest_of_sixteen_points() {
+        assert_eq!(compass(0.0), "N");
+        assert_eq!(compass(11.0), "N");
+        assert_eq!(compass(12.0), "NNE");
+        assert_eq!(compass(186.0), "S");
+        assert_eq!(compass(202.5), "SSW");
+        assert_eq!(compass(350.0), "N");
+        assert_eq!(compass(-90.0), "W");
+    }
+
+    #[test]
+    fn station_ids_are_checked_and_uppercased() {
+        assert_eq!(parse_station("ksea").unwrap(), "KSEA");
+        assert_eq!(parse_station(" KBFI ").unwrap(), "KBFI");
+        assert_eq!(parse_station("SNUW1").unwrap(), "SNUW1");
+        for bad in ["", "KS", "K-SEA", "ABCDEFGHI"] {
+            let err = parse_station(bad).unwrap_err().to_string();
+            assert!(err.contains("wxdotgov stations"), "{err}");
+        }
+    }
+
+    #[test]
+    fn choices_survive_a_save_and_load() {
+        let path = state_file("roundtrip");
+        assert!(dirs::load_state::<Choices>(&path).unwrap().is_empty());
+
+        let mut choices = Choices::new();
+        choices.insert("98101".to_string(), "KBFI".to_string());
+        choices.insert("Seattle, WA".to_string(), "KSEA".to_string());
+        dirs::save_state(&path, &choices).unwrap();
+        assert_eq!(dirs::load_state::<Choices>(&path).unwrap(), choices);
+        let leftovers = std::fs::read_dir(path.parent().unwrap()).unwrap().count();
+        assert_eq!(leftovers, 1, "a temporary file was left behind");
+
+        std::fs::write(&path, "not json").unwrap();
+        let err = dirs::load_state::<Choices>(&path).unwrap_err().to_string();
+        assert!(err.contains("Error parsing state file"), "{err}");
+    }
+}
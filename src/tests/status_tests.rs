@@ -0,0 +1,156 @@
+#[cfg(test)]
+mod tests {
+    use crate::render::{RenderOptions, Style};
+    use crate::status::{
+        condition_class, current_period, render_line, render_waybar, LineTemplate,
+    };
+    use crate::times::{DisplayZone, TimeDisplay, TimeFormat};
+    use crate::units::{QuantitativeValue, Units};
+    use crate::weatherdotgov::{Alert, HourlyPeriod, Severity};
+    use chrono::{DateTime, FixedOffset, TimeDelta, Utc};
+
+    fn at(rfc3339: &str) -> DateTime<FixedOffset> {
+        DateTime::parse_from_rfc3339(rfc3339).unwrap()
+    }
+
+    fn periods() -> Vec<HourlyPeriod> {
+        let start = at("2024-01-28T18:00:00-08:00");
+        (0..12)
+            .map(|i| HourlyPeriod {
+                start_time: start + TimeDelta::hours(i),
+                end_time: start + TimeDelta::hours(i + 1),
+                is_daytime: false,
+                temperature: 51 - i as i32,
+                temperature_unit: "F".to_string(),
+                wind_speed: "5 mph".to_string(),
+                wind_direction: "SSW".to_string(),
+                short_forecast: format!("Chance Light Rain {i}"),
+                dewpoint: Some(QuantitativeValue {
+                    value: Some(5.0),
+                    unit_code: "wmoUnit:degC".to_string(),
+                }),
+                probability_of_precipitation: None,
+                icon: None,
+            })
+            .collect()
+    }
+
+    /// "Now" is 8:15 PM, inside the third period.
+    fn opts() -> RenderOptions {
+        RenderOptions {
+            style: Style::Plain,
+            limit: None,
+            units: Units::Us,
+            times: TimeDisplay {
+                format: TimeFormat::TwelveHour,
+                zone: DisplayZone::Location,
+                now: at("2024-01-28T20:15:00-08:00").with_timezone(&Utc),
+            },
+        }
+    }
+
+    fn alert(event: &str, severity: Severity) -> Alert {
+        Alert {
+            id: format!("urn:test:{event}"),
+            event: event.to_string(),
+            headline: None,
+            severity,
+            onset: None,
+            ends: None,
+            message_type: None,
+            references: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn renders_fields_from_the_period_in_progress() {
+        let template: LineTemplate = "{temp}°{unit} {short} {wind} @ {time}".parse().unwrap();
+        let line = render_line(&periods(), &template, &opts()).unwrap();
+        assert_eq!(line, "49°F Chance Light Rain 2 5 mph SSW @ Sun 8 PM\n");
+    }
+
+    #[test]
+    fn falls_back_to_the_first_period_when_none_is_current() {
+        let mut later = opts();
+        later.times.now = Utc::now();
+        let periods = periods();
+        let current = current_period(&periods, &later).unwrap();
+        assert!(std::ptr::eq(current, &periods[0]));
+    }
+
+    #[test]
+    fn converts_values_and_blanks_missing_ones() {
+        let template: LineTemplate = "[{dewpoint}][{pop}]".parse().unwrap();
+        let line = render_line(&periods(), &template, &opts()).unwrap();
+        assert_eq!(line, "[41°F][]\n");
+    }
+
+    #[test]
+    fn braces_can_be_escaped() {
+        let template: LineTemplate = "{{{temp}}}".parse().unwrap();
+        assert_eq!(
+            render_line(&periods(), &template, &opts()).unwrap(),
+            "{49}\n"
+        );
+    }
+
+    #[test]
+    fn rejects_unknown_fields_and_stray_braces() {
+        let err = "{temperature}".parse::<LineTemplate>().unwrap_err();
+        assert!(format!("{err}").contains("unknown field '{temperature}'"));
+        assert!("{temp".parse::<LineTemplate>().is_err());
+        assert!("temp}".parse::<LineTemplate>().is_err());
+    }
+
+    #[test]
+    fn an_empty_forecast_is_an_error_not_a_blank_line() {
+        let template: LineTemplate = "{temp}".parse().unwrap();
+        assert!(render_line(&[], &template, &opts()).is_err());
+    }
+
+    #[test]
+    fn classifies_conditions_most_specific_first() {
+        for (short, class) in [
+            ("Chance Showers And Thunderstorms", "thunderstorm"),
+            ("Rain And Snow", "snow"),
+            ("Light Rain Likely", "rain"),
+            ("Patchy Fog", "fog"),
+            ("Partly Cloudy", "cloudy"),
+            ("Mostly Sunny", "clear"),
+            ("Blowing Dust", "unknown"),
+        ] {
+            assert_eq!(condition_class(short), class, "{short}");
+        }
+    }
+
+    #[test]
+    fn waybar_json_carries_text_tooltip_and_class() {
+        let template: LineTemplate = "{temp}°{unit}".parse().unwrap();
+        let out = render_waybar(&periods(), &[], "Seattle, WA", &template, &opts()).unwrap();
+        let json: serde_json::Value = serde_json::from_str(&out).unwrap();
+
+        assert_eq!(json["text"], "49°F");
+        assert_eq!(json["class"], "rain");
+        let tooltip = json["tooltip"].as_str().unwrap();
+        assert!(tooltip.starts_with("Seattle, WA\n"), "{tooltip}");
+        assert!(tooltip.contains("Sun 8 PM  49°F"), "{tooltip}");
+        // The tooltip starts at the current hour, not the first period.
+        assert!(!tooltip.contains("Sun 6 PM"), "{tooltip}");
+        assert_eq!(tooltip.lines().count(), 1 + 6);
+    }
+
+    #[test]
+    fn waybar_class_is_the_most_severe_alert() {
+        let alerts = [
+            alert("Wind Advisory", Severity::Moderate),
+            alert("Flood Warning", Severity::Severe),
+        ];
+        let template: LineTemplate = "{temp}".parse().unwrap();
+        let out = render_waybar(&periods(), &alerts, "Seattle", &template, &opts()).unwrap();
+        let json: serde_json::Value = serde_json::from_str(&out).unwrap();
+
+        assert_eq!(json["class"], "alert-severe");
+        let tooltip = json["tooltip"].as_str().unwrap();
+        assert!(tooltip.contains("Wind Advisory") && tooltip.contains("Flood Warning"));
+    }
+}
@@ -0,0 +1,108 @@
+#[cfg(test)]
+mod tests {
+    use crate::http;
+    use crate::tape::{self, Exchange, RecordedRequest};
+    use mockito::Server;
+    use std::path::PathBuf;
+
+    fn temp_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("wxdotgov-{name}-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        dir
+    }
+
+    fn request(url: &str) -> reqwest::Request {
+        http::client()
+            .unwrap()
+            .get(url)
+            .query(&[("units", "us")])
+            .header("Accept", "application/geo+json")
+            .build()
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn a_recorded_exchange_replays_without_the_server() {
+        let dir = temp_dir("tape-replay");
+        let mut server = Server::new_async().await;
+        let mock = server
+            .mock("GET", "/gridpoints/SEW/115,68/forecast")
+            .match_query(mockito::Matcher::Any)
+            .with_status(200)
+            .with_header("etag", "\"abc\"")
+            .with_body(r#"{"properties":{"periods":[]}}"#)
+            .expect(1)
+            .create();
+        let url = format!("{}/gridpoints/SEW/115,68/forecast", server.url());
+
+        let sent = request(&url);
+        let recorded = RecordedRequest::of(&sent);
+        let live = http::client().unwrap().execute(sent).await.unwrap();
+        let response = tape::record(&dir, recorded, live).await.unwrap();
+        // The caller still gets the body it would have read live.
+        assert_eq!(
+            response.text().await.unwrap(),
+            r#"{"properties":{"periods":[]}}"#
+        );
+
+        let replayed = tape::replay(&dir, &RecordedRequest::of(&request(&url))).unwrap();
+        assert_eq!(replayed.status(), 200);
+        assert_eq!(replayed.headers()["etag"], "\"abc\"");
+        assert_eq!(
+            replayed.text().await.unwrap(),
+            r#"{"properties":{"periods":[]}}"#
+        );
+        mock.assert();
+
+        // One readable file, carrying the request that fetched it.
+        let files: Vec<_> = std::fs::read_dir(&dir).unwrap().collect();
+        assert_eq!(files.len(), 1);
+        let path = files[0].as_ref().unwrap().path();
+        let name = path.file_name().unwrap().to_string_lossy().into_owned();
+        assert!(name.contains("gridpoints-SEW-115-68-forecast"), "{name}");
+        let exchange: Exchange =
+            serde_json::from_str(&std::fs::read_to_string(&path).unwrap()).unwrap();
+        assert!(exchange.request.url.ends_with("/forecast?units=us"));
+        assert_eq!(exchange.request.headers["accept"], "application/geo+json");
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[tokio::test]
+    async fn an_unrecorded_request_is_an_error_naming_it() {
+        let dir = temp_dir("tape-miss");
+        std::fs::create_dir_all(&dir).unwrap();
+
+        let url = "https://api.weather.gov/points/47.6,-122.3";
+        let err = tape::replay(&dir, &RecordedRequest::of(&request(url)))
+            .expect_err("nothing was recorded");
+        let msg = format!("{err}");
+        assert!(msg.contains("No recording of GET"), "{msg}");
+        assert!(msg.contains("/points/47.6,-122.3?units=us"), "{msg}");
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[tokio::test]
+    async fn error_responses_are_recorded_too() {
+        let dir = temp_dir("tape-error");
+        let mut server = Server::new_async().await;
+        server
+            .mock("GET", "/points/0,0")
+            .match_query(mockito::Matcher::Any)
+            .with_status(404)
+            .with_body("Not Found")
+            .create();
+        let url = format!("{}/points/0,0", server.url());
+
+        let sent = request(&url);
+        let recorded = RecordedRequest::of(&sent);
+        let live = http::client().unwrap().execute(sent).await.unwrap();
+        tape::record(&dir, recorded, live).await.unwrap();
+
+        let replayed = tape::replay(&dir, &RecordedRequest::of(&request(&url))).unwrap();
+        assert_eq!(replayed.status(), 404);
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,344 @@
+#[cfg(test)]
+mod tests {
+    use crate::render::{
+        limited, render_detailed, render_hourly, truncation_lines, RenderOptions, Style,
+    };
+    use crate::template::{
+        render_layout, render_template, Place, TemplateContext, TemplateSource, DETAILED,
+    };
+    use crate::times::{DisplayZone, TimeDisplay, TimeFormat};
+    use crate::units::{QuantitativeValue, Units};
+    use crate::weatherdotgov::{Alert, HourlyPeriod, Issuance, Period, PointsProperties, Severity};
+    use chrono::{DateTime, FixedOffset, TimeDelta, Utc};
+    use serde::Serialize;
+
+    fn start() -> DateTime<FixedOffset> {
+        DateTime::parse_from_rfc3339("2024-01-28T00:00:00-08:00").unwrap()
+    }
+
+    fn detailed_periods(count: usize) -> Vec<Period> {
+        (0..count)
+            .map(|i| Period {
+                name: format!("Period{i}"),
+                start_time: start() + TimeDelta::hours(12 * i as i64),
+                end_time: start() + TimeDelta::hours(12 * (i as i64 + 1)),
+                is_daytime: i % 2 == 1,
+                temperature: None,
+                short_forecast: None,
+                detailed_forecast: format!("Forecast text {i}"),
+                icon: None,
+            })
+            .collect()
+    }
+
+    /// Enough hours to cross midnight, with the optional values present on
+    /// some periods and absent on others.
+    fn hourly_periods(count: usize) -> Vec<HourlyPeriod> {
+        (0..count)
+            .map(|i| HourlyPeriod {
+                start_time: start() + TimeDelta::hours(20 + i as i64),
+                end_time: start() + TimeDelta::hours(21 + i as i64),
+                is_daytime: false,
+                temperature: 50 + i as i32,
+                temperature_unit: "F".to_string(),
+                wind_speed: format!("{i} mph"),
+                wind_direction: "SSW".to_string(),
+                short_forecast: format!("Short forecast {i}"),
+                dewpoint: (i % 2 == 0).then(|| QuantitativeValue {
+                    value: Some(i as f64),
+                    unit_code: "wmoUnit:degC".to_string(),
+                }),
+                probability_of_precipitation: (i % 3 == 0).then(|| QuantitativeValue {
+                    value: Some(10.0 * i as f64),
+                    unit_code: "wmoUnit:percent".to_string(),
+                }),
+                icon: None,
+            })
+            .collect()
+    }
+
+    fn point() -> PointsProperties {
+        PointsProperties {
+            grid_id: "SEW".to_string(),
+            grid_x: 115,
+            grid_y: 68,
+            forecast: "https://api.weather.gov/gridpoints/SEW/115,68/forecast".to_string(),
+            forecast_hourly: None,
+            ..Default::default()
+        }
+    }
+
+    fn opts(limit: Option<usize>) -> RenderOptions {
+        RenderOptions {
+            style: Style::Plain,
+            limit,
+            units: Units::Us,
+            times: TimeDisplay {
+                format: TimeFormat::TwelveHour,
+                zone: DisplayZone::Location,
+                now: Utc::now(),
+            },
+        }
+    }
+
+    fn render<T: Serialize>(
+        source: &TemplateSource,
+        forecast_type: &str,
+        periods: &[T],
+        alerts: &[Alert],
+        filtered: usize,
+        opts: &RenderOptions,
+    ) -> anyhow::Result<String> {
+        let point = point();
+        let shown = limited(periods, opts.limit);
+        let context = TemplateContext {
+            location: Place {
+                name: "Seattle, WA",
+                lat: "47.6",
+                lon: "-122.3",
+            },
+            point: &point,
+            alerts,
+            forecast_type,
+            issuance: &Issuance::default(),
+            periods: shown,
+            filtered,
+            withheld: periods.len() - shown.len(),
+            notes: truncation_lines(filtered, periods.len(), shown.len()),
+        };
+        render_template(source, &context, opts)
+    }
+
+    fn bundled(name: &str) -> TemplateSource {
+        let source: TemplateSource = name.parse().unwrap();
+        assert!(matches!(source, TemplateSource::Bundled(_)), "{name}");
+        source
+    }
+
+    /// `render_detailed` and `render_hourly` render the bundled templates
+    /// with only the periods; `--template` renders them with the whole
+    /// fetched context. Both must print the same thing, in either style.
+    #[test]
+    fn bundled_detailed_is_the_forecast_layout_in_both_styles() {
+        let periods = detailed_periods(6);
+        for style in [Style::Plain, Style::Pretty] {
+            for (filtered, limit) in [(0, None), (0, Some(4)), (3, None), (2, Some(1))] {
+                let opts = RenderOptions {
+                    style,
+                    ..opts(limit)
+                };
+                let expected = render_detailed(&periods, filtered, &opts).unwrap();
+                let actual = render(
+                    &bundled("detailed"),
+                    "detailed",
+                    &periods,
+                    &[],
+                    filtered,
+                    &opts,
+                )
+                .unwrap();
+                assert_eq!(
+                    actual, expected,
+                    "{style:?}, filtered {filtered}, limit {limit:?}"
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn bundled_hourly_is_the_hourly_layout_in_both_styles() {
+        let periods = hourly_periods(8);
+        for style in [Style::Plain, Style::Pretty] {
+            for (filtered, limit) in [(0, None), (0, Some(5)), (4, Some(2))] {
+                let opts = RenderOptions {
+                    style,
+                    ..opts(limit)
+                };
+                let expected = render_hourly(&periods, filtered, &opts).unwrap();
+                let actual =
+                    render(&bundled("hourly"), "hourly", &periods, &[], filtered, &opts).unwrap();
+                assert_eq!(
+                    actual, expected,
+                    "{style:?}, filtered {filtered}, limit {limit:?}"
+                );
+            }
+        }
+    }
+
+    /// The two styles lay the same fields out differently: the plain layout
+    /// on one line, the pretty one with the name or time on a line of its own.
+    #[test]
+    fn the_layouts_differ_by_style_in_arrangement_only() {
+        let pretty = RenderOptions {
+            style: Style::Pretty,
+            ..opts(None)
+        };
+        let detailed = detailed_periods(1);
+        assert_eq!(
+            render_detailed(&detailed, 0, &opts(None)).unwrap(),
+            "\nWeather Forecast:\n\nPeriod0: Forecast text 0\n-------------------------------------\n"
+        );
+        assert_eq!(
+            render_detailed(&detailed, 0, &pretty).unwrap(),
+            "\nWeather Forecast:\n\nPeriod0\nForecast text 0\n-------------------------------------\n"
+        );
+
+        let hourly = hourly_periods(1);
+        let line = "50°F | Short forecast 0 | Wind: 0 mph SSW | Dewpoint: 32°F | Precip: 0%\n";
+        let plain = render_hourly(&hourly, 0, &opts(None)).unwrap();
+        assert!(
+            plain.contains(&format!("== Sunday, January 28 ==\nSun 8 PM: {line}")),
+            "{plain}"
+        );
+        let pretty = render_hourly(&hourly, 0, &pretty).unwrap();
+        assert!(
+            pretty.contains(&format!("\nSunday, January 28\nSun 8 PM\n{line}")),
+            "{pretty}"
+        );
+    }
+
+    #[test]
+    fn slack_template_lists_alerts_and_periods() {
+        let alerts = [Alert {
+            id: "urn:test:wind".to_string(),
+            event: "Wind Advisory".to_string(),
+            headline: Some("Wind Advisory until 6 PM".to_string()),
+            severity: Severity::Moderate,
+            onset: None,
+            ends: None,
+            message_type: None,
+            references: Vec::new(),
+        }];
+        let out = render(
+            &bundled("slack"),
+            "detailed",
+            &detailed_periods(2),
+            &alerts,
+            0,
+            &opts(None),
+        )
+        .unwrap();
+
+        assert!(
+            out.starts_with("*Detailed forecast for Seattle, WA*\n"),
+            "{out}"
+        );
+        assert!(
+            out.contains(":warning: *Wind Advisory*: Wind Advisory until 6 PM"),
+            "{out}"
+        );
+        assert!(out.contains("• *Period1*: Forecast text 1"), "{out}");
+    }
+
+    #[test]
+    fn file_templates_see_the_grid_point_and_filters() {
+        let dir = std::env::temp_dir().join(format!("wxdotgov-template-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("custom.j2");
+        std::fs::write(
+            &path,
+            "{{ point.gridId }}/{{ point.gridX }},{{ point.gridY }}\n\
+             {% for p in periods %}{{ p.startTime | time }} {{ p.dewpoint | measure }}\n{% endfor %}",
+        )
+        .unwrap();
+
+        let source: TemplateSource = path.to_str().unwrap().parse().unwrap();
+        let out = render(&source, "hourly", &hourly_periods(1), &[], 0, &opts(None)).unwrap();
+        assert_eq!(out, "SEW/115,68\nSun 8 PM 32°F\n");
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn misspelt_fields_are_errors_not_blanks() {
+        let dir = std::env::temp_dir().join(format!("wxdotgov-strict-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("typo.j2");
+        std::fs::write(
+            &path,
+            "{% for p in periods %}{{ p.shortForcast }}{% endfor %}",
+        )
+        .unwrap();
+
+        let source: TemplateSource = path.to_str().unwrap().parse().unwrap();
+        let result = render(&source, "hourly", &hourly_periods(1), &[], 0, &opts(None));
+        assert!(result.is_err(), "got {result:?}");
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn a_layout_over_the_wrong_periods_is_an_error() {
+        let err = render_layout(&DETAILED, &hourly_periods(1), 0, &opts(None)).unwrap_err();
+        assert!(
+            format!("{err:#}").contains("Error rendering template detailed"),
+            "{err:#}"
+        );
+        let out = render(
+            &bundled("hourly"),
+            "detailed",
+            &detailed_periods(1),
+            &[],
+            0,
+            &opts(None),
+        );
+        assert!(out.is_err(), "{out:?}");
+    }
+
+    #[test]
+    fn paint_colours_only_what_it_knows() {
+        let dir = std::env::temp_dir().join(format!("wxdotgov-paint-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("paint.j2");
+        std::fs::write(
+            &path,
+            "{{ 'hot' | paint('temperature') }} {{ 'pretty' if pretty else 'plain' }}",
+        )
+        .unwrap();
+        let source: TemplateSource = path.to_str().unwrap().parse().unwrap();
+        let out = render(
+            &source,
+            "detailed",
+            &detailed_periods(0),
+            &[],
+            0,
+            &opts(None),
+        );
+        assert_eq!(out.unwrap(), "hot plain");
+
+        std::fs::write(&path, "{{ 'hot' | paint('loud') }}").unwrap();
+        let err = render(
+            &source,
+            "detailed",
+            &detailed_periods(0),
+            &[],
+            0,
+            &opts(None),
+        );
+        let err = format!("{:#}", err.unwrap_err());
+        assert!(
+            err.contains("'loud' is not something paint colours"),
+            "{err}"
+        );
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn a_missing_template_file_is_reported_by_path() {
+        let source: TemplateSource = "/nonexistent/forecast.j2".parse().unwrap();
+        let err = render(
+            &source,
+            "detailed",
+            &detailed_periods(1),
+            &[],
+            0,
+            &opts(None),
+        )
+        .unwrap_err();
+        assert!(
+            format!("{err}").contains("/nonexistent/forecast.j2"),
+            "{err}"
+        );
+    }
+}
@@ -0,0 +1,213 @@
+#[cfg(test)]
+mod tests {
+    use crate::times::{
+        parse_interval, parse_valid_time, zone_offset, DisplayZone, TimeDisplay, TimeFormat,
+        TimeSpec,
+    };
+    use chrono::{DateTime, FixedOffset, TimeDelta, Utc};
+
+    fn at(rfc3339: &str) -> DateTime<FixedOffset> {
+        DateTime::parse_from_rfc3339(rfc3339).unwrap()
+    }
+
+    fn display(format: TimeFormat, now: &str) -> TimeDisplay {
+        TimeDisplay {
+            format,
+            zone: DisplayZone::Location,
+            now: at(now).with_timezone(&Utc),
+        }
+    }
+
+    #[test]
+    fn iso_reproduces_what_the_api_sent() {
+        let t = at("2024-01-28T18:00:00-08:00");
+        let d = display(TimeFormat::Iso, "2024-01-28T12:00:00-08:00");
+        assert_eq!(d.label(&t), "2024-01-28T18:00:00-08:00");
+    }
+
+    #[test]
+    fn clock_formats_use_the_location_offset() {
+        // 02:00 UTC on the 29th is still Sunday evening in Seattle.
+        let t = at("2024-01-28T18:00:00-08:00");
+        let now = "2024-01-28T12:00:00-08:00";
+        assert_eq!(display(TimeFormat::TwelveHour, now).label(&t), "Sun 6 PM");
+        assert_eq!(
+            display(TimeFormat::TwentyFourHour, now).label(&t),
+            "Sun 18:00"
+        );
+    }
+
+    #[test]
+    fn twelve_hour_shows_minutes_only_when_needed() {
+        let d = display(TimeFormat::TwelveHour, "2024-01-28T12:00:00-08:00");
+        assert_eq!(d.label(&at("2024-01-28T06:30:00-08:00")), "Sun 6:30 AM");
+    }
+
+    #[test]
+    fn relative_labels_count_from_now() {
+        let d = display(TimeFormat::Relative, "2024-01-28T12:00:00-08:00");
+        let cases = [
+            ("2024-01-28T12:00:00-08:00", "now"),
+            ("2024-01-28T15:00:00-08:00", "in 3h"),
+            ("2024-01-29T14:00:00-08:00", "in 1d 2h"),
+            ("2024-01-30T12:00:00-08:00", "in 2d"),
+            ("2024-01-28T10:00:00-08:00", "2h ago"),
+            // Offsets differ but the instant is 1h ahead.
+            ("2024-01-28T21:00:00+00:00", "in 1h"),
+        ];
+        for (t, want) in cases {
+            assert_eq!(d.label(&at(t)), want, "{t}");
+        }
+    }
+
+    #[test]
+    fn day_breaks_fall_on_calendar_days_in_the_display_zone() {
+        let d = display(TimeFormat::Iso, "2024-01-28T12:00:00-08:00");
+        let evening = at("2024-01-28T23:00:00-08:00");
+        let midnight = at("2024-01-29T00:00:00-08:00");
+
+        assert_eq!(
+            d.day_break(None, &evening).as_deref(),
+            Some("Sunday, January 28")
+        );
+        assert_eq!(d.day_break(Some(&evening), &evening), None);
+        assert_eq!(
+            d.day_break(Some(&evening), &midnight).as_deref(),
+            Some("Monday, January 29")
+        );
+    }
+
+    #[test]
+    fn parses_named_relative_and_absolute_time_specs() {
+        assert_eq!("now".parse::<TimeSpec>().unwrap(), TimeSpec::Now);
+        assert_eq!("Tonight".parse::<TimeSpec>().unwrap(), TimeSpec::Tonight);
+        assert_eq!(
+            "+6h".parse::<TimeSpec>().unwrap(),
+            TimeSpec::Ahead(TimeDelta::hours(6))
+        );
+        assert_eq!(
+            "+2d".parse::<TimeSpec>().unwrap(),
+            TimeSpec::Ahead(TimeDelta::days(2))
+        );
+        assert_eq!(
+            "2024-01-28T18:00:00-08:00".parse::<TimeSpec>().unwrap(),
+            TimeSpec::At(at("2024-01-28T18:00:00-08:00"))
+        );
+        assert!(matches!(
+            "2024-01-28T18:00".parse::<TimeSpec>().unwrap(),
+            TimeSpec::Wall(_)
+        ));
+        assert!(matches!(
+            "2024-01-28".parse::<TimeSpec>().unwrap(),
+            TimeSpec::Wall(_)
+        ));
+    }
+
+    #[test]
+    fn rejects_unreadable_time_specs() {
+        for bad in [
+            "",
+            "later",
+            "+6",
+            "+6w",
+            "+h",
+            "28/01/2024",
+            "+é",
+            "+5é",
+            "+-5h",
+            "++5h",
+            "+9999999999999999h",
+            "+99999999999999999999d",
+        ] {
+            assert!(bad.parse::<TimeSpec>().is_err(), "accepted {bad:?}");
+        }
+    }
+
+    #[test]
+    fn relative_times_past_the_calendar_are_an_error() {
+        let now = at("2024-01-28T09:30:00-08:00").with_timezone(&Utc);
+        let offset = *at("2024-01-28T00:00:00-08:00").offset();
+        // Within what a TimeDelta holds, but not a date chrono can name.
+        let far = "+100000000000d".parse::<TimeSpec>().unwrap();
+        assert!(far.resolve(now, offset).is_err());
+    }
+
+    #[test]
+    fn named_specs_resolve_against_now_in_the_given_offset() {
+        let now = at("2024-01-28T09:30:00-08:00").with_timezone(&Utc);
+        let offset = *at("2024-01-28T00:00:00-08:00").offset();
+        let resolve = |s: &str| s.parse::<TimeSpec>().unwrap().resolve(now, offset).unwrap();
+
+        assert_eq!(resolve("today"), at("2024-01-28T00:00:00-08:00"));
+        assert_eq!(resolve("tonight"), at("2024-01-28T18:00:00-08:00"));
+        assert_eq!(resolve("tomorrow"), at("2024-01-29T00:00:00-08:00"));
+        assert_eq!(resolve("+6h"), at("2024-01-28T15:30:00-08:00"));
+        assert_eq!(resolve("2024-02-01T07:00"), at("2024-02-01T07:00:00-08:00"));
+    }
+
+    #[test]
+    fn intervals_take_seconds_minutes_or_hours() {
+        assert_eq!(parse_interval("90s").unwrap().as_secs(), 90);
+        assert_eq!(parse_interval("10m").unwrap().as_secs(), 600);
+        assert_eq!(parse_interval("1h").unwrap().as_secs(), 3600);
+        for bad in ["10", "m", "5d", "-1m", "+1m", ""] {
+            assert!(parse_interval(bad).is_err(), "{bad}");
+        }
+    }
+
+    #[test]
+    fn intervals_that_overflow_or_end_mid_character_are_errors() {
+        let err = parse_interval("99999999999999999h")
+            .unwrap_err()
+            .to_string();
+        assert!(err.contains("too long"), "{err}");
+        assert!(parse_interval("99999999999999999999s").is_err());
+        let err = parse_interval("5é").unwrap_err().to_string();
+        assert!(err.contains("unknown unit"), "{err}");
+        assert!(parse_interval("é").is_err());
+    }
+
+    #[test]
+    fn zone_offsets_follow_daylight_saving() {
+        let utc = |s: &str| s.parse::<DateTime<Utc>>().unwrap();
+        let winter = zone_offset("America/Los_Angeles", utc("2024-01-28T12:00:00Z")).unwrap();
+        assert_eq!(winter.local_minus_utc(), -8 * 3600);
+        let summer = zone_offset("America/Los_Angeles", utc("2024-07-15T12:00:00Z")).unwrap();
+        assert_eq!(summer.local_minus_utc(), -7 * 3600);
+        // Arizona keeps standard time all year.
+        let phoenix = zone_offset("America/Phoenix", utc("2024-07-15T12:00:00Z")).unwrap();
+        assert_eq!(phoenix.local_minus_utc(), -7 * 3600);
+        assert_eq!(
+            zone_offset("Mars/Olympus_Mons", utc("2024-07-15T12:00:00Z")),
+            None
+        );
+    }
+
+    #[test]
+    fn valid_times_are_a_start_and_a_duration() {
+        let (start, end) = parse_valid_time("2024-01-29T02:00:00+00:00/PT3H").unwrap();
+        assert_eq!(start, at("2024-01-29T02:00:00+00:00"));
+        assert_eq!(end - start, TimeDelta::hours(3));
+        let (start, end) = parse_valid_time("2024-01-29T02:00:00+00:00/P1DT6H30M").unwrap();
+        assert_eq!(end - start, TimeDelta::hours(30) + TimeDelta::minutes(30));
+        assert_eq!(
+            parse_valid_time("2024-01-29T02:00:00+00:00/P2D").map(|(s, e)| e - s),
+            Some(TimeDelta::days(2))
+        );
+        for bad in [
+            "2024-01-29T02:00:00+00:00",
+            "2024-01-29T02:00:00+00:00/P1M",
+            "x/PT1H",
+            "2024-01-29T02:00:00+00:00/3H",
+            "2024-01-29T02:00:00+00:00/PT-3H",
+            "2024-01-29T02:00:00+00:00/PT+3H",
+            "2024-01-29T02:00:00+00:00/PTH",
+            "2024-01-29T02:00:00+00:00/PT9999999999999999S",
+            "2024-01-29T02:00:00+00:00/P9999999999999999D",
+            "2024-01-29T02:00:00+00:00/P99999999999999999999D",
+            "2024-01-29T02:00:00+00:00/P100000000000D",
+        ] {
+            assert!(parse_valid_time(bad).is_none(), "{bad}");
+        }
+    }
+}
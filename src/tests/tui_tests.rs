@@ -0,0 +1,228 @@
+#[cfg(test)]
+mod tests {
+    use crate::stations::Choices;
+    use crate::tui::{targets, App, Tab};
+    use crate::units::Units;
+    use mockito::{Matcher, ServerGuard};
+    use ratatui::backend::TestBackend;
+    use ratatui::crossterm::event::KeyCode;
+    use ratatui::Terminal;
+
+    fn app(locations: &[&str]) -> App {
+        let targets = locations.iter().map(|l| l.parse().unwrap()).collect();
+        App::new(targets, Units::Us)
+    }
+
+    fn mock_upstream(upstream: &mut ServerGuard) {
+        let url = upstream.url();
+        upstream
+            .mock("GET", "/search")
+            .match_query(Matcher::Any)
+            .with_body(r#"[{"lat": "47.6", "lon": "-122.3", "display_name": "Seattle, WA"}]"#)
+            .create();
+        upstream
+            .mock("GET", "/points/47.6,-122.3")
+            .with_body(format!(
+                r#"{{"properties": {{
+                    "gridId": "SEW", "gridX": 115, "gridY": 68,
+                    "forecast": "{url}/gridpoints/SEW/115,68/forecast",
+                    "forecastHourly": "{url}/gridpoints/SEW/115,68/forecast/hourly"
+                }}}}"#
+            ))
+            .create();
+        upstream
+            .mock("GET", "/gridpoints/SEW/115,68/forecast")
+            .match_query(Matcher::Any)
+            .with_body(
+                r#"{"properties": {"periods": [{
+                    "name": "Tonight",
+                    "startTime": "2024-01-28T18:00:00-08:00",
+                    "endTime": "2024-01-29T06:00:00-08:00",
+                    "isDaytime": false,
+                    "detailedForecast": "Rain likely."
+                }]}}"#,
+            )
+            .create();
+        upstream
+            .mock("GET", "/gridpoints/SEW/115,68/forecast/hourly")
+            .match_query(Matcher::Any)
+            .with_body(
+                r#"{"properties": {"periods": [{
+                    "startTime": "2024-01-28T18:00:00-08:00",
+                    "endTime": "2024-01-28T19:00:00-08:00",
+                    "isDaytime": false,
+                    "temperature": 47,
+                    "temperatureUnit": "F",
+                    "windSpeed": "5 mph",
+                    "windDirection": "SSW",
+                    "shortForecast": "Light Rain"
+                }]}}"#,
+            )
+            .create();
+        upstream
+            .mock("GET", "/alerts/active")
+            .match_query(Matcher::Any)
+            .with_body(
+                r#"{"features": [{"properties": {
+                    "event": "Wind Advisory", "headline": "Wind Advisory until 6 PM",
+                    "severity": "Moderate"
+                }}]}"#,
+            )
+            .create();
+    }
+
+    /// The screen as text, one string per row.
+    fn screen(app: &App) -> String {
+        let mut terminal = Terminal::new(TestBackend::new(80, 24)).unwrap();
+        terminal.draw(|frame| app.draw(frame)).unwrap();
+        let buffer = terminal.backend().buffer();
+        (0..buffer.area.height)
+            .map(|y| {
+                (0..buffer.area.width)
+                    .map(|x| buffer[(x, y)].symbol())
+                    .collect::<String>()
+            })
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+
+    #[test]
+    fn keys_switch_tabs_and_locations() {
+        let mut app = app(&["98101", "Portland, OR"]);
+        app.handle_key(KeyCode::Char('4'));
+        assert_eq!(app.tab, Tab::Alerts);
+        app.handle_key(KeyCode::Tab);
+        assert_eq!(app.tab, Tab::Forecast);
+        app.handle_key(KeyCode::Left);
+        assert_eq!(app.tab, Tab::Alerts);
+
+        app.handle_key(KeyCode::Char('j'));
+        assert_eq!(app.scroll, 1);
+        app.handle_key(KeyCode::Char(']'));
+        assert_eq!((app.location, app.scroll), (1, 0));
+        app.handle_key(KeyCode::Char(']'));
+        assert_eq!(app.location, 0);
+        app.handle_key(KeyCode::Char('['));
+        assert_eq!(app.location, 1);
+
+        assert!(!app.quit);
+        app.handle_key(KeyCode::Char('q'));
+        assert!(app.quit);
+    }
+
+    #[test]
+    fn saved_locations_follow_the_given_ones() {
+        let mut saved = Choices::new();
+        for label in ["98101", "Tacoma", "47.6,-122.3", "Seattle, WA"] {
+            saved.insert(label.to_string(), "KSEA".to_string());
+        }
+        let given = vec!["Boise".parse().unwrap(), "98101".parse().unwrap()];
+        let labels: Vec<String> = targets(given, &saved)
+            .unwrap()
+            .into_iter()
+            .map(|t| t.label)
+            .collect();
+        assert_eq!(labels, ["Boise", "98101", "Seattle, WA", "Tacoma"]);
+
+        let labels: Vec<String> = targets(Vec::new(), &saved)
+            .unwrap()
+            .into_iter()
+            .map(|t| t.label)
+            .collect();
+        assert_eq!(labels, ["98101", "Seattle, WA", "Tacoma"]);
+
+        let err = targets(Vec::new(), &Choices::new())
+            .unwrap_err()
+            .to_string();
+        assert!(err.contains("stations --use"), "{err}");
+    }
+
+    #[test]
+    fn an_unfetched_location_shows_loading() {
+        let app = app(&["98101"]);
+        assert!(app.needs_load());
+        let screen = screen(&app);
+        assert!(screen.contains("Loading..."), "{screen}");
+        assert!(screen.contains("location 1/1"), "{screen}");
+    }
+
+    #[tokio::test]
+    async fn each_tab_draws_from_the_fetched_forecast() {
+        let mut upstream = mockito::Server::new_async().await;
+        mock_upstream(&mut upstream);
+        let mut app = app(&["98101"]);
+        app.nominatim_url = Some(upstream.url());
+        app.weather_url = Some(upstream.url());
+        app.load().await;
+        assert!(!app.needs_load());
+
+        for (key, expected) in [
+            ('1', "Rain likely."),
+            ('2', "Temperature (°F): high 47°"),
+            ('3', "47°F  Light Rain"),
+            ('4', "Wind Advisory until 6 PM"),
+        ] {
+            app.handle_key(KeyCode::Char(key));
+            let screen = screen(&app);
+            assert!(screen.contains(expected), "tab {key}:\n{screen}");
+            assert!(screen.contains("Seattle, WA"), "tab {key}:\n{screen}");
+        }
+
+        app.handle_key(KeyCode::Char('r'));
+        assert!(app.needs_load());
+    }
+
+    #[tokio::test]
+    async fn refresh_reloads_past_the_cache() {
+        let mut upstream = mockito::Server::new_async().await;
+        mock_upstream(&mut upstream);
+        let mut app = app(&["98101"]);
+        app.nominatim_url = Some(upstream.url());
+        app.weather_url = Some(upstream.url());
+        app.load().await;
+
+        let reloaded = upstream
+            .mock("GET", "/gridpoints/SEW/115,68/forecast")
+            .match_query(Matcher::Any)
+            .match_header("cache-control", "no-cache")
+            .with_body(
+                r#"{"properties": {"periods": [{
+                    "name": "Tonight",
+                    "startTime": "2024-01-28T18:00:00-08:00",
+                    "endTime": "2024-01-29T06:00:00-08:00",
+                    "isDaytime": false,
+                    "detailedForecast": "Clearing later."
+                }]}}"#,
+            )
+            .create_async()
+            .await;
+
+        // A plain load is served from the cache...
+        app.load().await;
+        let screen_text = screen(&app);
+        assert!(screen_text.contains("Rain likely."), "{screen_text}");
+
+        // ...while r asks the origin again.
+        app.handle_key(KeyCode::Char('r'));
+        app.load().await;
+        let screen_text = screen(&app);
+        assert!(screen_text.contains("Clearing later."), "{screen_text}");
+        reloaded.assert_async().await;
+    }
+
+    #[tokio::test]
+    async fn a_failed_fetch_is_shown_not_fatal() {
+        let mut upstream = mockito::Server::new_async().await;
+        upstream
+            .mock("GET", "/search")
+            .match_query(Matcher::Any)
+            .with_body("[]")
+            .create();
+        let mut app = app(&["98101"]);
+        app.nominatim_url = Some(upstream.url());
+        app.load().await;
+
+        let screen = screen(&app);
+        assert!(screen.contains("Error:"), "{screen}");
+    }
+}
@@ -0,0 +1,94 @@
+#[cfg(test)]
+mod tests {
+    use crate::units::{QuantitativeValue, SpeedUnit, Units, WindSpeed};
+
+    fn qv(value: f64, unit_code: &str) -> QuantitativeValue {
+        QuantitativeValue {
+            value: Some(value),
+            unit_code: unit_code.to_string(),
+        }
+    }
+
+    #[test]
+    fn parses_single_and_ranged_wind_speeds() {
+        assert_eq!(
+            "5 mph".parse::<WindSpeed>().unwrap(),
+            WindSpeed {
+                low: 5.0,
+                high: 5.0,
+                unit: SpeedUnit::Mph
+            }
+        );
+        assert_eq!(
+            "10 to 15 km/h".parse::<WindSpeed>().unwrap(),
+            WindSpeed {
+                low: 10.0,
+                high: 15.0,
+                unit: SpeedUnit::Kmh
+            }
+        );
+    }
+
+    #[test]
+    fn rejects_wind_speeds_it_cannot_read() {
+        for bad in ["", "calm", "5", "5 furlongs", "five mph", "5 to mph"] {
+            assert!(bad.parse::<WindSpeed>().is_err(), "accepted {bad:?}");
+        }
+    }
+
+    #[test]
+    fn wind_speed_round_trips_through_display_in_the_api_format() {
+        for text in ["5 mph", "5 to 10 mph", "15 km/h"] {
+            assert_eq!(text.parse::<WindSpeed>().unwrap().to_string(), text);
+        }
+    }
+
+    #[test]
+    fn converts_wind_ranges_between_systems() {
+        let mph: WindSpeed = "5 to 10 mph".parse().unwrap();
+        assert_eq!(mph.to_units(Units::Si).to_string(), "8 to 16 km/h");
+        assert_eq!(mph.to_units(Units::Us), mph);
+
+        let kmh: WindSpeed = "16 km/h".parse().unwrap();
+        assert_eq!(kmh.to_units(Units::Us).to_string(), "10 mph");
+    }
+
+    #[test]
+    fn knots_are_never_converted() {
+        let kt: WindSpeed = "10 to 15 kt".parse().unwrap();
+        assert_eq!(kt.to_units(Units::Us), kt);
+        assert_eq!(kt.to_units(Units::Si), kt);
+    }
+
+    #[test]
+    fn converts_observation_values_into_each_system() {
+        let cases = [
+            (qv(20.0, "wmoUnit:degC"), "68°F", "20°C"),
+            (qv(10.0, "wmoUnit:m_s-1"), "22 mph", "36 km/h"),
+            (qv(101_325.0, "wmoUnit:Pa"), "29.92 inHg", "1013 hPa"),
+            (qv(25.4, "wmoUnit:mm"), "1.00 in", "25 mm"),
+            (qv(55.0, "wmoUnit:percent"), "55%", "55%"),
+        ];
+        for (value, us, si) in cases {
+            assert_eq!(value.to_units(Units::Us).unwrap().to_string(), us);
+            assert_eq!(value.to_units(Units::Si).unwrap().to_string(), si);
+        }
+    }
+
+    #[test]
+    fn null_readings_convert_to_nothing() {
+        let missing = QuantitativeValue {
+            value: None,
+            unit_code: "wmoUnit:degC".to_string(),
+        };
+        assert_eq!(missing.to_units(Units::Us), None);
+    }
+
+    #[test]
+    fn unknown_unit_codes_pass_through() {
+        let odd = qv(3.0, "wmoUnit:furlong");
+        let m = odd.to_units(Units::Us).unwrap();
+        assert_eq!(m.value, 3.0);
+        assert_eq!(m.unit, "furlong");
+    }
+}
@@ -0,0 +1,188 @@
+#[cfg(test)]
+mod tests {
+    use crate::render::{RenderOptions, Style};
+    use crate::times::{DisplayZone, TimeDisplay, TimeFormat};
+    use crate::units::Units;
+    use crate::watch::{diff, render_changes, Change};
+    use crate::weatherdotgov::{Alert, AlertReference, HourlyPeriod, Period, Severity};
+    use chrono::{DateTime, FixedOffset, TimeDelta, Utc};
+
+    fn at(rfc3339: &str) -> DateTime<FixedOffset> {
+        DateTime::parse_from_rfc3339(rfc3339).unwrap()
+    }
+
+    /// Hourly periods from `first` (0 is 6 PM Sunday) to `last`, exclusive.
+    fn hours(first: i64, last: i64) -> Vec<HourlyPeriod> {
+        let start = at("2024-01-28T18:00:00-08:00");
+        (first..last)
+            .map(|i| HourlyPeriod {
+                start_time: start + TimeDelta::hours(i),
+                end_time: start + TimeDelta::hours(i + 1),
+                is_daytime: false,
+                temperature: 50,
+                temperature_unit: "F".to_string(),
+                wind_speed: "5 mph".to_string(),
+                wind_direction: "SSW".to_string(),
+                short_forecast: "Cloudy".to_string(),
+                dewpoint: None,
+                probability_of_precipitation: None,
+                icon: None,
+            })
+            .collect()
+    }
+
+    fn opts(style: Style) -> RenderOptions {
+        RenderOptions {
+            style,
+            limit: None,
+            units: Units::Us,
+            times: TimeDisplay {
+                format: TimeFormat::TwelveHour,
+                zone: DisplayZone::Location,
+                now: at("2024-01-28T18:15:00-08:00").with_timezone(&Utc),
+            },
+        }
+    }
+
+    fn alert(id: &str, event: &str) -> Alert {
+        Alert {
+            id: id.to_string(),
+            event: event.to_string(),
+            headline: None,
+            severity: Severity::Moderate,
+            onset: None,
+            ends: None,
+            message_type: None,
+            references: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn reports_temperature_and_forecast_changes_by_hour() {
+        let before = hours(0, 3);
+        let mut after = hours(0, 3);
+        after[1].temperature = 47;
+        after[2].short_forecast = "Rain Likely".to_string();
+
+        let changes = diff(&before, &after, &[], &[], &opts(Style::Plain).times);
+        assert_eq!(
+            changes,
+            vec![
+                Change::Temperature {
+                    period: "Sun 7 PM".to_string(),
+                    from: 50,
+                    to: 47,
+                },
+                Change::Forecast {
+                    period: "Sun 8 PM".to_string(),
+                    from: "Cloudy".to_string(),
+                    to: "Rain Likely".to_string(),
+                },
+            ]
+        );
+    }
+
+    /// An hour ending and a new one appearing is the list moving on, not the
+    /// forecast changing.
+    #[test]
+    fn periods_rolling_past_are_not_changes() {
+        let changes = diff(
+            &hours(0, 3),
+            &hours(1, 4),
+            &[],
+            &[],
+            &opts(Style::Plain).times,
+        );
+        assert!(changes.is_empty(), "{changes:?}");
+    }
+
+    #[test]
+    fn detailed_periods_are_named_and_compared_when_the_api_sends_values() {
+        let period = |temperature: Option<i32>| Period {
+            name: "Tonight".to_string(),
+            start_time: at("2024-01-28T18:00:00-08:00"),
+            end_time: at("2024-01-29T06:00:00-08:00"),
+            is_daytime: false,
+            temperature,
+            short_forecast: None,
+            detailed_forecast: "Rain.".to_string(),
+            icon: None,
+        };
+        let times = opts(Style::Plain).times;
+
+        let changes = diff(&[period(Some(41))], &[period(Some(44))], &[], &[], &times);
+        assert_eq!(
+            changes,
+            vec![Change::Temperature {
+                period: "Tonight".to_string(),
+                from: 41,
+                to: 44,
+            }]
+        );
+        assert!(diff(&[period(None)], &[period(Some(44))], &[], &[], &times).is_empty());
+    }
+
+    fn update(id: &str, event: &str, replaces: &[&str]) -> Alert {
+        Alert {
+            headline: Some(format!("{event} extended")),
+            message_type: Some("Update".to_string()),
+            references: replaces
+                .iter()
+                .map(|id| AlertReference {
+                    identifier: id.to_string(),
+                    sent: None,
+                })
+                .collect(),
+            ..alert(id, event)
+        }
+    }
+
+    #[test]
+    fn an_update_is_the_alert_it_replaces() {
+        let before = [
+            alert("urn:a", "Wind Advisory"),
+            alert("urn:b", "Flood Watch"),
+        ];
+        let after = [
+            update("urn:a2", "Wind Advisory", &["urn:a"]),
+            alert("urn:c", "Gale Warning"),
+        ];
+
+        let changes = diff::<HourlyPeriod>(&[], &[], &before, &after, &opts(Style::Plain).times);
+        assert_eq!(
+            changes,
+            vec![
+                Change::AlertIssued("Gale Warning".to_string()),
+                Change::AlertEnded("Flood Watch".to_string()),
+            ]
+        );
+
+        // A later update may name only the update before it.
+        let again = [update("urn:a3", "Wind Advisory", &["urn:a2"])];
+        let changes =
+            diff::<HourlyPeriod>(&[], &[], &after[..1], &again, &opts(Style::Plain).times);
+        assert!(changes.is_empty(), "{changes:?}");
+    }
+
+    #[test]
+    fn changes_list_in_both_styles() {
+        let changes = [
+            Change::Temperature {
+                period: "Sun 7 PM".to_string(),
+                from: 50,
+                to: 47,
+            },
+            Change::AlertIssued("Gale Warning".to_string()),
+        ];
+        for style in [Style::Plain, Style::Pretty] {
+            let out = render_changes(&changes, &opts(style));
+            assert!(out.contains("Changed since the last refresh:"), "{out}");
+            assert!(out.contains("Sun 7 PM: 50°F -> 47°F"), "{out}");
+            assert!(out.contains("New alert: Gale Warning"), "{out}");
+        }
+        assert_eq!(
+            render_changes(&[], &opts(Style::Plain)),
+            "No changes since the last refresh.\n"
+        );
+    }
+}
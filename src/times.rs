@@ -0,0 +1,331 @@
+//! How forecast timestamps are displayed.
+//!
+//! Every period from the API carries an ISO 8601 timestamp with an offset
+//! (`2024-01-28T18:00:00-08:00`). That offset is the forecast location's at
+//! that instant -- the zone `/points` reports as `timeZone` -- so showing times
+//! "in the location's zone" needs no time zone database: the offset the API
+//! sent is already the answer, DST transitions included. Only the user's own
+//! zone requires a conversion, which `chrono::Local` handles.
+
+use std::str::FromStr;
+
+use anyhow::{bail, Context, Result};
+use chrono::{
+    DateTime, Days, FixedOffset, Local, NaiveDate, NaiveDateTime, NaiveTime, TimeDelta, Timelike,
+    Utc,
+};
+use clap::ValueEnum;
+
+/// How a timestamp is written.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, ValueEnum)]
+pub enum TimeFormat {
+    /// RFC 3339, exactly as the API sends it (`2024-01-28T18:00:00-08:00`).
+    Iso,
+    /// Weekday and 12-hour clock (`Sun 6 PM`).
+    #[default]
+    #[value(name = "12h")]
+    TwelveHour,
+    /// Weekday and 24-hour clock (`Sun 18:00`).
+    #[value(name = "24h")]
+    TwentyFourHour,
+    /// Distance from now (`in 3h`, `2h ago`).
+    Relative,
+}
+
+/// Which zone timestamps are shown in.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, ValueEnum)]
+pub enum DisplayZone {
+    /// The forecast location's zone, as the API reports it.
+    #[default]
+    Location,
+    /// The zone of the machine running this tool.
+    Local,
+}
+
+/// Everything needed to turn a timestamp into a label.
+///
+/// `now` is carried rather than read from the clock so relative labels are
+/// deterministic in tests, and so one invocation labels every period against
+/// the same instant.
+#[derive(Clone, Copy, Debug)]
+pub struct TimeDisplay {
+    pub format: TimeFormat,
+    pub zone: DisplayZone,
+    pub now: DateTime<Utc>,
+}
+
+impl TimeDisplay {
+    pub fn new(format: TimeFormat, zone: DisplayZone) -> Self {
+        TimeDisplay {
+            format,
+            zone,
+            now: Utc::now(),
+        }
+    }
+
+    /// The offset wall-clock times are interpreted in: the location's, taken
+    /// from `reference` (any period's timestamp), or this machine's.
+    pub fn offset(&self, reference: Option<&DateTime<FixedOffset>>) -> FixedOffset {
+        match (self.zone, reference) {
+            (DisplayZone::Location, Some(t)) => *t.offset(),
+            _ => *self.now.with_timezone(&Local).offset(),
+        }
+    }
+
+    /// `t` re-expressed in the display zone.
+    pub fn in_zone(&self, t: &DateTime<FixedOffset>) -> DateTime<FixedOffset> {
+        match self.zone {
+            DisplayZone::Location => *t,
+            DisplayZone::Local => t.with_timezone(&Local).fixed_offset(),
+        }
+    }
+
+    /// The label a period is printed under.
+    pub fn label(&self, t: &DateTime<FixedOffset>) -> String {
+        let t = self.in_zone(t);
+        match self.format {
+            TimeFormat::Iso => t.to_rfc3339(),
+            TimeFormat::TwelveHour => {
+                // "Sun 6 PM" on the hour, which is every hourly period; the
+                // minutes only appear for the odd timestamp that needs them.
+                if t.minute() == 0 {
+                    t.format("%a %-I %p").to_string()
+                } else {
+                    t.format("%a %-I:%M %p").to_string()
+                }
+            }
+            TimeFormat::TwentyFourHour => t.format("%a %H:%M").to_string(),
+            TimeFormat::Relative => relative(t.with_timezone(&Utc) - self.now),
+        }
+    }
+
+    /// The calendar day `t` falls on in the display zone ("Sunday, January 28").
+    pub fn day_label(&self, t: &DateTime<FixedOffset>) -> String {
+        self.in_zone(t).format("%A, %B %-d").to_string()
+    }
+
+    /// The heading printed where a new calendar day starts, or `None` if `t`
+    /// falls on the same day (in the display zone) as `previous`.
+    pub fn day_break(
+        &self,
+        previous: Option<&DateTime<FixedOffset>>,
+        t: &DateTime<FixedOffset>,
+    ) -> Option<String> {
+        let day = self.in_zone(t).date_naive();
+        if previous.map(|p| self.in_zone(p).date_naive()) == Some(day) {
+            return None;
+        }
+        Some(self.day_label(t))
+    }
+}
+
+/// "in 3h", "2d 4h ago", "now". Rounded to the hour: forecast periods are
+/// hourly at their finest, so minutes would be noise.
+fn relative(delta: chrono::TimeDelta) -> String {
+    let hours = (delta.num_minutes() as f64 / 60.0).round() as i64;
+    if hours == 0 {
+        return "now".to_string();
+    }
+
+    let span = hours.unsigned_abs();
+    let text = match (span / 24, span % 24) {
+        (0, h) => format!("{h}h"),
+        (d, 0) => format!("{d}d"),
+        (d, h) => format!("{d}d {h}h"),
+    };
+
+    if hours > 0 {
+        format!("in {text}")
+    } else {
+        format!("{text} ago")
+    }
+}
+
+/// A point in time given on the command line, for `--from` and `--until`.
+///
+/// Relative and named forms are resolved against `now` and a wall-clock
+/// offset only once the forecast has been fetched, because the location's
+/// offset is not known until then.
+#[derive(Clone, Debug, PartialEq)]
+pub enum TimeSpec {
+    Now,
+    /// `+6h`, `+2d`, `+90m`.
+    Ahead(TimeDelta),
+    /// Midnight at the start of today.
+    Today,
+    /// Midnight at the start of tomorrow.
+    Tomorrow,
+    /// 6 PM today, when the NWS "Tonight" period begins.
+    Tonight,
+    /// An RFC 3339 timestamp, which carries its own offset.
+    At(DateTime<FixedOffset>),
+    /// `2024-01-28T18:00` or `2024-01-28`, read in the display zone.
+    Wall(NaiveDateTime),
+}
+
+impl TimeSpec {
+    /// The instant this names, for `now` in the zone `offset`. Fails only when
+    /// a relative time reaches past the dates chrono can represent.
+    pub fn resolve(
+        &self,
+        now: DateTime<Utc>,
+        offset: FixedOffset,
+    ) -> Result<DateTime<FixedOffset>> {
+        let today = now.with_timezone(&offset).date_naive();
+        let wall = |date: NaiveDate, hour: u32| {
+            let naive = date.and_time(NaiveTime::from_hms_opt(hour, 0, 0).expect("valid hour"));
+            naive
+                .and_local_timezone(offset)
+                .single()
+                .expect("a fixed offset has no ambiguous times")
+        };
+
+        Ok(match self {
+            TimeSpec::Now => now.with_timezone(&offset),
+            TimeSpec::Ahead(delta) => now
+                .with_timezone(&offset)
+                .checked_add_signed(*delta)
+                .context("That relative time is too far ahead")?,
+            TimeSpec::Today => wall(today, 0),
+            TimeSpec::Tomorrow => wall(today + Days::new(1), 0),
+            TimeSpec::Tonight => wall(today, 18),
+            TimeSpec::At(t) => *t,
+            TimeSpec::Wall(naive) => naive
+                .and_local_timezone(offset)
+                .single()
+                .expect("a fixed offset has no ambiguous times"),
+        })
+    }
+}
+
+impl FromStr for TimeSpec {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let s = s.trim();
+        match s.to_ascii_lowercase().as_str() {
+            "now" => return Ok(TimeSpec::Now),
+            "today" => return Ok(TimeSpec::Today),
+            "tomorrow" => return Ok(TimeSpec::Tomorrow),
+            "tonight" => return Ok(TimeSpec::Tonight),
+            _ => {}
+        }
+
+        if let Some(rest) = s.strip_prefix('+') {
+            let Some((n, unit)) = split_unit(rest) else {
+                bail!("'{s}' is not a relative time like +6h");
+            };
+            let n: i64 = n
+                .parse()
+                .with_context(|| format!("'{s}' is too far ahead"))?;
+            let delta = match unit {
+                'm' => TimeDelta::try_minutes(n),
+                'h' => TimeDelta::try_hours(n),
+                'd' => TimeDelta::try_days(n),
+                _ => bail!("'{s}' has an unknown unit; use m, h or d (e.g. +6h)"),
+            };
+            let delta = delta.with_context(|| format!("'{s}' is too far ahead"))?;
+            return Ok(TimeSpec::Ahead(delta));
+        }
+
+        if let Ok(t) = DateTime::parse_from_rfc3339(s) {
+            return Ok(TimeSpec::At(t));
+        }
+        if let Ok(naive) = NaiveDateTime::parse_from_str(s, "%Y-%m-%dT%H:%M") {
+            return Ok(TimeSpec::Wall(naive));
+        }
+        if let Ok(date) = NaiveDate::parse_from_str(s, "%Y-%m-%d") {
+            return Ok(TimeSpec::Wall(date.and_time(NaiveTime::MIN)));
+        }
+
+        bail!(
+            "'{s}' is not a time. Use now, today, tonight, tomorrow, a relative \
+             time (+6h, +2d), or a date (2024-01-28, 2024-01-28T18:00)"
+        )
+    }
+}
+
+/// The UTC offset of the IANA zone `name` (the `timeZone` of `/points`) at
+/// `at`. `None` for a name the bundled zone database does not know.
+pub fn zone_offset(name: &str, at: DateTime<Utc>) -> Option<FixedOffset> {
+    let zone: chrono_tz::Tz = name.parse().ok()?;
+    Some(*at.with_timezone(&zone).fixed_offset().offset())
+}
+
+/// Split a count from its one-letter unit, as in `6h`. `None` unless the count
+/// is plain digits, so signs and stray characters are turned away here.
+fn split_unit(s: &str) -> Option<(&str, char)> {
+    let unit = s.chars().next_back()?;
+    let n = &s[..s.len() - unit.len_utf8()];
+    if n.is_empty() || !n.bytes().all(|b| b.is_ascii_digit()) {
+        return None;
+    }
+    Some((n, unit))
+}
+
+/// A refresh interval from the command line: `90s`, `10m` or `1h`.
+pub fn parse_interval(s: &str) -> Result<std::time::Duration> {
+    let s = s.trim();
+    let Some((n, unit)) = split_unit(s) else {
+        bail!("'{s}' is not an interval like 10m");
+    };
+    let n: u64 = n
+        .parse()
+        .with_context(|| format!("'{s}' is too long an interval"))?;
+    let seconds = match unit {
+        's' => Some(n),
+        'm' => n.checked_mul(60),
+        'h' => n.checked_mul(60 * 60),
+        _ => bail!("'{s}' has an unknown unit; use s, m or h (e.g. 10m)"),
+    };
+    let seconds = seconds.with_context(|| format!("'{s}' is too long an interval"))?;
+    Ok(std::time::Duration::from_secs(seconds))
+}
+
+/// The shortest refresh interval accepted. NWS reissues forecasts about
+/// hourly, so refreshing faster than this only adds load.
+pub const MIN_REFRESH: std::time::Duration = std::time::Duration::from_secs(60);
+
+/// [`parse_interval`], for the modes that poll upstream: at least
+/// [`MIN_REFRESH`].
+pub fn parse_refresh_interval(s: &str) -> Result<std::time::Duration> {
+    let interval = parse_interval(s)?;
+    if interval < MIN_REFRESH {
+        bail!("'{s}' is too frequent; the forecast changes about hourly, so use at least 1m");
+    }
+    Ok(interval)
+}
+
+/// The interval a gridpoint value holds for, written in ISO 8601 as a start
+/// and a duration: `2024-01-29T02:00:00+00:00/PT3H`. `None` for anything
+/// else, including durations in months or years, which NWS does not use, and
+/// negative or out-of-range ones.
+pub fn parse_valid_time(s: &str) -> Option<(DateTime<FixedOffset>, DateTime<FixedOffset>)> {
+    let (start, duration) = s.split_once('/')?;
+    let start = DateTime::parse_from_rfc3339(start).ok()?;
+    let (days, time) = match duration.strip_prefix('P')?.split_once('T') {
+        Some((days, time)) => (days, time),
+        None => (duration.strip_prefix('P')?, ""),
+    };
+    let mut length = TimeDelta::zero();
+    for (part, units) in [
+        (days, &[('D', 24 * 60 * 60)][..]),
+        (time, &[('H', 60 * 60), ('M', 60), ('S', 1)][..]),
+    ] {
+        let mut rest = part;
+        for &(designator, seconds) in units {
+            if let Some((n, tail)) = rest.split_once(designator) {
+                if n.is_empty() || !n.bytes().all(|b| b.is_ascii_digit()) {
+                    return None;
+                }
+                let part = TimeDelta::try_seconds(n.parse::<i64>().ok()?.checked_mul(seconds)?)?;
+                length = length.checked_add(&part)?;
+                rest = tail;
+            }
+        }
+        if !rest.is_empty() {
+            return None;
+        }
+    }
+    Some((start, start.checked_add_signed(length)?))
+}
@@ -0,0 +1,343 @@
+//! `wxdotgov tui`: the forecast as a full-screen terminal app.
+//!
+//! Four tabs over one location at a time: the detailed forecast, the hourly
+//! chart, the hour in progress, and active alerts. Each tab's text comes from
+//! the same renderers the one-shot output uses, in their plain style, so the
+//! TUI cannot drift from what `--chart` or `--format` print. Everything is on
+//! the keyboard, so it works over SSH without a mouse:
+//!
+//! ```text
+//! 1-4, Tab, Left/Right  switch tabs
+//! [ ], n/p              previous/next location
+//! Up/Down, j/k          scroll
+//! r                     refresh this location
+//! q, Esc                quit
+//! ```
+//!
+//! The locations are the `--location`s given, then any others saved with
+//! `wxdotgov stations --use`, so a bare `wxdotgov tui` shows the places already
+//! set up. A saved `--point` is left out: there is no place name to look up.
+//!
+//! A location is fetched the first time it is shown. Fetches share one
+//! [`crate::cache`], so switching back to a location is instant; `r` reloads
+//! the location from the origin past the cache, and keeps what it gets.
+
+use std::sync::Arc;
+use std::time::Duration;
+
+use anyhow::{bail, Context, Result};
+use ratatui::crossterm::event::{self, Event, KeyCode, KeyEventKind};
+use ratatui::layout::{Constraint, Layout};
+use ratatui::style::{Modifier, Style as TextStyle};
+use ratatui::widgets::{Block, Paragraph, Tabs};
+use ratatui::Frame;
+
+use crate::cache::{self, Cache};
+use crate::exporter::Target;
+use crate::nominatim::get_lat_lon;
+use crate::render::{
+    render_alerts, render_chart, render_detailed, RenderOptions, Style, DEFAULT_LIMIT,
+};
+use crate::stations::{self, Choices};
+use crate::status::{render_line, LineTemplate, CURRENT_TEMPLATE};
+use crate::times::{DisplayZone, TimeDisplay, TimeFormat};
+use crate::units::Units;
+use crate::weatherdotgov::{
+    get_active_alerts, get_detailed_forecast, get_hourly_forecast, get_weather_point, Alert,
+    HourlyPeriod, Period,
+};
+
+#[derive(Debug, clap::Args)]
+pub struct TuiArgs {
+    /// A location to show: a ZIP code, "City" or "City, ST". Repeat for
+    /// more than one, and switch between them with [ and ]. Locations saved
+    /// with `wxdotgov stations --use` follow these, or stand in for them.
+    #[arg(long = "location", value_name = "PLACE")]
+    locations: Vec<Target>,
+
+    /// Unit system for temperatures, wind speeds and other measurements.
+    #[arg(long, value_enum, env = "WXDOTGOV_UNITS", default_value_t = Units::Us)]
+    units: Units,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Tab {
+    Forecast,
+    Hourly,
+    Now,
+    Alerts,
+}
+
+impl Tab {
+    const ALL: [Tab; 4] = [Tab::Forecast, Tab::Hourly, Tab::Now, Tab::Alerts];
+
+    fn title(self) -> &'static str {
+        match self {
+            Tab::Forecast => "1 Forecast",
+            Tab::Hourly => "2 Hourly",
+            Tab::Now => "3 Now",
+            Tab::Alerts => "4 Alerts",
+        }
+    }
+
+    fn index(self) -> usize {
+        Tab::ALL
+            .iter()
+            .position(|t| *t == self)
+            .expect("ALL lists every tab")
+    }
+}
+
+/// Everything shown for one location.
+pub struct Forecast {
+    pub place: String,
+    pub detailed: Vec<Period>,
+    pub hourly: Vec<HourlyPeriod>,
+    pub alerts: Vec<Alert>,
+}
+
+/// The app's state, separate from the terminal so tests can drive it.
+pub struct App {
+    targets: Vec<Target>,
+    units: Units,
+    /// Per target: not fetched yet, fetched, or the error from fetching.
+    forecasts: Vec<Option<Result<Forecast, String>>>,
+    /// Per target: whether its next fetch is a refresh, past the cache.
+    reload: Vec<bool>,
+    cache: Arc<Cache>,
+    pub location: usize,
+    pub tab: Tab,
+    pub scroll: u16,
+    pub quit: bool,
+    /// Upstream base URLs, `None` for the real services. Tests point these at
+    /// a mock server.
+    pub nominatim_url: Option<String>,
+    pub weather_url: Option<String>,
+}
+
+impl App {
+    pub fn new(targets: Vec<Target>, units: Units) -> Self {
+        let forecasts = targets.iter().map(|_| None).collect();
+        let reload = vec![false; targets.len()];
+        App {
+            targets,
+            units,
+            forecasts,
+            reload,
+            cache: Arc::default(),
+            location: 0,
+            tab: Tab::Forecast,
+            scroll: 0,
+            quit: false,
+            nominatim_url: None,
+            weather_url: None,
+        }
+    }
+
+    /// Whether the current location still needs fetching.
+    pub fn needs_load(&self) -> bool {
+        self.forecasts[self.location].is_none()
+    }
+
+    async fn fetch(&self, target: &Target) -> Result<Forecast> {
+        let location = get_lat_lon(target.input.clone(), self.nominatim_url.as_deref()).await?;
+        let point = get_weather_point(&location.lat, &location.lon, self.weather_url.as_deref())
+            .await?
+            .properties;
+        let detailed = get_detailed_forecast(&point.forecast, self.units)
+            .await?
+            .properties
+            .periods;
+        let url = point
+            .forecast_hourly
+            .as_ref()
+            .context("Hourly forecast not available for this location")?;
+        let hourly = get_hourly_forecast(url, self.units)
+            .await?
+            .properties
+            .periods;
+        let alerts =
+            get_active_alerts(&location.lat, &location.lon, self.weather_url.as_deref()).await?;
+        Ok(Forecast {
+            place: location.display_name,
+            detailed,
+            hourly,
+            alerts,
+        })
+    }
+
+    /// Fetch the current location. A failure is kept and shown in place of
+    /// the forecast, rather than ending the session.
+    pub async fn load(&mut self) {
+        let reload = std::mem::take(&mut self.reload[self.location]);
+        let target = &self.targets[self.location];
+        let fetch = self.fetch(target);
+        let result = if reload {
+            cache::reload(self.cache.clone(), fetch).await
+        } else {
+            cache::scope(self.cache.clone(), fetch).await
+        };
+        self.forecasts[self.location] = Some(result.map_err(|e| format!("{e:#}")));
+    }
+
+    pub fn handle_key(&mut self, key: KeyCode) {
+        let tabs = Tab::ALL.len();
+        let locations = self.targets.len();
+        match key {
+            KeyCode::Char('q') | KeyCode::Esc => self.quit = true,
+            KeyCode::Char(c @ '1'..='4') => {
+                self.show_tab(Tab::ALL[c as usize - '1' as usize]);
+            }
+            KeyCode::Tab | KeyCode::Right | KeyCode::Char('l') => {
+                self.show_tab(Tab::ALL[(self.tab.index() + 1) % tabs]);
+            }
+            KeyCode::BackTab | KeyCode::Left | KeyCode::Char('h') => {
+                self.show_tab(Tab::ALL[(self.tab.index() + tabs - 1) % tabs]);
+            }
+            KeyCode::Char(']') | KeyCode::Char('n') => {
+                self.show_location((self.location + 1) % locations);
+            }
+            KeyCode::Char('[') | KeyCode::Char('p') => {
+                self.show_location((self.location + locations - 1) % locations);
+            }
+            KeyCode::Down | KeyCode::Char('j') => self.scroll = self.scroll.saturating_add(1),
+            KeyCode::Up | KeyCode::Char('k') => self.scroll = self.scroll.saturating_sub(1),
+            KeyCode::Char('r') => {
+                self.forecasts[self.location] = None;
+                self.reload[self.location] = true;
+            }
+            _ => {}
+        }
+    }
+
+    fn show_tab(&mut self, tab: Tab) {
+        self.tab = tab;
+        self.scroll = 0;
+    }
+
+    fn show_location(&mut self, location: usize) {
+        self.location = location;
+        self.scroll = 0;
+    }
+
+    /// The current tab's text, from the shared renderers.
+    fn body(&self, forecast: &Forecast) -> String {
+        let opts = RenderOptions {
+            style: Style::Plain,
+            limit: None,
+            units: self.units,
+            times: TimeDisplay::new(TimeFormat::TwelveHour, DisplayZone::Location),
+        };
+        match self.tab {
+            Tab::Forecast => {
+                render_detailed(&forecast.detailed, 0, &opts).unwrap_or_else(|e| format!("{e:#}"))
+            }
+            Tab::Hourly => render_chart(
+                &forecast.hourly,
+                0,
+                &RenderOptions {
+                    limit: Some(DEFAULT_LIMIT),
+                    ..opts
+                },
+            ),
+            Tab::Now => {
+                let template: LineTemplate = CURRENT_TEMPLATE.parse().expect("template is valid");
+                render_line(&forecast.hourly, &template, &opts).unwrap_or_else(|e| format!("{e:#}"))
+            }
+            Tab::Alerts => render_alerts(&forecast.alerts, &opts),
+        }
+    }
+
+    pub fn draw(&self, frame: &mut Frame) {
+        let [tabs, body, footer] = Layout::vertical([
+            Constraint::Length(1),
+            Constraint::Min(0),
+            Constraint::Length(1),
+        ])
+        .areas(frame.area());
+
+        let titles: Vec<&str> = Tab::ALL.iter().map(|t| t.title()).collect();
+        let highlight = TextStyle::default().add_modifier(Modifier::REVERSED);
+        frame.render_widget(
+            Tabs::new(titles)
+                .select(self.tab.index())
+                .highlight_style(highlight),
+            tabs,
+        );
+
+        let target = &self.targets[self.location];
+        let (title, text) = match &self.forecasts[self.location] {
+            None => (target.label.clone(), "Loading...".to_string()),
+            Some(Err(e)) => (target.label.clone(), format!("Error: {e}")),
+            Some(Ok(forecast)) => (forecast.place.clone(), self.body(forecast)),
+        };
+        frame.render_widget(
+            Paragraph::new(text)
+                .block(Block::bordered().title(format!(" {title} ")))
+                .scroll((self.scroll, 0)),
+            body,
+        );
+
+        let help = format!(
+            "q quit  1-4 tabs  [ ] location {}/{}  r refresh  j/k scroll",
+            self.location + 1,
+            self.targets.len()
+        );
+        frame.render_widget(Paragraph::new(help), footer);
+    }
+}
+
+/// The locations to cycle through: `given`, then each location in `saved`
+/// that is not among them and names a place to look up.
+pub fn targets(given: Vec<Target>, saved: &Choices) -> Result<Vec<Target>> {
+    let mut targets = given;
+    for label in saved.keys() {
+        if crate::parse_point(label).is_ok() || targets.iter().any(|t| t.label == *label) {
+            continue;
+        }
+        if let Ok(target) = label.parse() {
+            targets.push(target);
+        }
+    }
+    if targets.is_empty() {
+        bail!("No locations to show: give --location, or save one with `wxdotgov stations --use`");
+    }
+    Ok(targets)
+}
+
+/// Run until the user quits. The terminal is restored however this returns.
+pub async fn run(args: TuiArgs) -> Result<()> {
+    let saved = match stations::default_file() {
+        Some(path) => crate::dirs::load_state::<Choices>(&path)?,
+        None => Choices::new(),
+    };
+    let mut app = App::new(targets(args.locations, &saved)?, args.units);
+    let mut terminal = ratatui::init();
+    let result = async {
+        while !app.quit {
+            terminal.draw(|frame| app.draw(frame))?;
+            if app.needs_load() {
+                app.load().await;
+                continue;
+            }
+            // Waiting for a key blocks; tell the runtime so it can move other
+            // work off this thread.
+            let key = tokio::task::block_in_place(|| -> std::io::Result<_> {
+                if !event::poll(Duration::from_millis(250))? {
+                    return Ok(None);
+                }
+                Ok(match event::read()? {
+                    Event::Key(key) if key.kind == KeyEventKind::Press => Some(key.code),
+                    _ => None,
+                })
+            })?;
+            if let Some(key) = key {
+                app.handle_key(key);
+            }
+        }
+        Ok::<_, anyhow::Error>(())
+    }
+    .await;
+    ratatui::restore();
+    result
+}
@@ -0,0 +1,246 @@
+//! Unit systems and conversion.
+//!
+//! The forecast endpoints take `?units=us|si` and convert server-side, but not
+//! every value the API returns honours it. Gridpoint layers and observations
+//! are always SI, and carry their unit as a WMO code alongside the number:
+//!
+//! ```json
+//! { "unitCode": "wmoUnit:degC", "value": 8.3 }
+//! ```
+//!
+//! Even the hourly forecast mixes the two: with `units=us` the temperature is
+//! Fahrenheit but `dewpoint` is still `wmoUnit:degC`. Printing those values
+//! as-is would put °F and °C on the same line, so everything displayed goes
+//! through this module and comes out in the system the user asked for.
+//!
+//! Wind speed in forecast periods is a string rather than a quantitative value
+//! ("5 mph", "5 to 10 mph", "10 to 15 km/h"), so it gets its own parser.
+
+use std::fmt;
+use std::str::FromStr;
+
+use anyhow::{bail, Context, Result};
+use clap::ValueEnum;
+use serde::{Deserialize, Serialize};
+
+/// The unit system output is displayed in.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, ValueEnum)]
+pub enum Units {
+    /// US customary: °F, mph, inHg, inches.
+    #[default]
+    Us,
+    /// International: °C, km/h, hPa, millimetres.
+    Si,
+}
+
+impl Units {
+    /// The value of the NWS `units` query parameter.
+    pub fn as_query(self) -> &'static str {
+        match self {
+            Units::Us => "us",
+            Units::Si => "si",
+        }
+    }
+
+    /// The `temperatureUnit` the NWS reports for forecasts in this system.
+    pub fn temperature_unit(self) -> &'static str {
+        match self {
+            Units::Us => "F",
+            Units::Si => "C",
+        }
+    }
+}
+
+pub fn celsius_to_fahrenheit(c: f64) -> f64 {
+    c * 9.0 / 5.0 + 32.0
+}
+
+pub fn fahrenheit_to_celsius(f: f64) -> f64 {
+    (f - 32.0) * 5.0 / 9.0
+}
+
+const KMH_PER_MPH: f64 = 1.609_344;
+const KMH_PER_KNOT: f64 = 1.852;
+const PA_PER_INHG: f64 = 3_386.389;
+const MM_PER_INCH: f64 = 25.4;
+const M_PER_FOOT: f64 = 0.3048;
+
+/// A value from a gridpoint layer or an observation.
+///
+/// `value` is null whenever the station or model has no reading, which is
+/// common for observations, so it is optional rather than defaulted.
+#[derive(Clone, Debug, Deserialize, Serialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct QuantitativeValue {
+    pub value: Option<f64>,
+    pub unit_code: String,
+}
+
+/// A number ready to print, with the unit it is now in.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Measurement {
+    pub value: f64,
+    pub unit: String,
+}
+
+impl fmt::Display for Measurement {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        // Degrees and percentages attach to the number; everything else is a
+        // word and reads better with a space.
+        match self.unit.as_str() {
+            "°F" | "°C" | "%" => write!(f, "{:.0}{}", self.value, self.unit),
+            "inHg" | "in" => write!(f, "{:.2} {}", self.value, self.unit),
+            _ => write!(f, "{:.0} {}", self.value, self.unit),
+        }
+    }
+}
+
+impl QuantitativeValue {
+    /// Convert into `units`, or `None` if there is no reading.
+    ///
+    /// Unit codes this module does not know are passed through with the code's
+    /// suffix as the unit, rather than being dropped: a raw value is more
+    /// useful than a blank.
+    pub fn to_units(&self, units: Units) -> Option<Measurement> {
+        let v = self.value?;
+        let code = self
+            .unit_code
+            .strip_prefix("wmoUnit:")
+            .unwrap_or(&self.unit_code);
+
+        let (value, unit) = match (code, units) {
+            ("degC", Units::Us) => (celsius_to_fahrenheit(v), "°F"),
+            ("degC", Units::Si) => (v, "°C"),
+            ("degF", Units::Us) => (v, "°F"),
+            ("degF", Units::Si) => (fahrenheit_to_celsius(v), "°C"),
+            ("km_h-1", Units::Us) => (v / KMH_PER_MPH, "mph"),
+            ("km_h-1", Units::Si) => (v, "km/h"),
+            ("m_s-1", Units::Us) => (v * 3.6 / KMH_PER_MPH, "mph"),
+            ("m_s-1", Units::Si) => (v * 3.6, "km/h"),
+            ("kt", Units::Us) => (v * KMH_PER_KNOT / KMH_PER_MPH, "mph"),
+            ("kt", Units::Si) => (v * KMH_PER_KNOT, "km/h"),
+            ("Pa", Units::Us) => (v / PA_PER_INHG, "inHg"),
+            ("Pa", Units::Si) => (v / 100.0, "hPa"),
+            ("mm", Units::Us) => (v / MM_PER_INCH, "in"),
+            ("mm", Units::Si) => (v, "mm"),
+            ("m", Units::Us) => (v / M_PER_FOOT, "ft"),
+            ("m", Units::Si) => (v, "m"),
+            ("percent", _) => (v, "%"),
+            _ => (v, code),
+        };
+
+        Some(Measurement {
+            value,
+            unit: unit.to_string(),
+        })
+    }
+}
+
+/// A speed unit as written in forecast wind strings.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SpeedUnit {
+    Mph,
+    Kmh,
+    Knots,
+}
+
+impl SpeedUnit {
+    fn to_kmh(self, v: f64) -> f64 {
+        match self {
+            SpeedUnit::Mph => v * KMH_PER_MPH,
+            SpeedUnit::Kmh => v,
+            SpeedUnit::Knots => v * KMH_PER_KNOT,
+        }
+    }
+
+    fn kmh_to_self(self, v: f64) -> f64 {
+        match self {
+            SpeedUnit::Mph => v / KMH_PER_MPH,
+            SpeedUnit::Kmh => v,
+            SpeedUnit::Knots => v / KMH_PER_KNOT,
+        }
+    }
+
+    fn label(self) -> &'static str {
+        match self {
+            SpeedUnit::Mph => "mph",
+            SpeedUnit::Kmh => "km/h",
+            SpeedUnit::Knots => "kt",
+        }
+    }
+}
+
+/// A forecast wind speed, either a single value or a range.
+///
+/// Parsed from the strings the forecast endpoints return: `"5 mph"`,
+/// `"5 to 10 mph"`, `"15 km/h"`. A single value has `low == high`.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct WindSpeed {
+    pub low: f64,
+    pub high: f64,
+    pub unit: SpeedUnit,
+}
+
+impl WindSpeed {
+    /// Re-express in `units`. Knots are left alone: the API only uses them
+    /// where they are the convention, and neither system's unit replaces them.
+    pub fn to_units(self, units: Units) -> WindSpeed {
+        let target = match (self.unit, units) {
+            (SpeedUnit::Knots, _) => return self,
+            (_, Units::Us) => SpeedUnit::Mph,
+            (_, Units::Si) => SpeedUnit::Kmh,
+        };
+        WindSpeed {
+            low: target.kmh_to_self(self.unit.to_kmh(self.low)),
+            high: target.kmh_to_self(self.unit.to_kmh(self.high)),
+            unit: target,
+        }
+    }
+}
+
+impl FromStr for WindSpeed {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let s = s.trim();
+        let (numbers, unit) = s
+            .rsplit_once(' ')
+            .with_context(|| format!("wind speed '{s}' has no unit"))?;
+
+        let unit = match unit {
+            "mph" => SpeedUnit::Mph,
+            "km/h" => SpeedUnit::Kmh,
+            "kt" | "kts" | "knots" => SpeedUnit::Knots,
+            other => bail!("wind speed '{s}' has an unrecognised unit '{other}'"),
+        };
+
+        let parse = |n: &str| {
+            n.trim()
+                .parse::<f64>()
+                .with_context(|| format!("wind speed '{s}' is not numeric"))
+        };
+
+        let (low, high) = match numbers.split_once(" to ") {
+            Some((low, high)) => (parse(low)?, parse(high)?),
+            None => {
+                let v = parse(numbers)?;
+                (v, v)
+            }
+        };
+
+        Ok(WindSpeed { low, high, unit })
+    }
+}
+
+impl fmt::Display for WindSpeed {
+    /// Formats the way the API does, so a converted value is indistinguishable
+    /// from one the server sent.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let (low, high) = (self.low.round(), self.high.round());
+        if low == high {
+            write!(f, "{low:.0} {}", self.unit.label())
+        } else {
+            write!(f, "{low:.0} to {high:.0} {}", self.unit.label())
+        }
+    }
+}
@@ -0,0 +1,284 @@
+//! `--watch`: keep the forecast on screen and say what changed.
+//!
+//! The forecast is fetched again every interval and the screen redrawn, with
+//! the differences from the previous fetch listed above it: temperature
+//! shifts, reworded short forecasts, and alerts issued or ended. Periods are
+//! matched by start time, so an hour that has rolled off the front of the
+//! list, or appeared at the end, is not reported as a change.
+//!
+//! Every refresh runs in one [`crate::cache`] scope, so the forecast itself is
+//! fetched no more often than its cache lifetime allows, however short the
+//! interval; alerts, cached for a minute, are what a short interval buys.
+//!
+//! A refresh that fails keeps the last forecast on screen with a warning, as a
+//! pane left open overnight should survive a network blip.
+
+use std::future::Future;
+use std::io::{IsTerminal, Write};
+use std::sync::Arc;
+use std::time::Duration;
+
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+use colored::*;
+
+use crate::cache::{self, Cache};
+use crate::filter::{PeriodFilter, Timed};
+use crate::render::{RenderOptions, Style};
+use crate::times::TimeDisplay;
+use crate::weatherdotgov::{get_active_alerts, Alert, HourlyPeriod, Period};
+
+/// What the diff compares in a period.
+pub trait Watched: Timed {
+    /// How the period is named in the list of changes.
+    fn label(&self, times: &TimeDisplay) -> String;
+    fn temperature(&self) -> Option<i32>;
+    fn short_forecast(&self) -> Option<&str>;
+}
+
+impl Watched for Period {
+    fn label(&self, _times: &TimeDisplay) -> String {
+        self.name.clone()
+    }
+    fn temperature(&self) -> Option<i32> {
+        self.temperature
+    }
+    fn short_forecast(&self) -> Option<&str> {
+        self.short_forecast.as_deref()
+    }
+}
+
+impl Watched for HourlyPeriod {
+    fn label(&self, times: &TimeDisplay) -> String {
+        times.label(&self.start_time)
+    }
+    fn temperature(&self) -> Option<i32> {
+        Some(self.temperature)
+    }
+    fn short_forecast(&self) -> Option<&str> {
+        Some(&self.short_forecast)
+    }
+}
+
+/// One difference between two fetches.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Change {
+    Temperature {
+        period: String,
+        from: i32,
+        to: i32,
+    },
+    Forecast {
+        period: String,
+        from: String,
+        to: String,
+    },
+    AlertIssued(String),
+    AlertEnded(String),
+}
+
+/// The changes from `before` to `after`, periods in forecast order, then
+/// alerts.
+pub fn diff<T: Watched>(
+    before: &[T],
+    after: &[T],
+    alerts_before: &[Alert],
+    alerts_after: &[Alert],
+    times: &TimeDisplay,
+) -> Vec<Change> {
+    let mut changes = Vec::new();
+    for new in after {
+        let Some(old) = before.iter().find(|p| p.start_time() == new.start_time()) else {
+            continue;
+        };
+        if let (Some(from), Some(to)) = (old.temperature(), new.temperature()) {
+            if from != to {
+                changes.push(Change::Temperature {
+                    period: new.label(times),
+                    from,
+                    to,
+                });
+            }
+        }
+        if let (Some(from), Some(to)) = (old.short_forecast(), new.short_forecast()) {
+            if from != to {
+                changes.push(Change::Forecast {
+                    period: new.label(times),
+                    from: from.to_string(),
+                    to: to.to_string(),
+                });
+            }
+        }
+    }
+
+    // An update has an id of its own, so alerts are matched with
+    // `Alert::same_as` rather than by id.
+    let listed = |alerts: &[Alert], alert: &Alert| alerts.iter().any(|a| a.same_as(alert));
+    for alert in alerts_after {
+        if !listed(alerts_before, alert) {
+            changes.push(Change::AlertIssued(alert_text(alert)));
+        }
+    }
+    for alert in alerts_before {
+        if !listed(alerts_after, alert) {
+            changes.push(Change::AlertEnded(alert.event.clone()));
+        }
+    }
+    changes
+}
+
+fn alert_text(alert: &Alert) -> String {
+    alert
+        .headline
+        .clone()
+        .unwrap_or_else(|| alert.event.clone())
+}
+
+/// The list of changes printed above the redrawn forecast.
+pub fn render_changes(changes: &[Change], opts: &RenderOptions) -> String {
+    if changes.is_empty() {
+        return match opts.style {
+            Style::Pretty => format!("{}\n", "No changes since the last refresh.".dimmed()),
+            Style::Plain => "No changes since the last refresh.\n".to_string(),
+        };
+    }
+    let unit = opts.units.temperature_unit();
+    let mut out = match opts.style {
+        Style::Pretty => format!("{}\n", "Changed since the last refresh:".bold()),
+        Style::Plain => "Changed since the last refresh:\n".to_string(),
+    };
+    for change in changes {
+        let (text, up) = match change {
+            Change::Temperature { period, from, to } => {
+                (format!("{period}: {from}°{unit} -> {to}°{unit}"), to > from)
+            }
+            Change::Forecast { period, from, to } => (format!("{period}: {from} -> {to}"), true),
+            Change::AlertIssued(text) => (format!("New alert: {text}"), true),
+            Change::AlertEnded(event) => (format!("Alert ended: {event}"), false),
+        };
+        match opts.style {
+            // Warmer and new are yellow, cooler and ended are cyan.
+            Style::Pretty if up => out.push_str(&format!("  {}\n", text.yellow())),
+            Style::Pretty => out.push_str(&format!("  {}\n", text.cyan())),
+            Style::Plain => out.push_str(&format!("  * {text}\n")),
+        }
+    }
+    out
+}
+
+/// The line closing each redraw.
+fn footer(updated: DateTime<Utc>, interval: Duration, error: Option<&anyhow::Error>) -> String {
+    let updated = updated.with_timezone(&chrono::Local).format("%-I:%M %p");
+    let mut out = String::new();
+    if let Some(e) = error {
+        out.push_str(&format!(
+            "warning: refresh failed, showing the forecast from {updated}: {e:#}\n"
+        ));
+    }
+    let every = match interval.as_secs() {
+        s if s % 3600 == 0 => format!("{}h", s / 3600),
+        s if s % 60 == 0 => format!("{}m", s / 60),
+        s => format!("{s}s"),
+    };
+    out.push_str(&format!(
+        "Updated {updated}; refreshing every {every}. Ctrl-C to stop.\n"
+    ));
+    out
+}
+
+/// The last forecast drawn, kept to diff the next one against and to draw
+/// again, as it was, when a refresh fails.
+struct Drawn<T> {
+    periods: Vec<T>,
+    /// How many periods the filter hid, for the note under them.
+    filtered: usize,
+    alerts: Vec<Alert>,
+    at: DateTime<Utc>,
+}
+
+/// Where the forecast comes from, and how it is drawn.
+pub struct Watch<'a> {
+    pub lat: &'a str,
+    pub lon: &'a str,
+    pub filter: PeriodFilter,
+    pub opts: RenderOptions,
+    pub interval: Duration,
+}
+
+impl Watch<'_> {
+    /// Fetch, draw and repeat until interrupted. Only a failure of the first
+    /// fetch is returned, there being nothing to show until one succeeds, or
+    /// a `--from`/`--until` that cannot be resolved.
+    pub async fn run<T, F, Fut>(
+        &self,
+        mut fetch: F,
+        render: fn(&[T], usize, &RenderOptions) -> Result<String>,
+    ) -> Result<()>
+    where
+        T: Watched,
+        F: FnMut() -> Fut,
+        Fut: Future<Output = Result<Vec<T>>>,
+    {
+        let cache = Arc::new(Cache::default());
+        let mut timer = tokio::time::interval(self.interval);
+        timer.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+        let mut last: Option<Drawn<T>> = None;
+
+        loop {
+            timer.tick().await;
+            let mut opts = self.opts;
+            opts.times.now = Utc::now();
+
+            let fetched = cache::scope(cache.clone(), async {
+                let periods = fetch().await?;
+                let alerts = get_active_alerts(self.lat, self.lon, None).await?;
+                Ok::<_, anyhow::Error>((periods, alerts))
+            })
+            .await;
+
+            let (screen, error) = match fetched {
+                Ok((mut periods, alerts)) => {
+                    let filtered = self.filter.apply(&mut periods, &opts.times)?;
+                    let mut screen = String::new();
+                    if let Some(before) = &last {
+                        let changes = diff(
+                            &before.periods,
+                            &periods,
+                            &before.alerts,
+                            &alerts,
+                            &opts.times,
+                        );
+                        screen.push_str(&render_changes(&changes, &opts));
+                    }
+                    screen.push_str(&render(&periods, filtered, &opts)?);
+                    last = Some(Drawn {
+                        periods,
+                        filtered,
+                        alerts,
+                        at: opts.times.now,
+                    });
+                    (screen, None)
+                }
+                Err(e) if last.is_none() => return Err(e),
+                Err(e) => {
+                    let drawn = last.as_ref().expect("checked above");
+                    (render(&drawn.periods, drawn.filtered, &opts)?, Some(e))
+                }
+            };
+            let updated = last.as_ref().map_or(opts.times.now, |drawn| drawn.at);
+            redraw(&screen, &footer(updated, self.interval, error.as_ref()))?;
+        }
+    }
+}
+
+/// Replace the screen when stdout is a terminal; otherwise, append, so a
+/// redirected watch is a log of every refresh.
+fn redraw(screen: &str, footer: &str) -> Result<()> {
+    let mut stdout = std::io::stdout().lock();
+    if stdout.is_terminal() {
+        write!(stdout, "\x1b[2J\x1b[H")?;
+    }
+    write!(stdout, "{screen}\n{footer}")?;
+    stdout.flush()?;
+    Ok(())
+}
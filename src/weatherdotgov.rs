@@ -9,6 +9,61 @@
 //! GET /gridpoints/{office}/{x},{y}/forecast/hourly  -> hourly periods
 //! ```
 //!
+//! Active alerts are looked up by point directly, without the grid step:
+//!
+//! ```text
+//! GET /alerts/active?point={lat},{lon}  -> features[].properties
+//! ```
+//!
+//! The points data also names the forecast office, and the zones and county
+//! the point is in, by URL. Offices are looked up by id:
+//!
+//! ```text
+//! GET /offices/{office}  -> name, address, telephone
+//! ```
+//!
+//! A zone has a forecast and alerts of its own, for places better described
+//! by zone than by point. Zone forecast periods are narrative only:
+//!
+//! ```text
+//! GET /zones?type=forecast&area=WA      -> features[].properties.id, name
+//! GET /zones?id={zone}                  -> features[].properties.type
+//! GET /zones/{type}/{zone}/forecast     -> periods[].name, detailedForecast
+//! GET /alerts/active/zone/{zone}        -> features[].properties
+//! ```
+//!
+//! The grid square's raw data has every forecast element as a time series,
+//! including fire weather elements the forecasts leave out. Each value holds
+//! for an interval, written as a start and an ISO 8601 duration:
+//!
+//! ```text
+//! GET /gridpoints/{office}/{x},{y}  -> relativeHumidity.values[].validTime, value
+//! ```
+//!
+//! Observations come from stations rather than the grid. The points data
+//! links the stations near a point, nearest first, and each station has its
+//! readings by time:
+//!
+//! ```text
+//! GET /gridpoints/{office}/{x},{y}/stations           -> features[].properties.stationIdentifier
+//! GET /stations/{station}/observations?start=&end=    -> features[].properties.temperature, ...
+//! GET /stations/{station}/observations/latest         -> properties.temperature, ...
+//! GET /stations/{station}                             -> properties.name, geometry
+//! ```
+//!
+//! Marine zones (`coastal` and `offshore`) work the same way, and matter
+//! because `/points` answers 404 for a point on the water: such a point is
+//! resolved to its marine zone with `GET /zones?point={lat},{lon}` instead.
+//!
+//! Text products, such as the Area Forecast Discussion a forecast office
+//! writes a few times a day, are listed by type and office, newest first, and
+//! then fetched by id. These two answer in JSON-LD rather than GeoJSON:
+//!
+//! ```text
+//! GET /products/types/AFD/locations/{office}  -> @graph[].id
+//! GET /products/{id}                          -> productText
+//! ```
+//!
 //! Responses are GeoJSON with the interesting values under `properties`. Only
 //! the fields this tool prints are deserialized; everything else is ignored.
 //!
@@ -19,11 +74,14 @@
 //!       {
 //!         "name": "Tonight",
 //!         "startTime": "2024-01-28T18:00:00-08:00",
+//!         "endTime": "2024-01-29T06:00:00-08:00",
+//!         "isDaytime": false,
 //!         "temperature": 51,
 //!         "temperatureUnit": "F",
 //!         "windSpeed": "5 mph",
 //!         "windDirection": "SSW",
 //!         "shortForecast": "Chance Light Rain",
+//!         "icon": "https://api.weather.gov/icons/land/night/rain,20?size=medium",
 //!         "detailedForecast": "A chance of rain. Mostly cloudy..."
 //!       }
 //!     ]
@@ -31,28 +89,106 @@
 //! }
 //! ```
 //!
+//! Both forecast endpoints take `?units=us|si`. The temperature and wind speed
+//! come back in the requested system, but nested quantitative values such as
+//! the hourly `dewpoint` are always SI; see [`crate::units`] for the conversion
+//! applied before display.
+//!
 //! Note the field names are camelCase. Every struct here therefore carries
 //! `#[serde(rename_all = "camelCase")]`; omitting it on `HourlyPeriod` is what
-//! made `--forecast-type hourly` fail against the live API (see #19).
+//! made `--forecast-type hourly` fail against the live API (see #19). The
+//! structs that are also serialized, for `--template` contexts, keep those
+//! names on the way out, so a template names fields as the API docs do.
+//!
+//! Every response is cached (see [`crate::cache`]) with its `ETag` and
+//! `Last-Modified`. Once a cached body is past its freshness it is refetched
+//! with `If-None-Match` and `If-Modified-Since`, and a `304 Not Modified`
+//! renews the cached copy without downloading it again. `watch` and `serve`
+//! refresh far more often than a forecast changes, so most of their refreshes
+//! cost an empty response.
+//!
+//! Now and then the API serves a forecast that is hours old, or has no
+//! periods. [`get_checked_forecast`] and [`get_checked_hourly_forecast`] say
+//! so, judging age by the forecast's own [`Issuance`] times, and can retry
+//! past every cache or fall back to the last forecast that was good.
 //!
 //! API docs: <https://www.weather.gov/documentation/services-web-api>
 
+use std::time::Duration;
+
 use anyhow::{bail, Context, Result};
+use chrono::{DateTime, FixedOffset, SecondsFormat, Utc};
+use clap::ValueEnum;
+use reqwest::header::{
+    CACHE_CONTROL, ETAG, IF_MODIFIED_SINCE, IF_NONE_MATCH, LAST_MODIFIED, PRAGMA,
+};
+use reqwest::StatusCode;
 use serde::de::DeserializeOwned;
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
 
+use crate::cache::{self, Validators};
 use crate::http;
+use crate::times::parse_valid_time;
+use crate::units::{QuantitativeValue, Units, WindSpeed};
 
 #[derive(Debug, Deserialize)]
 pub struct PointsResponse {
     pub properties: PointsProperties,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Default, Deserialize, Serialize)]
 #[serde(rename_all = "camelCase")]
 pub struct PointsProperties {
+    /// The forecast office, e.g. `SEW`, and the grid square within its area.
+    pub grid_id: String,
+    pub grid_x: u32,
+    pub grid_y: u32,
     pub forecast: String,
     pub forecast_hourly: Option<String>,
+    /// The office responsible for the point, its County Warning Area.
+    pub cwa: Option<String>,
+    /// URL of the office, e.g. `https://api.weather.gov/offices/SEW`.
+    pub forecast_office: Option<String>,
+    /// URLs of the zones the point is in, e.g. `.../zones/forecast/WAZ558`.
+    /// Alerts and products are issued by zone; see [`zone_id`].
+    pub forecast_zone: Option<String>,
+    pub county: Option<String>,
+    pub fire_weather_zone: Option<String>,
+    /// URL of the observation stations near the point, nearest first.
+    pub observation_stations: Option<String>,
+    /// The nearest NEXRAD radar, e.g. `KATX`.
+    pub radar_station: Option<String>,
+    /// An IANA zone name, e.g. `America/Los_Angeles`.
+    pub time_zone: Option<String>,
+}
+
+impl PointsProperties {
+    /// The forecast office: `cwa`, or the grid's office when it is missing.
+    /// The two differ only where an office forecasts for a neighbour's area.
+    pub fn office_id(&self) -> &str {
+        self.cwa.as_deref().unwrap_or(&self.grid_id)
+    }
+
+    /// The forecast zone ID, e.g. `WAZ558`.
+    pub fn forecast_zone_id(&self) -> Option<&str> {
+        self.forecast_zone.as_deref().map(zone_id)
+    }
+
+    /// The county zone ID, e.g. `WAC033`.
+    pub fn county_id(&self) -> Option<&str> {
+        self.county.as_deref().map(zone_id)
+    }
+
+    /// The fire weather zone ID, e.g. `WAZ654`.
+    pub fn fire_weather_zone_id(&self) -> Option<&str> {
+        self.fire_weather_zone.as_deref().map(zone_id)
+    }
+}
+
+/// The ID at the end of a zone or office URL: `WAZ558` from
+/// `https://api.weather.gov/zones/forecast/WAZ558`.
+pub fn zone_id(url: &str) -> &str {
+    url.trim_end_matches('/').rsplit('/').next().unwrap_or(url)
 }
 
 #[derive(Debug, Deserialize)]
@@ -62,14 +198,56 @@ pub struct ForecastResponse {
 
 #[derive(Debug, Deserialize)]
 pub struct ForecastProperties {
+    #[serde(flatten)]
+    pub issuance: Issuance,
     pub periods: Vec<Period>,
 }
 
-#[derive(Debug, Deserialize)]
+/// When a forecast was made, as both forecast endpoints report it.
+///
+/// `updateTime` is when the forecaster last changed the grid, and `updated`
+/// usually repeats it. `generatedAt` is when the API built this response,
+/// which is hours old when a CDN serves a stuck copy. `validTimes` is the ISO
+/// 8601 interval the forecast covers, e.g. `2024-01-28T12:00:00+00:00/P7DT13H`.
+#[derive(Clone, Debug, Default, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Issuance {
+    pub updated: Option<DateTime<FixedOffset>>,
+    pub generated_at: Option<DateTime<FixedOffset>>,
+    pub update_time: Option<DateTime<FixedOffset>>,
+    pub valid_times: Option<String>,
+}
+
+impl Issuance {
+    /// How current the forecast is known to be: the older of its update time
+    /// and its generation time, as either being old makes the forecast old.
+    pub fn as_of(&self) -> Option<DateTime<FixedOffset>> {
+        match (self.update_time.or(self.updated), self.generated_at) {
+            (Some(updated), Some(generated)) => Some(updated.min(generated)),
+            (updated, generated) => updated.or(generated),
+        }
+    }
+}
+
+#[derive(Debug, Deserialize, Serialize)]
 #[serde(rename_all = "camelCase")]
 pub struct Period {
     pub name: String,
+    pub start_time: DateTime<FixedOffset>,
+    pub end_time: DateTime<FixedOffset>,
+    pub is_daytime: bool,
+    /// The period's high or low, in the units the forecast was requested in.
+    /// `--watch` compares both between fetches, and [`crate::archive::add`]
+    /// records the temperature for `history --compare`. They are optional so
+    /// a response without them still parses.
+    #[serde(default)]
+    pub temperature: Option<i32>,
+    #[serde(default)]
+    pub short_forecast: Option<String>,
     pub detailed_forecast: String,
+    /// An image URL for the period's conditions, used by `--output html`.
+    #[serde(default)]
+    pub icon: Option<String>,
 }
 
 #[derive(Debug, Deserialize)]
@@ -79,43 +257,642 @@ pub struct HourlyForecastResponse {
 
 #[derive(Debug, Deserialize)]
 pub struct HourlyForecastProperties {
+    #[serde(flatten)]
+    pub issuance: Issuance,
     pub periods: Vec<HourlyPeriod>,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, Serialize)]
 #[serde(rename_all = "camelCase")]
 pub struct HourlyPeriod {
-    pub start_time: String,
+    /// Carries the forecast location's UTC offset; see [`crate::times`].
+    pub start_time: DateTime<FixedOffset>,
+    pub end_time: DateTime<FixedOffset>,
+    pub is_daytime: bool,
     pub temperature: i32,
     pub temperature_unit: String,
     pub wind_speed: String,
     pub wind_direction: String,
     pub short_forecast: String,
+    /// Always `wmoUnit:degC`, whatever `units` the request asked for.
+    #[serde(default)]
+    pub dewpoint: Option<QuantitativeValue>,
+    #[serde(default)]
+    pub probability_of_precipitation: Option<QuantitativeValue>,
+    #[serde(default)]
+    pub icon: Option<String>,
+}
+
+impl HourlyPeriod {
+    /// Re-express the temperature and wind speed in `units` if the server
+    /// answered in the other system.
+    ///
+    /// The API honours `?units` today, so this is normally a no-op. It is here
+    /// so the display cannot depend on that: a period that slips through in the
+    /// wrong system is converted rather than printed with a unit the user did
+    /// not ask for. A wind speed the parser does not recognise is left as sent.
+    pub fn convert_to(&mut self, units: Units) {
+        let wanted = units.temperature_unit();
+        if self.temperature_unit != wanted {
+            let t = f64::from(self.temperature);
+            let converted = match units {
+                Units::Us => crate::units::celsius_to_fahrenheit(t),
+                Units::Si => crate::units::fahrenheit_to_celsius(t),
+            };
+            self.temperature = converted.round() as i32;
+            self.temperature_unit = wanted.to_string();
+        }
+
+        if let Ok(speed) = self.wind_speed.parse::<WindSpeed>() {
+            let converted = speed.to_units(units);
+            if converted.unit != speed.unit {
+                self.wind_speed = converted.to_string();
+            }
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+pub struct AlertsResponse {
+    pub features: Vec<AlertFeature>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct AlertFeature {
+    pub properties: Alert,
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Alert {
+    /// The alert's URN, unique to this message. An update or cancellation
+    /// gets an id of its own; see [`Alert::original_id`].
+    #[serde(default)]
+    pub id: String,
+    pub event: String,
+    pub headline: Option<String>,
+    pub severity: Severity,
+    /// When the hazard begins and ends, which is not when the alert was
+    /// issued or expires. Either may be absent.
+    #[serde(default)]
+    pub onset: Option<DateTime<FixedOffset>>,
+    #[serde(default)]
+    pub ends: Option<DateTime<FixedOffset>>,
+    /// `Alert`, `Update` or `Cancel`. An update or cancellation is a new
+    /// alert, with its own id, rather than a change to the original.
+    #[serde(default)]
+    pub message_type: Option<String>,
+    /// The earlier alerts an update or cancellation replaces.
+    #[serde(default)]
+    pub references: Vec<AlertReference>,
+}
+
+/// An earlier alert named in [`Alert::references`].
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct AlertReference {
+    /// The earlier alert's [`Alert::id`].
+    pub identifier: String,
+    #[serde(default)]
+    pub sent: Option<DateTime<FixedOffset>>,
+}
+
+/// The alert events issued for marine zones, from the NWS list of VTEC
+/// products. `Dense Fog Advisory` and a few others are issued over land too,
+/// and are left out.
+pub const MARINE_EVENTS: &[&str] = &[
+    "Brisk Wind Advisory",
+    "Freezing Spray Advisory",
+    "Gale Warning",
+    "Gale Watch",
+    "Hazardous Seas Warning",
+    "Hazardous Seas Watch",
+    "Heavy Freezing Spray Warning",
+    "Heavy Freezing Spray Watch",
+    "Hurricane Force Wind Warning",
+    "Hurricane Force Wind Watch",
+    "Low Water Advisory",
+    "Marine Weather Statement",
+    "Small Craft Advisory",
+    "Special Marine Warning",
+    "Storm Warning",
+    "Storm Watch",
+];
+
+/// The alert events that say fire weather is dangerous now or soon.
+pub const FIRE_EVENTS: &[&str] = &["Fire Weather Watch", "Red Flag Warning"];
+
+impl Alert {
+    /// Whether this is one of the [`MARINE_EVENTS`].
+    pub fn is_marine(&self) -> bool {
+        MARINE_EVENTS.contains(&self.event.as_str())
+    }
+
+    /// Whether this is one of the [`FIRE_EVENTS`].
+    pub fn is_fire(&self) -> bool {
+        FIRE_EVENTS.contains(&self.event.as_str())
+    }
+
+    /// The id of the alert this one began as: the earliest sent of those it
+    /// references, or its own id if it references none. Every update to an
+    /// alert shares it, so it identifies the alert across updates.
+    pub fn original_id(&self) -> &str {
+        self.references
+            .iter()
+            .min_by_key(|r| (r.sent.is_none(), r.sent))
+            .map_or(&self.id, |r| &r.identifier)
+    }
+
+    /// Whether `self` and `other` are the same alert: they began as the same
+    /// alert, or one replaces the other. Without ids, the event name is the
+    /// best available identity.
+    pub fn same_as(&self, other: &Alert) -> bool {
+        if self.id.is_empty() || other.id.is_empty() {
+            return self.event == other.event;
+        }
+        let replaces = |x: &Alert, y: &Alert| x.references.iter().any(|r| r.identifier == y.id);
+        self.original_id() == other.original_id() || replaces(self, other) || replaces(other, self)
+    }
+}
+
+/// CAP severity. Ordered so the most severe alert compares greatest, with
+/// `Unknown` below `Minor`.
+#[derive(Clone, Copy, Debug, Deserialize, Serialize, PartialEq, Eq, ValueEnum)]
+pub enum Severity {
+    Extreme,
+    Severe,
+    Moderate,
+    Minor,
+    /// Also used for any value the CAP spec adds later, rather than failing
+    /// the whole alert list over one unrecognised string.
+    #[serde(other)]
+    Unknown,
+}
+
+impl Severity {
+    /// Every severity, most severe first.
+    pub const ALL: [Severity; 5] = [
+        Severity::Extreme,
+        Severity::Severe,
+        Severity::Moderate,
+        Severity::Minor,
+        Severity::Unknown,
+    ];
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Severity::Extreme => "extreme",
+            Severity::Severe => "severe",
+            Severity::Moderate => "moderate",
+            Severity::Minor => "minor",
+            Severity::Unknown => "unknown",
+        }
+    }
+
+    fn rank(self) -> u8 {
+        match self {
+            Severity::Unknown => 0,
+            Severity::Minor => 1,
+            Severity::Moderate => 2,
+            Severity::Severe => 3,
+            Severity::Extreme => 4,
+        }
+    }
+}
+
+impl Ord for Severity {
+    fn cmp(&self, other: &Self) -> std::cmp::Ordering {
+        self.rank().cmp(&other.rank())
+    }
+}
+
+impl PartialOrd for Severity {
+    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct ProductList {
+    #[serde(rename = "@graph")]
+    products: Vec<ProductSummary>,
+}
+
+#[derive(Debug, Deserialize)]
+struct ProductSummary {
+    id: String,
+}
+
+/// An issued text product.
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Product {
+    /// The issuing office's ICAO id, e.g. `KSEW`.
+    pub issuing_office: String,
+    pub issuance_time: DateTime<FixedOffset>,
+    pub product_name: String,
+    pub product_text: String,
+}
+
+/// The kinds of zone `--zone` and `wxdotgov zones` work with.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, ValueEnum)]
+pub enum ZoneType {
+    /// Public forecast zones, e.g. `WAZ558`: what zone forecasts are for.
+    Forecast,
+    County,
+    /// Fire weather zones, which Red Flag Warnings are issued for.
+    Fire,
+    /// Marine zones near shore, e.g. `PZZ135` for Puget Sound.
+    Coastal,
+    /// Marine zones beyond the coastal waters, e.g. `PZZ800`.
+    Offshore,
+}
+
+impl ZoneType {
+    /// The name in API paths and the `type` query parameter.
+    pub fn as_str(self) -> &'static str {
+        match self {
+            ZoneType::Forecast => "forecast",
+            ZoneType::County => "county",
+            ZoneType::Fire => "fire",
+            ZoneType::Coastal => "coastal",
+            ZoneType::Offshore => "offshore",
+        }
+    }
+
+    /// Whether the zone is water, with its forecast in knots and feet.
+    pub fn is_marine(self) -> bool {
+        matches!(self, ZoneType::Coastal | ZoneType::Offshore)
+    }
+
+    /// Whether `/zones/{type}/{id}/forecast` has anything for this type.
+    pub fn has_forecast(self) -> bool {
+        matches!(
+            self,
+            ZoneType::Forecast | ZoneType::Coastal | ZoneType::Offshore
+        )
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct ZonesResponse {
+    features: Vec<ZoneFeature>,
+}
+
+#[derive(Debug, Deserialize)]
+struct ZoneFeature {
+    properties: Zone,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct Zone {
+    pub id: String,
+    /// A description of the area, e.g. `City of Seattle`.
+    pub name: String,
+    /// As the API names it: `public` for a forecast zone, otherwise as in
+    /// [`ZoneType`]. See [`Zone::zone_type`].
+    #[serde(rename = "type", default)]
+    pub kind: String,
+}
+
+impl Zone {
+    /// The zone's type, or `None` for kinds this tool has no use for.
+    pub fn zone_type(&self) -> Option<ZoneType> {
+        match self.kind.as_str() {
+            "public" | "forecast" => Some(ZoneType::Forecast),
+            "county" => Some(ZoneType::County),
+            "fire" => Some(ZoneType::Fire),
+            "coastal" => Some(ZoneType::Coastal),
+            "offshore" => Some(ZoneType::Offshore),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct ZoneForecastResponse {
+    properties: ZoneForecast,
+}
+
+/// The forecast for a whole zone. Its periods are narrative only: there are
+/// no times, temperatures or icons, just a name and the text.
+#[derive(Debug, Deserialize)]
+pub struct ZoneForecast {
+    pub periods: Vec<ZonePeriod>,
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ZonePeriod {
+    pub name: String,
+    pub detailed_forecast: String,
+}
+
+/// A zone ID from the command line, e.g. `WAZ558` or `waz558`: a two-letter
+/// state or marine area, a `Z` or `C`, and three digits.
+pub fn parse_zone(s: &str) -> Result<String> {
+    let id = s.trim().to_ascii_uppercase();
+    let bytes = id.as_bytes();
+    let valid = bytes.len() == 6
+        && bytes[..2].iter().all(u8::is_ascii_uppercase)
+        && matches!(bytes[2], b'Z' | b'C')
+        && bytes[3..].iter().all(u8::is_ascii_digit);
+    if !valid {
+        bail!("'{s}' is not a zone ID like WAZ558; find one with `wxdotgov zones search`");
+    }
+    Ok(id)
+}
+
+/// A forecast office, from `/offices/{id}`.
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Office {
+    pub id: String,
+    /// Where the office is, e.g. `Seattle, WA`.
+    pub name: String,
+    pub address: Option<OfficeAddress>,
+    pub telephone: Option<String>,
+    pub email: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct OfficeAddress {
+    pub street_address: String,
+    pub address_locality: String,
+    pub address_region: String,
+    pub postal_code: String,
+}
+
+/// One element of a grid square's raw data, as a time series.
+#[derive(Clone, Debug, Default, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct GridLayer {
+    /// A WMO unit code, e.g. `wmoUnit:percent`. Indices have none.
+    pub uom: Option<String>,
+    pub values: Vec<GridValue>,
+}
+
+#[derive(Clone, Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GridValue {
+    /// `2024-01-29T02:00:00+00:00/PT3H`; see [`parse_valid_time`].
+    pub valid_time: String,
+    pub value: Option<f64>,
+}
+
+impl GridLayer {
+    /// The greatest value in force at any time within `[start, end)`, or the
+    /// least unless `highest`, with when it takes effect (no earlier than
+    /// `start`). `None` if the layer has no value in the window.
+    pub fn extreme(
+        &self,
+        start: DateTime<FixedOffset>,
+        end: DateTime<FixedOffset>,
+        highest: bool,
+    ) -> Option<(DateTime<FixedOffset>, QuantitativeValue)> {
+        let mut best: Option<(DateTime<FixedOffset>, f64)> = None;
+        for value in &self.values {
+            let (Some((from, until)), Some(v)) = (parse_valid_time(&value.valid_time), value.value)
+            else {
+                continue;
+            };
+            if until <= start || from >= end {
+                continue;
+            }
+            let better = best.is_none_or(|(_, b)| if highest { v > b } else { v < b });
+            if better {
+                best = Some((from.max(start), v));
+            }
+        }
+        best.map(|(at, value)| {
+            let unit_code = self.uom.clone().unwrap_or_default();
+            (
+                at,
+                QuantitativeValue {
+                    value: Some(value),
+                    unit_code,
+                },
+            )
+        })
+    }
+}
+
+/// The fire weather elements of a grid square's raw data.
+#[derive(Clone, Debug, Default, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct FireWeather {
+    pub relative_humidity: GridLayer,
+    pub wind_gust: GridLayer,
+    /// How high smoke and heat mix into the atmosphere.
+    pub mixing_height: GridLayer,
+    /// The mean wind through the mixed layer, which carries smoke away.
+    pub transport_wind_speed: GridLayer,
+    /// Lower-atmosphere stability and dryness, 2 (very low) to 6 (high).
+    pub haines_index: GridLayer,
+    /// 0 to 10; higher is closer to Red Flag criteria.
+    pub red_flag_threat_index: GridLayer,
+}
+
+/// An observation station, e.g. `KSEA`.
+#[derive(Clone, Debug, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Station {
+    pub station_identifier: String,
+    pub name: String,
+    /// Latitude and longitude, from the feature's geometry rather than its
+    /// properties.
+    #[serde(skip)]
+    pub position: Option<(f64, f64)>,
+}
+
+#[derive(Debug, Deserialize)]
+struct StationsResponse {
+    features: Vec<StationFeature>,
+}
+
+#[derive(Debug, Deserialize)]
+struct StationFeature {
+    #[serde(default)]
+    geometry: Option<PointGeometry>,
+    properties: Station,
+}
+
+/// A GeoJSON point: `[longitude, latitude]`, longitude first.
+#[derive(Debug, Deserialize)]
+struct PointGeometry {
+    coordinates: Vec<f64>,
+}
+
+impl StationFeature {
+    fn into_station(self) -> Station {
+        let position = self.geometry.and_then(|g| match g.coordinates[..] {
+            [lon, lat, ..] => Some((lat, lon)),
+            _ => None,
+        });
+        Station {
+            position,
+            ..self.properties
+        }
+    }
+}
+
+/// What a station measured at one time. Values are SI, and any may be
+/// missing when an instrument reported nothing.
+#[derive(Clone, Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Observation {
+    pub timestamp: DateTime<FixedOffset>,
+    /// e.g. "Mostly Cloudy"; sometimes empty rather than absent.
+    #[serde(default)]
+    pub text_description: Option<String>,
+    #[serde(default)]
+    pub temperature: Option<QuantitativeValue>,
+    #[serde(default)]
+    pub dewpoint: Option<QuantitativeValue>,
+    #[serde(default)]
+    pub wind_speed: Option<QuantitativeValue>,
+    #[serde(default)]
+    pub relative_humidity: Option<QuantitativeValue>,
+}
+
+#[derive(Debug, Deserialize)]
+struct ObservationsResponse {
+    features: Vec<ObservationFeature>,
+}
+
+#[derive(Debug, Deserialize)]
+struct ObservationFeature {
+    properties: Observation,
+}
+
+#[derive(Debug, Deserialize)]
+struct GridpointResponse {
+    properties: FireWeather,
 }
 
 pub const DEFAULT_BASE_URL: &str = "https://api.weather.gov";
 
+/// How long a response may be served from [`crate::cache`]. A grid point
+/// does not move; forecasts are reissued about hourly; alerts can appear at
+/// any moment and are cheap to ask for.
+const POINTS_MAX_AGE: Duration = Duration::from_secs(24 * 60 * 60);
+const FORECAST_MAX_AGE: Duration = Duration::from_secs(10 * 60);
+const ALERTS_MAX_AGE: Duration = Duration::from_secs(60);
+/// Zones are redrawn a few times a year.
+const ZONE_MAX_AGE: Duration = Duration::from_secs(24 * 60 * 60);
+/// Stations are added and retired about as often as zones are redrawn.
+const STATIONS_MAX_AGE: Duration = Duration::from_secs(24 * 60 * 60);
+/// Most stations report hourly, some every few minutes.
+const OBSERVATIONS_MAX_AGE: Duration = Duration::from_secs(5 * 60);
+/// An office moves about as often as a grid point.
+const OFFICE_MAX_AGE: Duration = Duration::from_secs(24 * 60 * 60);
+/// A product is never changed once issued; a correction is a new product.
+const PRODUCT_MAX_AGE: Duration = Duration::from_secs(24 * 60 * 60);
+
 /// GET `url` and deserialize the GeoJSON body into `T`.
 ///
-/// The three endpoints below are the same request shape differing only in the
-/// type they decode and the noun they use in errors, so they share one
+/// The endpoints below are the same request shape differing only in the type
+/// they decode and the noun they use in errors, so they share one
 /// implementation. `what` names the request in messages ("points data",
-/// "forecast", "hourly forecast").
-async fn get_geojson<T: DeserializeOwned>(url: &str, what: &str) -> Result<T> {
+/// "forecast", "hourly forecast"). Inside a cache scope, a body younger than
+/// `max_age` is reused instead of fetched.
+async fn get_geojson<T: DeserializeOwned>(
+    url: &str,
+    query: &[(&str, &str)],
+    what: &str,
+    max_age: Duration,
+) -> Result<T> {
+    get_json(url, query, "application/geo+json", what, max_age).await
+}
+
+/// [`get_geojson`] for any JSON `accept` type, for the endpoints that do not
+/// serve GeoJSON.
+async fn get_json<T: DeserializeOwned>(
+    url: &str,
+    query: &[(&str, &str)],
+    accept: &str,
+    what: &str,
+    max_age: Duration,
+) -> Result<T> {
+    let key = cache::key(url, query);
+    let body = match cache::lookup(&key, max_age) {
+        Some(body) => body,
+        None => {
+            let stale = cache::lookup_stale(&key);
+            let refresh = match &stale {
+                _ if cache::reloading() => Refresh::Reload,
+                Some((_, validators)) => Refresh::IfChanged(validators),
+                None => Refresh::Plain,
+            };
+            let (body, validators) = match fetch_json(url, query, accept, what, refresh).await? {
+                Some(fetched) => fetched,
+                // Unchanged upstream: the stale copy is current again, and is
+                // stored afresh so it is not revalidated on every lookup.
+                None => stale.with_context(|| {
+                    format!("Weather.gov answered an unconditional {what} request with 304")
+                })?,
+            };
+            cache::store_validated(&key, &body, validators);
+            body
+        }
+    };
+    serde_json::from_str(&body)
+        .with_context(|| format!("Error parsing JSON from Weather.gov {what} response"))
+}
+
+/// How a fetch treats copies already held, here or in a CDN on the way.
+enum Refresh<'a> {
+    Plain,
+    /// Ask whether the body these validators came with has changed.
+    IfChanged(&'a Validators),
+    /// Ask every cache on the way for a response from the origin.
+    Reload,
+}
+
+/// The network half of [`get_json`]: the body of a successful response and
+/// its validators, or `None` when an [`Refresh::IfChanged`] body is still
+/// current (HTTP 304).
+async fn fetch_json(
+    url: &str,
+    query: &[(&str, &str)],
+    accept: &str,
+    what: &str,
+    refresh: Refresh<'_>,
+) -> Result<Option<(String, Validators)>> {
     let client = http::client()?;
 
-    let response = client
+    let mut request = client
         .get(url)
-        // GeoJSON is what the API serves by default, but asking for it
-        // explicitly on every request keeps the format from being an
+        .query(query)
+        // Each endpoint's format is what the API serves by default, but asking
+        // for it explicitly on every request keeps the format from being an
         // undeclared dependency on that default. This header was previously
         // sent on /points only.
-        .header("Accept", "application/geo+json")
-        .send()
+        .header("Accept", accept);
+    match refresh {
+        Refresh::Plain => {}
+        Refresh::IfChanged(validators) => {
+            if let Some(etag) = &validators.etag {
+                request = request.header(IF_NONE_MATCH, etag);
+            }
+            if let Some(last_modified) = &validators.last_modified {
+                request = request.header(IF_MODIFIED_SINCE, last_modified);
+            }
+        }
+        Refresh::Reload => {
+            request = request
+                .header(CACHE_CONTROL, "no-cache")
+                .header(PRAGMA, "no-cache");
+        }
+    }
+    let response = http::send(http::Upstream::Nws, request)
         .await
         .with_context(|| format!("Error sending request to Weather.gov for {what}"))?;
 
     let status = response.status();
+    if status == StatusCode::NOT_MODIFIED {
+        return Ok(None);
+    }
     if !status.is_success() {
         // Bounded by `error_body`: an error page or proxy interstitial from
         // upstream used to be interpolated whole into this message.
@@ -123,10 +900,22 @@ async fn get_geojson<T: DeserializeOwned>(url: &str, what: &str) -> Result<T> {
         bail!("Weather.gov returned an error for {what} (HTTP {status}): {body}");
     }
 
-    response
-        .json()
+    let header = |name| {
+        response
+            .headers()
+            .get(name)
+            .and_then(|value| value.to_str().ok())
+            .map(str::to_string)
+    };
+    let validators = Validators {
+        etag: header(ETAG),
+        last_modified: header(LAST_MODIFIED),
+    };
+    let body = response
+        .text()
         .await
-        .with_context(|| format!("Error parsing JSON from Weather.gov {what} response"))
+        .with_context(|| format!("Error reading Weather.gov {what} response"))?;
+    Ok(Some((body, validators)))
 }
 
 pub async fn get_weather_point(
@@ -137,15 +926,481 @@ pub async fn get_weather_point(
     let base_url = base_url.unwrap_or(DEFAULT_BASE_URL);
     get_geojson(
         &format!("{base_url}/points/{latitude},{longitude}"),
+        &[],
         "points data",
+        POINTS_MAX_AGE,
+    )
+    .await
+}
+
+pub async fn get_detailed_forecast(forecast_url: &str, units: Units) -> Result<ForecastResponse> {
+    get_geojson(
+        forecast_url,
+        &[("units", units.as_query())],
+        "forecast",
+        FORECAST_MAX_AGE,
     )
     .await
 }
 
-pub async fn get_detailed_forecast(forecast_url: &str) -> Result<ForecastResponse> {
-    get_geojson(forecast_url, "forecast").await
+/// Fetch the hourly forecast, with every period converted to `units`.
+pub async fn get_hourly_forecast(
+    forecast_url: &str,
+    units: Units,
+) -> Result<HourlyForecastResponse> {
+    let mut response: HourlyForecastResponse = get_geojson(
+        forecast_url,
+        &[("units", units.as_query())],
+        "hourly forecast",
+        FORECAST_MAX_AGE,
+    )
+    .await?;
+    for period in &mut response.properties.periods {
+        period.convert_to(units);
+    }
+    Ok(response)
+}
+
+/// What to do about a forecast that is older than a [`StaleCheck`] allows,
+/// or has no periods at all.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, ValueEnum)]
+pub enum OnStale {
+    /// Use it, with a warning.
+    Warn,
+    /// Fetch it once more, asking every cache on the way for a fresh copy.
+    Retry,
+    /// Retry, then fall back to the last good copy from the cache.
+    Fallback,
+}
+
+pub struct StaleCheck {
+    /// Older than this, by [`Issuance::as_of`], is stale.
+    pub max_age: Duration,
+    pub on_stale: OnStale,
+}
+
+/// What is wrong with a forecast.
+#[derive(Debug, PartialEq, Eq)]
+pub enum Staleness {
+    Empty,
+    Old(DateTime<FixedOffset>),
+}
+
+impl std::fmt::Display for Staleness {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Staleness::Empty => write!(f, "the forecast has no periods"),
+            Staleness::Old(as_of) => write!(
+                f,
+                "the forecast is stale as of {}",
+                as_of.with_timezone(&Utc).format("%Y-%m-%d %H:%M UTC")
+            ),
+        }
+    }
+}
+
+/// The two forecast responses, as far as checking them for staleness goes.
+pub trait Issued: DeserializeOwned {
+    fn issuance(&self) -> &Issuance;
+    fn is_empty(&self) -> bool;
+}
+
+impl Issued for ForecastResponse {
+    fn issuance(&self) -> &Issuance {
+        &self.properties.issuance
+    }
+    fn is_empty(&self) -> bool {
+        self.properties.periods.is_empty()
+    }
+}
+
+impl Issued for HourlyForecastResponse {
+    fn issuance(&self) -> &Issuance {
+        &self.properties.issuance
+    }
+    fn is_empty(&self) -> bool {
+        self.properties.periods.is_empty()
+    }
 }
 
-pub async fn get_hourly_forecast(forecast_url: &str) -> Result<HourlyForecastResponse> {
-    get_geojson(forecast_url, "hourly forecast").await
+impl StaleCheck {
+    /// What is wrong with `forecast` at `now`, if anything. A forecast that
+    /// does not say when it was made is taken at its word.
+    pub fn staleness<T: Issued>(&self, forecast: &T, now: DateTime<Utc>) -> Option<Staleness> {
+        if forecast.is_empty() {
+            return Some(Staleness::Empty);
+        }
+        let as_of = forecast.issuance().as_of()?;
+        let age = (now - as_of.with_timezone(&Utc))
+            .to_std()
+            .unwrap_or_default();
+        (age > self.max_age).then_some(Staleness::Old(as_of))
+    }
+}
+
+/// A forecast, and the warning to show with it when it is stale.
+pub struct Checked<T> {
+    pub forecast: T,
+    pub warning: Option<String>,
+}
+
+/// Fetch a forecast and check it against `check`.
+///
+/// Every good forecast is also cached under a second key, where the next
+/// stale one leaves it alone; that copy is what [`OnStale::Fallback`] shows.
+async fn get_checked<T: Issued>(
+    url: &str,
+    query: &[(&str, &str)],
+    what: &str,
+    check: &StaleCheck,
+) -> Result<Checked<T>> {
+    let key = cache::key(url, query);
+    let good_key = last_good_key(&key);
+    let remember_good = || {
+        if let Some((body, _)) = cache::lookup_stale(&key) {
+            cache::store(&good_key, &body);
+        }
+    };
+
+    let mut forecast: T = get_geojson(url, query, what, FORECAST_MAX_AGE).await?;
+    let Some(mut staleness) = check.staleness(&forecast, Utc::now()) else {
+        remember_good();
+        return Ok(Checked {
+            forecast,
+            warning: None,
+        });
+    };
+
+    if check.on_stale != OnStale::Warn {
+        let accept = "application/geo+json";
+        let (body, validators) = fetch_json(url, query, accept, what, Refresh::Reload)
+            .await?
+            .context("Weather.gov answered an unconditional request with 304")?;
+        cache::store_validated(&key, &body, validators);
+        forecast = serde_json::from_str(&body)
+            .with_context(|| format!("Error parsing JSON from Weather.gov {what} response"))?;
+        match check.staleness(&forecast, Utc::now()) {
+            None => {
+                remember_good();
+                return Ok(Checked {
+                    forecast,
+                    warning: None,
+                });
+            }
+            Some(again) => staleness = again,
+        }
+    }
+
+    if check.on_stale == OnStale::Fallback {
+        let good = cache::lookup_stale(&good_key)
+            .and_then(|(body, _)| serde_json::from_str::<T>(&body).ok())
+            .filter(|good| !good.is_empty());
+        // Only a copy newer than what was just fetched is worth falling back
+        // to; an empty forecast is worse than any copy with periods.
+        let newer = |good: &T| match (&staleness, good.issuance().as_of()) {
+            (Staleness::Empty, _) => true,
+            (Staleness::Old(as_of), Some(good_as_of)) => good_as_of > *as_of,
+            (Staleness::Old(_), None) => false,
+        };
+        if let Some(good) = good.filter(newer) {
+            let note = match good.issuance().as_of() {
+                Some(as_of) => format!(
+                    "showing the last good copy, stale as of {}",
+                    as_of.with_timezone(&Utc).format("%Y-%m-%d %H:%M UTC")
+                ),
+                None => "showing the last good copy".to_string(),
+            };
+            return Ok(Checked {
+                forecast: good,
+                warning: Some(format!("{staleness}; {note}")),
+            });
+        }
+    }
+
+    Ok(Checked {
+        forecast,
+        warning: Some(staleness.to_string()),
+    })
+}
+
+/// Where [`get_checked`] keeps the last good copy of the forecast at `key`.
+fn last_good_key(key: &str) -> String {
+    format!("{key} (last good)")
+}
+
+/// [`get_detailed_forecast`], checked for staleness.
+pub async fn get_checked_forecast(
+    forecast_url: &str,
+    units: Units,
+    check: &StaleCheck,
+) -> Result<Checked<ForecastResponse>> {
+    get_checked(
+        forecast_url,
+        &[("units", units.as_query())],
+        "forecast",
+        check,
+    )
+    .await
+}
+
+/// [`get_hourly_forecast`], checked for staleness.
+pub async fn get_checked_hourly_forecast(
+    forecast_url: &str,
+    units: Units,
+    check: &StaleCheck,
+) -> Result<Checked<HourlyForecastResponse>> {
+    let mut checked: Checked<HourlyForecastResponse> = get_checked(
+        forecast_url,
+        &[("units", units.as_query())],
+        "hourly forecast",
+        check,
+    )
+    .await?;
+    for period in &mut checked.forecast.properties.periods {
+        period.convert_to(units);
+    }
+    Ok(checked)
+}
+
+/// Fetch the alerts currently in effect for a point.
+pub async fn get_active_alerts(
+    latitude: &str,
+    longitude: &str,
+    base_url: Option<&str>,
+) -> Result<Vec<Alert>> {
+    let base_url = base_url.unwrap_or(DEFAULT_BASE_URL);
+    let point = format!("{latitude},{longitude}");
+    let response: AlertsResponse = get_geojson(
+        &format!("{base_url}/alerts/active"),
+        &[("point", &point)],
+        "active alerts",
+        ALERTS_MAX_AGE,
+    )
+    .await?;
+    Ok(response
+        .features
+        .into_iter()
+        .map(|feature| feature.properties)
+        .collect())
+}
+
+/// Fetch the alerts currently in effect for zone `zone`, e.g. `WAZ558`.
+pub async fn get_zone_alerts(zone: &str, base_url: Option<&str>) -> Result<Vec<Alert>> {
+    let base_url = base_url.unwrap_or(DEFAULT_BASE_URL);
+    let response: AlertsResponse = get_geojson(
+        &format!("{base_url}/alerts/active/zone/{zone}"),
+        &[],
+        "zone alerts",
+        ALERTS_MAX_AGE,
+    )
+    .await?;
+    Ok(response
+        .features
+        .into_iter()
+        .map(|feature| feature.properties)
+        .collect())
+}
+
+/// Look up zone `id` of whatever type, e.g. `WAZ558` or `PZZ135`.
+pub async fn find_zone(id: &str, base_url: Option<&str>) -> Result<Zone> {
+    let base_url = base_url.unwrap_or(DEFAULT_BASE_URL);
+    let response: ZonesResponse = get_geojson(
+        &format!("{base_url}/zones"),
+        &[("id", id)],
+        "zone",
+        ZONE_MAX_AGE,
+    )
+    .await?;
+    response
+        .features
+        .into_iter()
+        .map(|feature| feature.properties)
+        .next()
+        .with_context(|| format!("No zone {id}; find one with `wxdotgov zones search`"))
+}
+
+/// The marine zone a point on the water lies in, where `/points` has nothing
+/// to say.
+pub async fn find_marine_zone(
+    latitude: &str,
+    longitude: &str,
+    base_url: Option<&str>,
+) -> Result<Option<Zone>> {
+    let base_url = base_url.unwrap_or(DEFAULT_BASE_URL);
+    let point = format!("{latitude},{longitude}");
+    let response: ZonesResponse = get_geojson(
+        &format!("{base_url}/zones"),
+        &[("point", &point)],
+        "zones",
+        ZONE_MAX_AGE,
+    )
+    .await?;
+    Ok(response
+        .features
+        .into_iter()
+        .map(|feature| feature.properties)
+        .find(|zone| zone.zone_type().is_some_and(ZoneType::is_marine)))
+}
+
+/// The zones of `zone_type` in `area`, a state or marine area code such as
+/// `WA`.
+pub async fn search_zones(
+    zone_type: ZoneType,
+    area: &str,
+    base_url: Option<&str>,
+) -> Result<Vec<Zone>> {
+    let base_url = base_url.unwrap_or(DEFAULT_BASE_URL);
+    let response: ZonesResponse = get_geojson(
+        &format!("{base_url}/zones"),
+        &[("type", zone_type.as_str()), ("area", area)],
+        "zones",
+        ZONE_MAX_AGE,
+    )
+    .await?;
+    Ok(response
+        .features
+        .into_iter()
+        .map(|feature| feature.properties)
+        .collect())
+}
+
+/// Fetch the forecast for zone `zone`, a public forecast or marine zone.
+pub async fn get_zone_forecast(
+    zone_type: ZoneType,
+    zone: &str,
+    base_url: Option<&str>,
+) -> Result<ZoneForecast> {
+    let base_url = base_url.unwrap_or(DEFAULT_BASE_URL);
+    let response: ZoneForecastResponse = get_geojson(
+        &format!("{base_url}/zones/{}/{zone}/forecast", zone_type.as_str()),
+        &[],
+        "zone forecast",
+        FORECAST_MAX_AGE,
+    )
+    .await?;
+    Ok(response.properties)
+}
+
+/// Fetch the fire weather elements of `point`'s grid square.
+pub async fn get_fire_weather(
+    point: &PointsProperties,
+    base_url: Option<&str>,
+) -> Result<FireWeather> {
+    let base_url = base_url.unwrap_or(DEFAULT_BASE_URL);
+    let response: GridpointResponse = get_geojson(
+        &format!(
+            "{base_url}/gridpoints/{}/{},{}",
+            point.grid_id, point.grid_x, point.grid_y
+        ),
+        &[],
+        "gridpoint data",
+        FORECAST_MAX_AGE,
+    )
+    .await?;
+    Ok(response.properties)
+}
+
+/// Fetch the observation stations at `stations_url`, the points data's
+/// `observationStations`, nearest first.
+pub async fn get_stations(stations_url: &str) -> Result<Vec<Station>> {
+    let response: StationsResponse =
+        get_geojson(stations_url, &[], "observation stations", STATIONS_MAX_AGE).await?;
+    Ok(response
+        .features
+        .into_iter()
+        .map(StationFeature::into_station)
+        .collect())
+}
+
+/// Fetch station `station`, e.g. `KSEA`.
+pub async fn get_station(station: &str, base_url: Option<&str>) -> Result<Station> {
+    let base_url = base_url.unwrap_or(DEFAULT_BASE_URL);
+    let feature: StationFeature = get_geojson(
+        &format!("{base_url}/stations/{station}"),
+        &[],
+        "observation station",
+        STATIONS_MAX_AGE,
+    )
+    .await?;
+    Ok(feature.into_station())
+}
+
+/// Fetch the latest observation from station `station`.
+pub async fn get_latest_observation(station: &str, base_url: Option<&str>) -> Result<Observation> {
+    let base_url = base_url.unwrap_or(DEFAULT_BASE_URL);
+    let feature: ObservationFeature = get_geojson(
+        &format!("{base_url}/stations/{station}/observations/latest"),
+        &[],
+        "latest observation",
+        OBSERVATIONS_MAX_AGE,
+    )
+    .await?;
+    Ok(feature.properties)
+}
+
+/// Fetch what station `station` observed between `start` and `end`, oldest
+/// first.
+pub async fn get_observations(
+    station: &str,
+    start: DateTime<Utc>,
+    end: DateTime<Utc>,
+    base_url: Option<&str>,
+) -> Result<Vec<Observation>> {
+    let base_url = base_url.unwrap_or(DEFAULT_BASE_URL);
+    let (start, end) = (
+        start.to_rfc3339_opts(SecondsFormat::Secs, true),
+        end.to_rfc3339_opts(SecondsFormat::Secs, true),
+    );
+    let response: ObservationsResponse = get_geojson(
+        &format!("{base_url}/stations/{station}/observations"),
+        &[("start", &start), ("end", &end)],
+        "observations",
+        OBSERVATIONS_MAX_AGE,
+    )
+    .await?;
+    let mut observations: Vec<Observation> = response
+        .features
+        .into_iter()
+        .map(|feature| feature.properties)
+        .collect();
+    observations.sort_by_key(|observation| observation.timestamp);
+    Ok(observations)
+}
+
+/// Fetch forecast office `office`, e.g. `SEW`.
+pub async fn get_office(office: &str, base_url: Option<&str>) -> Result<Office> {
+    let base_url = base_url.unwrap_or(DEFAULT_BASE_URL);
+    get_json(
+        &format!("{base_url}/offices/{office}"),
+        &[],
+        "application/ld+json",
+        "forecast office",
+        OFFICE_MAX_AGE,
+    )
+    .await
+}
+
+/// Fetch the latest Area Forecast Discussion from forecast office `office`,
+/// the points data's `gridId`.
+pub async fn get_discussion(office: &str, base_url: Option<&str>) -> Result<Product> {
+    let base_url = base_url.unwrap_or(DEFAULT_BASE_URL);
+    let list: ProductList = get_json(
+        &format!("{base_url}/products/types/AFD/locations/{office}"),
+        &[],
+        "application/ld+json",
+        "forecast discussions",
+        FORECAST_MAX_AGE,
+    )
+    .await?;
+    let Some(latest) = list.products.first() else {
+        bail!("Weather.gov has no forecast discussion from office {office}");
+    };
+    get_json(
+        &format!("{base_url}/products/{}", latest.id),
+        &[],
+        "application/ld+json",
+        "forecast discussion",
+        PRODUCT_MAX_AGE,
+    )
+    .await
 }
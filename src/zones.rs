@@ -0,0 +1,65 @@
+//! `wxdotgov zones`: finding the zone IDs `--zone` takes.
+//!
+//! NWS issues zone forecasts and most alerts by zone rather than by point, and
+//! a zone ID such as `WAZ558` is not something anyone knows offhand. `zones
+//! search` lists the zones in a state or marine area, optionally narrowed by
+//! name:
+//!
+//! ```text
+//! $ wxdotgov zones search --area WA --name seattle
+//! WAZ558  City of Seattle
+//! ```
+
+use anyhow::{bail, Result};
+
+use crate::weatherdotgov::{search_zones, ZoneType};
+
+#[derive(Debug, clap::Args)]
+pub struct ZonesArgs {
+    #[command(subcommand)]
+    command: ZonesCommand,
+}
+
+#[derive(Debug, clap::Subcommand)]
+enum ZonesCommand {
+    /// List the zones in a state or marine area.
+    Search(SearchArgs),
+}
+
+#[derive(Debug, clap::Args)]
+struct SearchArgs {
+    /// Two-letter state or marine area code, e.g. WA.
+    #[arg(long)]
+    area: String,
+
+    /// Only list zones whose name contains this, ignoring case.
+    #[arg(long)]
+    name: Option<String>,
+
+    /// Which kind of zone to list.
+    #[arg(long = "type", value_enum, default_value_t = ZoneType::Forecast)]
+    zone_type: ZoneType,
+}
+
+pub async fn run(args: ZonesArgs) -> Result<()> {
+    match args.command {
+        ZonesCommand::Search(search) => print_search(search).await,
+    }
+}
+
+async fn print_search(args: SearchArgs) -> Result<()> {
+    let area = args.area.trim().to_ascii_uppercase();
+    let mut zones = search_zones(args.zone_type, &area, None).await?;
+    if let Some(name) = &args.name {
+        let name = name.to_lowercase();
+        zones.retain(|zone| zone.name.to_lowercase().contains(&name));
+    }
+    if zones.is_empty() {
+        bail!("No {} zones found in {area}", args.zone_type.as_str());
+    }
+    zones.sort_by(|a, b| a.id.cmp(&b.id));
+    for zone in zones {
+        println!("{}  {}", zone.id, zone.name);
+    }
+    Ok(())
+}